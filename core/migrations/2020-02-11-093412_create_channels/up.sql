@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS channels (
+  id INTEGER PRIMARY KEY,
+  channel TEXT UNIQUE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS channel_links (
+  id INTEGER PRIMARY KEY,
+  link_from_key_id INTEGER NOT NULL,
+  link_to_channel_id INTEGER NOT NULL,
+  UNIQUE(link_from_key_id, link_to_channel_id)
+);
+CREATE INDEX IF NOT EXISTS channel_links_to_id_index ON channel_links (link_to_channel_id, link_from_key_id);
+CREATE INDEX IF NOT EXISTS channel_links_from_id_index ON channel_links (link_from_key_id, link_to_channel_id);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS channel_subscriptions (
+  id INTEGER PRIMARY KEY,
+  author_id INTEGER NOT NULL,
+  channel_id INTEGER NOT NULL,
+  subscribed BOOLEAN NOT NULL,
+  UNIQUE(author_id, channel_id)
+);
+CREATE INDEX IF NOT EXISTS channel_subscriptions_channel_id_index ON channel_subscriptions (channel_id, subscribed);
@@ -0,0 +1 @@
+CREATE VIRTUAL TABLE author_texts USING FTS5(name, description);
@@ -0,0 +1,26 @@
+-- This file should undo anything in `up.sql`
+-- Sqlite can't drop columns, so recreate the tables as they were.
+DROP TABLE links;
+DROP TABLE mentions;
+DROP TABLE blob_links;
+CREATE TABLE IF NOT EXISTS links (
+  id INTEGER PRIMARY KEY,
+  link_from_key_id INTEGER NOT NULL,
+  link_to_key_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS links_to_id_index ON links (link_to_key_id, link_from_key_id);
+CREATE INDEX IF NOT EXISTS links_from_id_index ON links (link_from_key_id, link_to_key_id);
+CREATE TABLE IF NOT EXISTS mentions (
+  id INTEGER PRIMARY KEY,
+  link_from_key_id INTEGER NOT NULL,
+  link_to_author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS mentions_id_to_index ON mentions (link_to_author_id, link_from_key_id);
+CREATE INDEX IF NOT EXISTS mentions_id_from_index ON mentions (link_from_key_id, link_to_author_id);
+CREATE TABLE IF NOT EXISTS blob_links (
+  id INTEGER PRIMARY KEY,
+  link_from_key_id INTEGER NOT NULL,
+  link_to_blob_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS blob_links_index_to ON blob_links(link_to_blob_id);
+CREATE INDEX IF NOT EXISTS blob_links_index_from ON blob_links(link_from_key_id);
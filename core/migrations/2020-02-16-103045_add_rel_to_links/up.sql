@@ -0,0 +1,6 @@
+ALTER TABLE links ADD COLUMN rel TEXT NOT NULL DEFAULT '';
+ALTER TABLE mentions ADD COLUMN rel TEXT NOT NULL DEFAULT '';
+ALTER TABLE blob_links ADD COLUMN rel TEXT NOT NULL DEFAULT '';
+CREATE INDEX IF NOT EXISTS links_rel_index ON links (rel, link_to_key_id);
+CREATE INDEX IF NOT EXISTS mentions_rel_index ON mentions (rel, link_to_author_id);
+CREATE INDEX IF NOT EXISTS blob_links_rel_index ON blob_links (rel, link_to_blob_id);
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+-- Sqlite can't drop columns, so recreate the table as it was.
+CREATE TABLE root_posts_old (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT,
+  key_id INTEGER UNIQUE NOT NULL,
+  author_id INTEGER NOT NULL
+);
+INSERT INTO root_posts_old SELECT flume_seq, asserted_timestamp, key_id, author_id FROM root_posts;
+DROP TABLE root_posts;
+ALTER TABLE root_posts_old RENAME TO root_posts;
+CREATE INDEX IF NOT EXISTS root_posts_timestamp_index ON root_posts(asserted_timestamp);
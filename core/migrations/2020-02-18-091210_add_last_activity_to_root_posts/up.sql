@@ -0,0 +1,6 @@
+ALTER TABLE root_posts ADD COLUMN last_activity_flume_seq BIGINT NOT NULL DEFAULT 0;
+UPDATE root_posts SET last_activity_flume_seq = MAX(
+  flume_seq,
+  IFNULL((SELECT MAX(flume_seq) FROM reply_posts WHERE reply_posts.root_post_id = root_posts.key_id), 0)
+);
+CREATE INDEX IF NOT EXISTS root_posts_last_activity_index ON root_posts(last_activity_flume_seq);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS branches_to_id_index;
+DROP INDEX IF EXISTS branches_from_id_index;
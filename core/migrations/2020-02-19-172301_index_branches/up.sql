@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS branches_to_id_index ON branches (link_to_key_id, link_from_key_id);
+CREATE INDEX IF NOT EXISTS branches_from_id_index ON branches (link_from_key_id, link_to_key_id);
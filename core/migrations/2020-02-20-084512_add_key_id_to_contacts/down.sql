@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+-- Sqlite can't drop columns, so recreate the table as it was.
+CREATE TABLE contacts_old (
+  id INTEGER PRIMARY KEY,
+  author_id INTEGER NOT NULL,
+  contact_author_id INTEGER NOT NULL,
+  is_decrypted BOOLEAN NOT NULL,
+  state INTEGER,
+  UNIQUE(author_id, contact_author_id, is_decrypted)
+);
+INSERT INTO contacts_old SELECT id, author_id, contact_author_id, is_decrypted, state FROM contacts;
+DROP TABLE contacts;
+ALTER TABLE contacts_old RENAME TO contacts;
+CREATE INDEX IF NOT EXISTS contacts_contact_author_id_state_index ON contacts(contact_author_id);
+CREATE INDEX IF NOT EXISTS contacts_author_id_state_index ON contacts(author_id, state);
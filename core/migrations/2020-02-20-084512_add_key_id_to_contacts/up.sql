@@ -0,0 +1,2 @@
+-- The latest contact message, that set the state.
+ALTER TABLE contacts ADD COLUMN key_id INTEGER NOT NULL DEFAULT 0;
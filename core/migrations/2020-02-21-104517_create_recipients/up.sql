@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS recipients (
+  id INTEGER PRIMARY KEY,
+  link_from_key_id INTEGER NOT NULL,
+  link_to_author_id INTEGER NOT NULL,
+  UNIQUE(link_from_key_id, link_to_author_id)
+);
+CREATE INDEX IF NOT EXISTS recipients_to_id_index ON recipients (link_to_author_id, link_from_key_id);
+CREATE INDEX IF NOT EXISTS recipients_from_id_index ON recipients (link_from_key_id, link_to_author_id);
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS post_edits (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  revision_root_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS post_edits_revision_root_index ON post_edits (revision_root_key_id, flume_seq);
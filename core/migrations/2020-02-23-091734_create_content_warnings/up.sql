@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS content_warnings (
+  key_id INTEGER PRIMARY KEY,
+  content_warning TEXT NOT NULL
+);
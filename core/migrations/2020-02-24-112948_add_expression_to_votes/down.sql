@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+-- Sqlite can't drop columns, so recreate the table as it was.
+CREATE TABLE votes_old (
+  id INTEGER PRIMARY KEY,
+  link_from_author_id INTEGER NOT NULL,
+  link_to_key_id INTEGER NOT NULL,
+  value INTEGER NOT NULL,
+  UNIQUE (link_from_author_id, link_to_key_id)
+);
+INSERT INTO votes_old SELECT id, link_from_author_id, link_to_key_id, value FROM votes;
+DROP TABLE votes;
+ALTER TABLE votes_old RENAME TO votes;
+CREATE INDEX IF NOT EXISTS votes_link_from_author_id_index on votes (link_from_author_id);
+CREATE INDEX IF NOT EXISTS votes_link_to_key_id_index on votes (link_to_key_id);
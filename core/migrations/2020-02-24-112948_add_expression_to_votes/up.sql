@@ -0,0 +1,3 @@
+ALTER TABLE votes ADD COLUMN link_from_key_id INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE votes ADD COLUMN asserted_timestamp BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE votes ADD COLUMN expression TEXT;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS indexer_migrations (
+  indexer TEXT NOT NULL,
+  version INTEGER NOT NULL,
+  PRIMARY KEY (indexer, version)
+);
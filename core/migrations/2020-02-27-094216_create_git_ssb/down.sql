@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE issue_edits;
+DROP TABLE pull_requests;
+DROP TABLE issues;
+DROP TABLE git_refs;
+DROP TABLE git_updates;
+DROP TABLE git_repos;
@@ -0,0 +1,56 @@
+CREATE TABLE IF NOT EXISTS git_repos (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS git_repos_key_id_index ON git_repos (key_id);
+
+CREATE TABLE IF NOT EXISTS git_updates (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  repo_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS git_updates_repo_index ON git_updates (repo_key_id, flume_seq);
+
+CREATE TABLE IF NOT EXISTS git_refs (
+  update_flume_seq BIGINT NOT NULL,
+  name TEXT NOT NULL,
+  sha1 TEXT,
+  PRIMARY KEY (update_flume_seq, name)
+);
+
+CREATE TABLE IF NOT EXISTS issues (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  repo_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS issues_repo_index ON issues (repo_key_id, flume_seq);
+CREATE INDEX IF NOT EXISTS issues_key_id_index ON issues (key_id);
+
+CREATE TABLE IF NOT EXISTS pull_requests (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  repo_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL,
+  branch TEXT,
+  head_repo_key_id INTEGER,
+  head_branch TEXT
+);
+CREATE INDEX IF NOT EXISTS pull_requests_repo_index ON pull_requests (repo_key_id, flume_seq);
+CREATE INDEX IF NOT EXISTS pull_requests_key_id_index ON pull_requests (key_id);
+
+CREATE TABLE IF NOT EXISTS issue_edits (
+  flume_seq BIGINT NOT NULL,
+  issue_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL,
+  is_open BOOLEAN,
+  title TEXT,
+  PRIMARY KEY (flume_seq, issue_key_id)
+);
+CREATE INDEX IF NOT EXISTS issue_edits_issue_index ON issue_edits (issue_key_id, flume_seq);
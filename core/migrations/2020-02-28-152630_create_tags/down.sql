@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE tag_operations;
+DROP TABLE tags;
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS tags (
+  flume_seq BIGINT PRIMARY KEY,
+  key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tags_key_id_index ON tags (key_id);
+CREATE INDEX IF NOT EXISTS tags_author_index ON tags (author_id, flume_seq);
+
+CREATE TABLE IF NOT EXISTS tag_operations (
+  flume_seq BIGINT PRIMARY KEY,
+  tag_key_id INTEGER NOT NULL,
+  message_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL,
+  tagged BOOLEAN NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tag_operations_tag_index ON tag_operations (tag_key_id, flume_seq);
+CREATE INDEX IF NOT EXISTS tag_operations_message_index ON tag_operations (message_key_id, flume_seq);
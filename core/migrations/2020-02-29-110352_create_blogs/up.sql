@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS blogs (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL,
+  title TEXT,
+  summary TEXT,
+  thumbnail TEXT,
+  blog TEXT
+);
+CREATE INDEX IF NOT EXISTS blogs_key_id_index ON blogs (key_id);
+CREATE INDEX IF NOT EXISTS blogs_author_index ON blogs (author_id, flume_seq);
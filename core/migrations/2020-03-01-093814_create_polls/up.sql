@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS polls (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL,
+  closes_at BIGINT
+);
+CREATE INDEX IF NOT EXISTS polls_key_id_index ON polls (key_id);
+
+CREATE TABLE IF NOT EXISTS positions (
+  flume_seq BIGINT PRIMARY KEY,
+  asserted_timestamp BIGINT NOT NULL,
+  key_id INTEGER NOT NULL,
+  poll_key_id INTEGER NOT NULL,
+  author_id INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS positions_poll_index ON positions (poll_key_id, flume_seq);
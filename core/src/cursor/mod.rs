@@ -1,15 +1,82 @@
 use bytes::{ByteOrder, LittleEndian};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Nullable};
+use diesel::sqlite::Sqlite;
 
-pub fn decode_cursor(encoded: &str) -> Result<i64, String> {
+pub fn encode_cursor(cursor: i64) -> String {
+    base64::encode(&(cursor as u64).to_le_bytes())
+}
+
+/// Encodes a cursor made of several values, eg. a timestamp and the flume seq to break ties
+/// between items with the same timestamp.
+pub fn encode_compound_cursor(parts: &[i64]) -> String {
+    let bytes = parts
+        .iter()
+        .flat_map(|part| (*part as u64).to_le_bytes().to_vec())
+        .collect::<Vec<u8>>();
+
+    base64::encode(&bytes)
+}
+
+/// Decodes a cursor made by `encode_cursor` or `encode_compound_cursor`, whichever number of
+/// values it has.
+pub fn decode_cursor_parts(encoded: &str) -> Result<Vec<i64>, String> {
     match base64::decode(encoded) {
-        Ok(ref bytes) if bytes.len() < 8 => {
-            Err("Error decoding cursor. Is it a valid base64 encoded i64?".to_string())
+        Ok(ref bytes) if bytes.len() < 8 || bytes.len() % 8 != 0 => {
+            Err("Error decoding cursor. Is it valid base64 encoded i64s?".to_string())
         }
-        Ok(bytes) => Ok(LittleEndian::read_i64(bytes.as_slice())),
+        Ok(bytes) => Ok(bytes.chunks(8).map(LittleEndian::read_i64).collect()),
         Err(err) => Err(err.to_string()),
     }
 }
 
-pub fn encode_cursor(cursor: i64) -> String {
-    base64::encode(&(cursor as u64).to_le_bytes())
+/// A column that results are ordered by, eg. the asserted timestamp or the flume seq.
+pub type OrderExpression<'a, QS> =
+    Box<dyn BoxableExpression<QS, Sqlite, SqlType = Nullable<BigInt>> + 'a>;
+
+type Filter<'a, QS> = Box<dyn BoxableExpression<QS, Sqlite, SqlType = Bool> + 'a>;
+
+type Order<'a, QS> = Box<dyn BoxableExpression<QS, Sqlite, SqlType = ()> + 'a>;
+
+/// Filters to the results before a compound `[value, seq]` cursor when ordered by `value`, then by
+/// `seq` to break ties. Old cursors without a seq can't break ties.
+pub fn before_cursor<'a, QS: 'a>(
+    value: impl Fn() -> OrderExpression<'a, QS>,
+    seq: impl Fn() -> OrderExpression<'a, QS>,
+    (cursor_value, cursor_seq): (i64, Option<i64>),
+) -> Filter<'a, QS> {
+    let cursor_seq = cursor_seq.unwrap_or(i64::MIN);
+    Box::new(
+        value()
+            .lt(cursor_value)
+            .or(value().eq(cursor_value).and(seq().lt(cursor_seq))),
+    )
+}
+
+/// Filters to the results after a compound `[value, seq]` cursor, like `before_cursor`.
+pub fn after_cursor<'a, QS: 'a>(
+    value: impl Fn() -> OrderExpression<'a, QS>,
+    seq: impl Fn() -> OrderExpression<'a, QS>,
+    (cursor_value, cursor_seq): (i64, Option<i64>),
+) -> Filter<'a, QS> {
+    let cursor_seq = cursor_seq.unwrap_or(i64::MAX);
+    Box::new(
+        value()
+            .gt(cursor_value)
+            .or(value().eq(cursor_value).and(seq().gt(cursor_seq))),
+    )
+}
+
+/// Orders by `value`, then by `seq` to break ties, to match `before_cursor` when `newest_first`
+/// and `after_cursor` otherwise.
+pub fn cursor_order<'a, QS: 'a>(
+    value: impl Fn() -> OrderExpression<'a, QS>,
+    seq: impl Fn() -> OrderExpression<'a, QS>,
+    newest_first: bool,
+) -> (Order<'a, QS>, Order<'a, QS>) {
+    if newest_first {
+        (Box::new(value().desc()), Box::new(seq().desc()))
+    } else {
+        (Box::new(value().asc()), Box::new(seq().asc()))
+    }
 }
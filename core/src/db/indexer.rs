@@ -0,0 +1,151 @@
+//! Indexers let apps index their own message types without changing the core crate.
+//!
+//! Register indexers with `Patchql::with_indexers` (or `Context::with_indexers`). Each indexer
+//! brings the sql migrations for its own tables and is called with every message as it's
+//! processed. To query the new tables, build a juniper `RootNode` with your own query type and
+//! run it with `Patchql::execute`. Resolvers can use `Context::connection` like the core ones do.
+
+use crate::db::schema::authors::dsl::{
+    author as authors_author, authors as authors_table, id as authors_id,
+};
+use crate::db::schema::indexer_migrations::dsl::{
+    indexer as indexer_migrations_indexer, indexer_migrations as indexer_migrations_table,
+    version as indexer_migrations_version,
+};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::schema::messages::dsl::{
+    asserted_time as messages_asserted_time, author_id as messages_author_id,
+    content as messages_content, flume_seq as messages_flume_seq, key_id as messages_key_id,
+    messages as messages_table, received_time as messages_received_time, seq as messages_seq,
+};
+use crate::ssb_message::{SsbMessage, SsbValue};
+use diesel::connection::SimpleConnection;
+use diesel::dsl::count_star;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+use serde_json::Value;
+
+/// The number of stored messages loaded at a time when a new indexer catches up.
+const REINDEX_CHUNK_SIZE: i64 = 10000;
+
+pub trait MessageIndexer: Send + Sync {
+    /// A name that's unique among the registered indexers. It's used to remember which of the
+    /// indexer's migrations have run, so it shouldn't change.
+    fn name(&self) -> &str;
+
+    /// The sql that creates and updates the indexer's tables, oldest first. Each migration runs
+    /// once, so only ever add new migrations to the end.
+    fn migrations(&self) -> &[&str];
+
+    /// Indexes a message. Called for every message as it's processed, after the core tables are
+    /// up to date. Private messages are passed in decrypted if they could be.
+    fn index(
+        &self,
+        connection: &SqliteConnection,
+        message: &SsbMessage,
+        key_id: i32,
+    ) -> Result<(), Error>;
+}
+
+/// Runs an indexer's pending migrations.
+///
+/// The first time an indexer's migrations run, it's given every message that has already been
+/// processed so it can catch up. Later migrations have to update existing rows themselves.
+pub fn run_indexer_migrations(
+    connection: &SqliteConnection,
+    indexer: &dyn MessageIndexer,
+) -> Result<(), Error> {
+    connection.transaction(|| {
+        let applied = indexer_migrations_table
+            .select(count_star())
+            .filter(indexer_migrations_indexer.eq(indexer.name()))
+            .first::<i64>(connection)?;
+
+        let migrations = indexer.migrations();
+
+        for (version, migration) in migrations.iter().enumerate().skip(applied as usize) {
+            info!(
+                "Running migration {} of indexer {}",
+                version + 1,
+                indexer.name()
+            );
+            connection.batch_execute(migration)?;
+            insert_into(indexer_migrations_table)
+                .values((
+                    indexer_migrations_indexer.eq(indexer.name()),
+                    indexer_migrations_version.eq(version as i32 + 1),
+                ))
+                .execute(connection)?;
+        }
+
+        if applied == 0 && !migrations.is_empty() {
+            reindex_stored_messages(connection, indexer)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Gives an indexer every message in the db, rebuilt from the messages table.
+fn reindex_stored_messages(
+    connection: &SqliteConnection,
+    indexer: &dyn MessageIndexer,
+) -> Result<(), Error> {
+    let mut last_seq = -1;
+
+    loop {
+        let chunk = messages_table
+            .inner_join(keys_table.on(keys_id.eq(messages_key_id.nullable())))
+            .inner_join(authors_table.on(authors_id.eq(messages_author_id.nullable())))
+            .select((
+                messages_flume_seq,
+                messages_key_id,
+                keys_key,
+                authors_author,
+                messages_seq,
+                messages_asserted_time,
+                messages_received_time,
+                messages_content,
+            ))
+            .filter(messages_flume_seq.gt(last_seq))
+            .order(messages_flume_seq.asc())
+            .limit(REINDEX_CHUNK_SIZE)
+            .load::<(
+                Option<i64>,
+                i32,
+                String,
+                String,
+                i32,
+                Option<i64>,
+                i64,
+                Option<String>,
+            )>(connection)?;
+
+        let is_last_chunk = (chunk.len() as i64) < REINDEX_CHUNK_SIZE;
+
+        for (flume_seq, key_id, key, author, seq, asserted_time, received_time, content) in chunk {
+            last_seq = flume_seq.unwrap_or(last_seq);
+
+            let message = SsbMessage {
+                key,
+                value: SsbValue {
+                    author,
+                    sequence: seq as u32,
+                    timestamp: asserted_time.unwrap_or(0) as f64,
+                    content: content
+                        .and_then(|content| serde_json::from_str(&content).ok())
+                        .unwrap_or(Value::Null),
+                },
+                timestamp: received_time as f64,
+            };
+
+            indexer.index(connection, &message, key_id)?;
+        }
+
+        if is_last_chunk {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! State that only exists on this machine and is never published, like which messages have been
+//! read.
+//!
+//! It lives in its own sqlite file, attached to every connection as the `local` schema, so it
+//! survives the main db being deleted and rebuilt from the offset log. Messages are identified by
+//! their key string, not by the key ids of the main db, which change when it's rebuilt.
+
+use crate::db::schema::keys;
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::schema::messages::dsl::{
+    key_id as messages_key_id, messages as messages_table, root_key_id as messages_root_key_id,
+};
+use diesel::connection::SimpleConnection;
+use diesel::dsl::not;
+use diesel::insert_or_ignore_into;
+use diesel::prelude::*;
+use diesel::r2d2::CustomizeConnection;
+use diesel::result::Error;
+use diesel::sql_types::{Integer, Nullable, Text};
+use diesel::sqlite::{Sqlite, SqliteConnection};
+
+table! {
+    local.read_messages (key) {
+        key -> Text,
+    }
+}
+
+use self::read_messages::dsl::{key as read_key, read_messages as read_messages_table};
+
+/// The path of the local state store that goes with a db.
+pub fn local_state_path(database_path: &str) -> String {
+    format!("{}.local", database_path)
+}
+
+fn attach(connection: &SqliteConnection, path: &str, rw_mode: &str) -> Result<(), Error> {
+    connection.batch_execute(&format!(
+        "ATTACH DATABASE 'file:{}?mode={}' AS local",
+        path.replace('\'', "''"),
+        rw_mode
+    ))
+}
+
+/// Attaches the local state store to the read-write connection, creating it if it doesn't
+/// exist yet.
+pub fn attach_local_state(connection: &SqliteConnection, path: &str) -> Result<(), Error> {
+    attach(connection, path, "rwc")?;
+    connection
+        .batch_execute("CREATE TABLE IF NOT EXISTS local.read_messages (key TEXT PRIMARY KEY)")
+}
+
+/// Attaches the local state store to read-only connections when the pool opens them.
+#[derive(Debug)]
+pub struct LocalStateAttacher {
+    pub path: String,
+}
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for LocalStateAttacher {
+    fn on_acquire(&self, connection: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        attach(connection, &self.path, "ro").map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Sqlite can't bind more than 999 variables in a statement, so the keys that are passed in are
+/// marked in chunks of this size.
+const KEYS_PER_STATEMENT: usize = 500;
+
+/// The key ids of the messages with these keys.
+fn key_ids(connection: &SqliteConnection, keys: &[String]) -> Result<Vec<Option<i32>>, Error> {
+    keys_table
+        .select(keys_id)
+        .filter(keys_key.eq_any(keys))
+        .load(connection)
+}
+
+/// The keys of the replies in the threads with these roots, for use as a sub query.
+fn reply_keys(root_key_ids: Vec<Option<i32>>) -> keys::BoxedQuery<'static, Sqlite, Text> {
+    let reply_key_ids = messages_table
+        .select(messages_key_id.nullable())
+        .filter(messages_root_key_id.eq_any(root_key_ids));
+
+    keys_table
+        .select(keys_key)
+        .filter(keys_id.eq_any(reply_key_ids))
+        .into_boxed()
+}
+
+/// Marks messages as read. Marking a thread's root marks the whole thread.
+pub fn mark_read(connection: &SqliteConnection, keys: &[String]) -> Result<(), Error> {
+    for keys in keys.chunks(KEYS_PER_STATEMENT) {
+        let rows = keys.iter().map(|key| read_key.eq(key)).collect::<Vec<_>>();
+
+        insert_or_ignore_into(read_messages_table)
+            .values(&rows)
+            .execute(connection)?;
+
+        insert_or_ignore_into(read_messages_table)
+            .values(reply_keys(key_ids(connection, keys)?))
+            .into_columns(read_key)
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+/// Marks messages as unread. Marking a thread's root marks the whole thread.
+pub fn mark_unread(connection: &SqliteConnection, keys: &[String]) -> Result<(), Error> {
+    for keys in keys.chunks(KEYS_PER_STATEMENT) {
+        let replies = reply_keys(key_ids(connection, keys)?);
+
+        diesel::delete(
+            read_messages_table.filter(read_key.eq_any(keys).or(read_key.eq_any(replies))),
+        )
+        .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+/// Whether a message in the main db has been read.
+pub fn is_read(connection: &SqliteConnection, key_id: i32) -> Result<bool, Error> {
+    keys_table
+        .select(keys_key)
+        .filter(keys_id.eq(key_id))
+        .filter(keys_key.eq_any(read_messages_table.select(read_key)))
+        .first::<String>(connection)
+        .optional()
+        .map(|key| key.is_some())
+}
+
+/// The key ids of the messages in the main db that haven't been read, for use as a sub query.
+pub fn unread_key_ids() -> keys::BoxedQuery<'static, Sqlite, Nullable<Integer>> {
+    keys_table
+        .select(keys_id)
+        .filter(not(keys_key.eq_any(read_messages_table.select(read_key))))
+        .into_boxed()
+}
@@ -1,4 +1,4 @@
-use diesel::dsl::max;
+use diesel::expression::dsl::max;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::result::Error;
@@ -13,9 +13,12 @@ use flumedb::offset_log::OffsetLog;
 use private_box::SecretKey;
 use std::sync::{Arc, Mutex};
 
+pub mod indexer;
+pub mod local_state;
 pub mod models;
 pub mod schema;
 
+use indexer::{run_indexer_migrations, MessageIndexer};
 use schema::messages::dsl::*;
 
 embed_migrations!();
@@ -31,6 +34,8 @@ pub struct Context {
     pub log: Arc<Mutex<GoOffsetLog>>,
 
     pub keys: Vec<SecretKey>,
+
+    pub indexers: Arc<Vec<Box<dyn MessageIndexer>>>,
 }
 
 impl Context {
@@ -39,6 +44,23 @@ impl Context {
         database_path: String,
         pub_key_string: String,
         secret_key_string: String,
+    ) -> Context {
+        Context::with_indexers(
+            offset_log_path,
+            database_path,
+            pub_key_string,
+            secret_key_string,
+            Vec::new(),
+        )
+    }
+
+    /// Like `new`, but also runs the indexers on every message that's processed.
+    pub fn with_indexers(
+        offset_log_path: String,
+        database_path: String,
+        pub_key_string: String,
+        secret_key_string: String,
+        indexers: Vec<Box<dyn MessageIndexer>>,
     ) -> Context {
         #[cfg(not(feature = "ssb-go-log"))]
         let offset_log = match OffsetLog::open_read_only(&offset_log_path) {
@@ -59,11 +81,24 @@ impl Context {
 
         let rw_connection = open_connection(&to_sqlite_uri(&database_path, "rwc"));
 
-        let manager = ConnectionManager::new(&to_sqlite_uri(&database_path, "ro"));
-        let pool = Pool::builder().build(manager).unwrap();
+        // The local state lives outside the db so it isn't lost when the db is rebuilt.
+        let local_state_path = local_state::local_state_path(&database_path);
+        local_state::attach_local_state(&rw_connection, &local_state_path).unwrap();
+
+        let manager = ConnectionManager::new(to_sqlite_uri(&database_path, "ro"));
+        let pool = Pool::builder()
+            .connection_customizer(Box::new(local_state::LocalStateAttacher {
+                path: local_state_path,
+            }))
+            .build(manager)
+            .unwrap();
 
         models::authors::set_is_me(&rw_connection, &pub_key_string).unwrap();
 
+        indexers.iter().for_each(|indexer| {
+            run_indexer_migrations(&rw_connection, indexer.as_ref()).unwrap();
+        });
+
         let rw_locked_connection_ref = Arc::new(Mutex::new(rw_connection));
         let locked_connection_ref = pool;
 
@@ -84,6 +119,7 @@ impl Context {
             connection: locked_connection_ref.clone(),
             log: locked_log_ref.clone(),
             keys,
+            indexers: Arc::new(indexers),
         }
     }
 }
@@ -103,17 +139,22 @@ pub fn execute_pragmas(connection: &SqliteConnection) -> Result<(), Error> {
 }
 
 pub fn open_connection(database_url: &str) -> SqliteConnection {
-    let connection = SqliteConnection::establish(&database_url)
-        .expect(&format!("Error connecting to {}", database_url));
+    let establish = || {
+        SqliteConnection::establish(database_url)
+            .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
+    };
+    let mut connection = establish();
 
-    if let Err(_) = any_pending_migrations(&connection) {
+    if any_pending_migrations(&connection).is_err() {
         info!("sqlite db may be empty or not exist. Running migrations");
         embedded_migrations::run(&connection).unwrap();
     }
 
     if let Ok(true) = any_pending_migrations(&connection) {
         info!("sqlite db has pending migrations. Deleting db and it will be rebuilt.");
-        std::fs::remove_file(&database_url).unwrap();
+        drop(connection);
+        remove_database(database_url).unwrap();
+        connection = establish();
         embedded_migrations::run(&connection).unwrap();
     }
 
@@ -122,6 +163,22 @@ pub fn open_connection(database_url: &str) -> SqliteConnection {
     connection
 }
 
+/// Deletes the db at a path or a uri made by `to_sqlite_uri`, with its write-ahead log and shared
+/// memory files if they exist.
+fn remove_database(database_url: &str) -> std::io::Result<()> {
+    let path = database_url.trim_start_matches("file:");
+    let path = path.split('?').next().unwrap_or(path);
+
+    for suffix in &["", "-wal", "-shm"] {
+        match std::fs::remove_file(format!("{}{}", path, suffix)) {
+            Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => (),
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
 pub fn get_latest(connection: &SqliteConnection) -> Result<Option<f64>, Error> {
     messages
         .select(max(flume_seq))
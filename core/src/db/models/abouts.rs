@@ -2,8 +2,12 @@ use super::authors::find_or_create_author;
 use super::keys::find_or_create_key;
 
 use crate::db::schema::abouts::dsl::{abouts, link_from_key_id, link_to_author_id, link_to_key_id};
+use crate::db::schema::authors::dsl::{
+    author as authors_author, authors as authors_table, id as authors_id,
+};
 use crate::db::schema::messages::dsl::{
-    author_id as messages_author_id, content as messages_content, flume_seq as messages_flume_seq,
+    author_id as messages_author_id, content as messages_content,
+    content_type as messages_content_type, flume_seq as messages_flume_seq,
     key_id as messages_key_id, messages as messages_table,
 };
 use crate::db::SqliteConnection;
@@ -94,7 +98,7 @@ pub fn get_author_abouts<T: About + serde::de::DeserializeOwned>(
         .filter(link_to_author_id.eq(author_id))
         .filter(messages_author_id.eq(author_id))
         .filter(messages_content.is_not_null())
-        .load::<String>(&(*connection))?
+        .load::<String>(connection)?
         .into_iter()
         .map(|item| serde_json::from_str::<T>(&item).map(|item| item.about().to_string()))
         .filter_map(Result::ok)
@@ -104,3 +108,118 @@ pub fn get_author_abouts<T: About + serde::de::DeserializeOwned>(
 
     Ok(about)
 }
+
+/// The authors, their feed ids and the contents of the about messages about a message, newest
+/// first.
+pub fn get_key_abouts(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Vec<(i32, String, Value)>, Error> {
+    let key_abouts = abouts
+        .inner_join(messages_table.on(messages_key_id.nullable().eq(link_from_key_id)))
+        .inner_join(authors_table.on(authors_id.eq(messages_author_id.nullable())))
+        .select((messages_author_id, authors_author, messages_content))
+        .filter(link_to_key_id.eq(key_id))
+        .filter(messages_content_type.eq("about"))
+        .order(messages_flume_seq.desc())
+        .load::<(i32, String, Option<String>)>(connection)?
+        .into_iter()
+        .filter_map(|(author_id, author, content)| {
+            content
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .map(|content| (author_id, author, content))
+        })
+        .collect();
+
+    Ok(key_abouts)
+}
+
+/// The latest value of a field, eg. `title` or `location`, that a message's author set about it.
+/// Other authors can't change the details of someone else's gathering or repo.
+pub fn get_key_detail(
+    connection: &SqliteConnection,
+    key_id: i32,
+    field: &str,
+) -> Result<Option<Value>, Error> {
+    let author_id = messages_table
+        .select(messages_author_id)
+        .filter(messages_key_id.eq(key_id))
+        .first::<i32>(connection)
+        .optional()?;
+
+    let detail = get_key_abouts(connection, key_id)?
+        .into_iter()
+        .filter(|(about_author_id, _, _)| Some(*about_author_id) == author_id)
+        .map(|(_, _, mut content)| content[field].take())
+        .find(|value| !value.is_null());
+
+    Ok(detail)
+}
+
+/// The latest title, description and image an author set about a message.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageAbout {
+    pub author_id: i32,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The blob id of the image.
+    pub image: Option<String>,
+}
+
+/// What each author said about a message, starting with the message's author when they said
+/// anything, then everyone else, most recent first.
+pub fn get_message_abouts(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Vec<MessageAbout>, Error> {
+    let author_id = messages_table
+        .select(messages_author_id)
+        .filter(messages_key_id.eq(key_id))
+        .first::<i32>(connection)
+        .optional()?;
+
+    let mut message_abouts: Vec<MessageAbout> = Vec::new();
+
+    get_key_abouts(connection, key_id)?
+        .into_iter()
+        .for_each(|(about_author_id, _, content)| {
+            let index = match message_abouts
+                .iter()
+                .position(|about| about.author_id == about_author_id)
+            {
+                Some(index) => index,
+                None => {
+                    message_abouts.push(MessageAbout {
+                        author_id: about_author_id,
+                        ..MessageAbout::default()
+                    });
+                    message_abouts.len() - 1
+                }
+            };
+
+            // The abouts are newest first, so only fill in what hasn't been set yet.
+            let about = &mut message_abouts[index];
+            let text = |field: &str| content[field].as_str().map(|text| text.to_owned());
+            about.title = about.title.take().or_else(|| text("title"));
+            about.description = about.description.take().or_else(|| text("description"));
+            about.image = about.image.take().or_else(|| image_link(&content["image"]));
+        });
+
+    if let Some(index) = message_abouts
+        .iter()
+        .position(|about| Some(about.author_id) == author_id)
+    {
+        let author_about = message_abouts.remove(index);
+        message_abouts.insert(0, author_about);
+    }
+
+    Ok(message_abouts)
+}
+
+/// The blob id of an about's `image`, which is either the id or an object with a `link`.
+pub fn image_link(image: &Value) -> Option<String> {
+    match image {
+        Value::String(link) => Some(link.to_owned()),
+        image => image["link"].as_str().map(|link| link.to_owned()),
+    }
+}
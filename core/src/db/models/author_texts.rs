@@ -0,0 +1,147 @@
+use crate::db::schema::author_texts::dsl::{
+    author_texts as author_texts_table, description as author_texts_description,
+    name as author_texts_name, rowid as author_texts_rid,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::replace_into;
+use diesel::sql_types::{Double, Text};
+
+/// Keeps the full text index of an author's self assigned name and description up to date.
+pub fn insert_or_update_author_texts(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    author_id: i32,
+) -> Result<(), Error> {
+    let content = &message.value.content;
+
+    if content["type"] != "about" || content["about"] != message.value.author.as_str() {
+        return Ok(());
+    }
+
+    let name = content["name"].as_str();
+    let description = content["description"].as_str();
+
+    if name.is_none() && description.is_none() {
+        return Ok(());
+    }
+
+    let (previous_name, previous_description) = author_texts_table
+        .select((author_texts_name, author_texts_description))
+        .filter(author_texts_rid.eq(author_id))
+        .first::<(Option<String>, Option<String>)>(connection)
+        .optional()?
+        .unwrap_or((None, None));
+
+    replace_into(author_texts_table)
+        .values((
+            author_texts_rid.eq(author_id),
+            author_texts_name.eq(name.map(|name| name.to_string()).or(previous_name)),
+            author_texts_description.eq(description
+                .map(|description| description.to_string())
+                .or(previous_description)),
+        ))
+        .execute(connection)?;
+
+    Ok(())
+}
+
+/// Turns a user's search string into an fts5 query where every word is a prefix match, so that
+/// eg. "pi" will find "piet". Returns `None` if there's nothing to search for.
+fn to_prefix_query(query: &str, column: Option<&str>) -> Option<String> {
+    let column_filter = column
+        .map(|column| format!("{} : ", column))
+        .unwrap_or_default();
+
+    let terms = query
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}\"{}\"*", column_filter, word))
+        .collect::<Vec<_>>();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Finds authors whose name (and optionally description) match the query, best matches first.
+pub fn search_author_texts(
+    connection: &SqliteConnection,
+    query: &str,
+    include_descriptions: bool,
+) -> Result<Vec<i32>, Error> {
+    let column = if include_descriptions {
+        None
+    } else {
+        Some("name")
+    };
+
+    let fts_query = match to_prefix_query(query, column) {
+        Some(fts_query) => fts_query,
+        None => return Ok(Vec::new()),
+    };
+
+    // Matches on names are worth more than matches on descriptions.
+    author_texts_table
+        .select(author_texts_rid)
+        .filter(sql("author_texts MATCH ").bind::<Text, _>(fts_query))
+        .order(sql::<Double>("bm25(author_texts, 10.0, 1.0)"))
+        .load::<i32>(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::author_texts::{insert_or_update_author_texts, search_author_texts};
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::json;
+
+    fn about(author: &str, content: serde_json::Value) -> SsbMessage {
+        let mut msg = SsbMessage::default();
+        msg.value.author = author.to_string();
+        msg.value.content = content;
+        msg
+    }
+
+    #[test]
+    fn search_names_and_descriptions() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let piet = about(
+                "@piet",
+                json!({"type": "about", "about": "@piet", "name": "Piet", "description": "Rust and dogs"}),
+            );
+            let mix = about(
+                "@mix",
+                json!({"type": "about", "about": "@mix", "name": "mix", "description": "I like Piet"}),
+            );
+            let renamed = about(
+                "@mix",
+                json!({"type": "about", "about": "@mix", "name": "mixmix"}),
+            );
+            let not_self_assigned = about(
+                "@mix",
+                json!({"type": "about", "about": "@piet", "name": "dinosaur"}),
+            );
+
+            insert_or_update_author_texts(&connection, &piet, 1)?;
+            insert_or_update_author_texts(&connection, &mix, 2)?;
+            insert_or_update_author_texts(&connection, &renamed, 2)?;
+            insert_or_update_author_texts(&connection, &not_self_assigned, 1)?;
+
+            assert_eq!(search_author_texts(&connection, "pi", false)?, vec![1]);
+            assert_eq!(search_author_texts(&connection, "piet", true)?, vec![1, 2]);
+            assert_eq!(search_author_texts(&connection, "mixmix", false)?, vec![2]);
+            assert_eq!(search_author_texts(&connection, "dinosaur", false)?, Vec::<i32>::new());
+            assert_eq!(search_author_texts(&connection, "\"", false)?, Vec::<i32>::new());
+            Ok(())
+        });
+    }
+}
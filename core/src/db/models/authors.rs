@@ -37,7 +37,7 @@ pub fn set_is_me(connection: &SqliteConnection, author: &str) -> Result<(), Erro
     diesel::update(authors_table)
         .set(authors_is_me.eq(Option::<bool>::None))
         .filter(authors_is_me.is_not_null())
-        .execute(&(*connection))?;
+        .execute(connection)?;
 
     //Create if the author doesn't exist yet (happens on first run before processing any offset log)
     find_or_create_author(connection, author)?;
@@ -45,7 +45,17 @@ pub fn set_is_me(connection: &SqliteConnection, author: &str) -> Result<(), Erro
     diesel::update(authors_table)
         .set(authors_is_me.eq(true))
         .filter(authors_author.eq(author))
-        .execute(&(*connection))?;
+        .execute(connection)?;
 
     Ok(())
 }
+
+/// The id of the author with publishing rights on this machine, if one has been set.
+pub fn get_my_author_id(connection: &SqliteConnection) -> Result<Option<i32>, Error> {
+    authors_table
+        .select(authors_id)
+        .filter(authors_is_me.eq(true))
+        .first::<Option<i32>>(connection)
+        .optional()
+        .map(|id| id.and_then(|id| id))
+}
@@ -0,0 +1,75 @@
+use super::abouts::image_link;
+use crate::db::schema::blogs;
+use crate::db::schema::blogs::dsl::{blogs as blogs_table, key_id as blogs_key_id};
+use crate::db::schema::reply_posts::dsl::{
+    flume_seq as reply_posts_flume_seq, key_id as reply_posts_key_id,
+    reply_posts as reply_posts_table, root_post_id as reply_posts_root_post_id,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::insert_into;
+use diesel::prelude::*;
+
+#[derive(Queryable, Insertable, Identifiable, Clone, Debug, Default)]
+#[table_name = "blogs"]
+#[primary_key(flume_seq)]
+pub struct Blog {
+    pub flume_seq: i64,
+    pub asserted_timestamp: i64,
+    pub key_id: i32,
+    pub author_id: i32,
+    pub title: Option<String>,
+    pub summary: Option<String>,
+    /// The blob id of the thumbnail image.
+    pub thumbnail: Option<String>,
+    /// The blob id of the blog's body.
+    pub blog: Option<String>,
+}
+
+// Caller must check that the message is actually a blog.
+pub fn insert_blog(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let content = &message.value.content;
+    let text = |field: &str| content[field].as_str().map(|text| text.to_owned());
+
+    let blog = Blog {
+        flume_seq: seq,
+        asserted_timestamp: message.value.timestamp as i64,
+        key_id: message_key_id,
+        author_id,
+        title: text("title"),
+        summary: text("summary"),
+        thumbnail: image_link(&content["thumbnail"]),
+        blog: text("blog"),
+    };
+
+    insert_into(blogs_table)
+        .values(blog)
+        .execute(connection)
+        .map(|_| ())
+}
+
+/// The blogs with the given key ids, in no particular order.
+pub fn get_blogs(connection: &SqliteConnection, key_ids: &[i32]) -> Result<Vec<Blog>, Error> {
+    blogs_table
+        .filter(blogs_key_id.eq_any(key_ids))
+        .load(connection)
+}
+
+/// The key ids and flume seqs of the comments on a blog, oldest first. Comments are posts with the
+/// blog as their `root`, like replies in a thread.
+pub fn get_blog_comments(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Vec<(i32, i64)>, Error> {
+    reply_posts_table
+        .select((reply_posts_key_id, reply_posts_flume_seq))
+        .filter(reply_posts_root_post_id.eq(key_id))
+        .order(reply_posts_flume_seq.asc())
+        .load(connection)
+}
@@ -2,11 +2,16 @@ use super::keys::find_or_create_key;
 use crate::db::schema::branches::dsl::{
     branches as branches_table, link_from_key_id, link_to_key_id,
 };
-use crate::db::SqliteConnection;
+use crate::db::schema::messages::dsl::{
+    content_type as messages_content_type, flume_seq as messages_flume_seq,
+    key_id as messages_key_id, messages as messages_table, root_key_id as messages_root_key_id,
+};
+use crate::db::{Error, SqliteConnection};
 use crate::ssb_message::*;
 use diesel::insert_into;
 use diesel::prelude::*;
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 
 pub fn insert_branches(connection: &SqliteConnection, message: &SsbMessage, message_key_id: i32) {
     if let Some(branches_value) = message.value.content.get("branch") {
@@ -33,3 +38,161 @@ pub fn insert_branches(connection: &SqliteConnection, message: &SsbMessage, mess
             })
     }
 }
+
+/// Finds the messages a message branches from, the ones it replies to. Branches that point at
+/// messages we don't have are skipped.
+pub fn get_branch_parents(connection: &SqliteConnection, key_id: i32) -> Result<Vec<i32>, Error> {
+    branches_table
+        .inner_join(messages_table.on(messages_key_id.eq(link_to_key_id)))
+        .select(messages_key_id)
+        .filter(link_from_key_id.eq(key_id))
+        .order(messages_flume_seq.asc())
+        .load::<i32>(connection)
+}
+
+/// Finds the posts that branch from a message, the direct replies to it.
+pub fn get_branch_children(connection: &SqliteConnection, key_id: i32) -> Result<Vec<i32>, Error> {
+    branches_table
+        .inner_join(messages_table.on(messages_key_id.eq(link_from_key_id)))
+        .select(messages_key_id)
+        .filter(link_to_key_id.eq(key_id))
+        .filter(messages_content_type.eq("post"))
+        .order(messages_flume_seq.asc())
+        .load::<i32>(connection)
+}
+
+/// A reply and the replies that branch from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplyTree {
+    pub key_id: i32,
+    pub children: Vec<ReplyTree>,
+}
+
+/// Nests the replies in a thread by their branches.
+///
+/// `replies` are the key ids of the replies in the order their siblings should be in. `branches`
+/// are `(from, to)` pairs. A reply with several parents in the thread goes under the last one,
+/// a reply without any (because it branches from the root, or from messages we don't have) goes
+/// at the top.
+pub fn build_reply_tree(replies: &[i32], branches: &[(i32, i32)]) -> Vec<ReplyTree> {
+    let position = replies
+        .iter()
+        .enumerate()
+        .map(|(index, key_id)| (*key_id, index))
+        .collect::<HashMap<i32, usize>>();
+
+    let mut parents = HashMap::new();
+    branches
+        .iter()
+        .filter(|(from, to)| from != to && position.contains_key(from))
+        .filter_map(|(from, to)| position.get(to).map(|index| (*from, *to, *index)))
+        .for_each(|(from, to, index)| {
+            let parent = parents.entry(from).or_insert((to, index));
+            if index > parent.1 {
+                *parent = (to, index);
+            }
+        });
+    let mut parents = parents
+        .into_iter()
+        .map(|(from, (to, _))| (from, to))
+        .collect::<HashMap<i32, i32>>();
+
+    // Branches can't really form a loop, but a reply that runs into one goes at the top.
+    let looped = replies
+        .iter()
+        .filter(|key_id| {
+            let mut seen = HashSet::new();
+            let mut current = Some(**key_id);
+            while let Some(id) = current {
+                if !seen.insert(id) {
+                    return true;
+                }
+                current = parents.get(&id).cloned();
+            }
+            false
+        })
+        .cloned()
+        .collect::<Vec<i32>>();
+    looped.iter().for_each(|key_id| {
+        parents.remove(key_id);
+    });
+
+    let mut children = HashMap::new();
+    replies.iter().for_each(|key_id| {
+        children
+            .entry(parents.get(key_id).cloned())
+            .or_insert_with(Vec::new)
+            .push(*key_id)
+    });
+
+    fn tree(key_id: i32, children: &HashMap<Option<i32>, Vec<i32>>) -> ReplyTree {
+        ReplyTree {
+            key_id,
+            children: children
+                .get(&Some(key_id))
+                .map(|keys| keys.iter().map(|key| tree(*key, children)).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    children
+        .get(&None)
+        .map(|keys| keys.iter().map(|key| tree(*key, &children)).collect())
+        .unwrap_or_default()
+}
+
+/// Finds all the `(from, to)` branches from the replies in a thread.
+pub fn get_thread_branches(
+    connection: &SqliteConnection,
+    root_key_id: i32,
+) -> Result<Vec<(i32, i32)>, Error> {
+    branches_table
+        .inner_join(messages_table.on(messages_key_id.eq(link_from_key_id)))
+        .select((link_from_key_id, link_to_key_id))
+        .filter(messages_root_key_id.eq(root_key_id))
+        .load::<(i32, i32)>(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::branches::{build_reply_tree, ReplyTree};
+
+    fn leaf(key_id: i32) -> ReplyTree {
+        ReplyTree {
+            key_id,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn nest_replies_by_branch() {
+        // 1 is the root, 9 is a message we don't have.
+        let replies = vec![2, 3, 4, 5];
+        let branches = vec![(2, 1), (3, 2), (4, 2), (4, 3), (5, 9)];
+
+        assert_eq!(
+            build_reply_tree(&replies, &branches),
+            vec![
+                ReplyTree {
+                    key_id: 2,
+                    children: vec![ReplyTree {
+                        key_id: 3,
+                        children: vec![leaf(4)]
+                    }]
+                },
+                leaf(5)
+            ]
+        );
+    }
+
+    #[test]
+    fn break_branch_cycles() {
+        let replies = vec![2, 3];
+        let branches = vec![(2, 3), (3, 2)];
+
+        assert_eq!(
+            build_reply_tree(&replies, &branches),
+            vec![leaf(2), leaf(3)]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use diesel::prelude::*;
+use diesel::replace_into;
+
+use super::channels::{find_or_create_channel, normalize_channel};
+use crate::db::schema::channel_subscriptions::dsl::{
+    author_id, channel_id, channel_subscriptions, subscribed,
+};
+use crate::db::SqliteConnection;
+use crate::ssb_message::*;
+
+use serde_json::Value;
+
+// Caller must check that the message is actually a channel message.
+pub fn insert_or_update_channel_subscriptions(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_author_id: i32,
+) {
+    if let Value::String(channel) = &message.value.content["channel"] {
+        if let Some(channel) = normalize_channel(channel) {
+            let is_subscribed = message.value.content["subscribed"]
+                .as_bool()
+                .unwrap_or(false);
+
+            let subscribed_channel_id = find_or_create_channel(connection, &channel).unwrap();
+
+            replace_into(channel_subscriptions)
+                .values((
+                    author_id.eq(message_author_id),
+                    channel_id.eq(subscribed_channel_id),
+                    subscribed.eq(is_subscribed),
+                ))
+                .execute(connection)
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::channel_subscriptions::insert_or_update_channel_subscriptions;
+    use crate::db::schema::channel_subscriptions::dsl::{channel_subscriptions, subscribed};
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::json;
+
+    #[test]
+    fn last_subscription_message_wins() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let mut msg = SsbMessage::default();
+
+            msg.value.content = json!({"type": "channel", "channel": "#Boats", "subscribed": true});
+            insert_or_update_channel_subscriptions(&connection, &msg, 1);
+
+            msg.value.content = json!({"type": "channel", "channel": "boats", "subscribed": false});
+            insert_or_update_channel_subscriptions(&connection, &msg, 1);
+
+            let results = channel_subscriptions
+                .select(subscribed)
+                .load::<bool>(&connection)?;
+
+            assert_eq!(results, vec![false]);
+            Ok(())
+        });
+    }
+}
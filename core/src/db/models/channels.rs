@@ -0,0 +1,178 @@
+use crate::db::schema::*;
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+
+use crate::db::schema::channel_links::dsl::{
+    channel_links as channel_links_table, link_from_key_id as channel_links_link_from_key_id,
+    link_to_channel_id as channel_links_link_to_channel_id,
+};
+use crate::db::schema::channels::dsl::{
+    channel as channels_channel, channels as channels_table, id as channels_id,
+};
+use diesel::dsl::sql;
+use diesel::insert_or_ignore_into;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable};
+use diesel::sqlite::Sqlite;
+use serde_json::Value;
+
+#[derive(Queryable, Insertable, Identifiable, Debug)]
+#[table_name = "channels"]
+pub struct Channel {
+    pub id: Option<i32>,
+    pub channel: String,
+}
+
+/// Characters that can't be part of a channel name. Matches the js `ssb-ref` normalizeChannel.
+const CHANNEL_STOP_CHARS: &[char] = &[
+    '|', ',', '.', '?', '!', '<', '>', '(', ')', '[', ']', '"', '\'', '`', ':', ';', '#', '*',
+];
+
+/// Channel names are case insensitive and may be written with or without a leading `#`. Like
+/// ssb-ref, only the first 30 characters are kept.
+/// Returns `None` if there's nothing left of the name once it's normalized.
+pub fn normalize_channel(channel: &str) -> Option<String> {
+    let normalized = channel
+        .trim_start_matches('#')
+        .chars()
+        .filter(|c| !c.is_whitespace() && !CHANNEL_STOP_CHARS.contains(c))
+        .take(30)
+        .collect::<String>()
+        .to_lowercase();
+
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Finds all the `#hashtags` in a post's text. A hashtag has to start a word (or a markdown link),
+/// so markdown headings (`# Heading`) and url fragments are skipped.
+pub fn find_hashtags(text: &str) -> Vec<String> {
+    text.char_indices()
+        .filter(|(_, c)| *c == '#')
+        .filter(|(index, _)| match text[..*index].chars().last() {
+            None => true,
+            Some(prev) => prev.is_whitespace() || prev == '[' || prev == '(',
+        })
+        .filter_map(|(index, _)| {
+            let tag = text[index + 1..]
+                .split(|c: char| c.is_whitespace() || CHANNEL_STOP_CHARS.contains(&c))
+                .next()
+                .unwrap_or("");
+            normalize_channel(tag)
+        })
+        .collect()
+}
+
+pub fn find_or_create_channel(connection: &SqliteConnection, channel: &str) -> Result<i32, Error> {
+    // Channel names are unique, so this only inserts a channel we haven't seen yet.
+    insert_or_ignore_into(channels_table)
+        .values(channels_channel.eq(channel))
+        .execute(connection)?;
+
+    channels_table
+        .select(channels_id)
+        .filter(channels_channel.eq(channel))
+        .first::<Option<i32>>(connection)
+        .map(|id| id.unwrap())
+}
+
+// Caller must check that the message is actually a post.
+pub fn insert_channels(connection: &SqliteConnection, message: &SsbMessage, message_key_id: i32) {
+    let content = &message.value.content;
+
+    let mut channels = Vec::new();
+
+    if let Value::String(channel) = &content["channel"] {
+        channels.extend(normalize_channel(channel));
+    }
+
+    if let Value::String(text) = &content["text"] {
+        channels.extend(find_hashtags(text));
+    }
+
+    // Patchwork also records hashtags as mentions with a `#` link.
+    if let Value::Array(mentions) = &content["mentions"] {
+        mentions
+            .iter()
+            .filter_map(|mention| mention["link"].as_str())
+            .filter(|link| link.starts_with('#'))
+            .filter_map(normalize_channel)
+            .for_each(|channel| channels.push(channel));
+    }
+
+    channels.sort();
+    channels.dedup();
+
+    channels
+        .iter()
+        .map(|channel| find_or_create_channel(connection, channel).unwrap())
+        .for_each(|channel_id| {
+            insert_or_ignore_into(channel_links_table)
+                .values((
+                    channel_links_link_from_key_id.eq(message_key_id),
+                    channel_links_link_to_channel_id.eq(channel_id),
+                ))
+                .execute(connection)
+                .unwrap();
+        });
+}
+
+/// The number of authors subscribed to a channel, in sql, for ordering channels by popularity in
+/// the db.
+pub const SUBSCRIBER_COUNT: &str = "\
+    (SELECT COUNT(*) FROM channel_subscriptions \
+    WHERE channel_subscriptions.channel_id = channels.id \
+        AND channel_subscriptions.subscribed)";
+
+/// The `(id, id, subscriber count)` of all the channels, for paging by popularity. The channel id
+/// breaks ties between channels with the same number of subscribers.
+pub fn channels_query<'a>(
+) -> channels::BoxedQuery<'a, Sqlite, (Integer, Nullable<BigInt>, Nullable<BigInt>)> {
+    channels_table
+        .select((
+            sql::<Integer>("channels.id"),
+            sql::<Nullable<BigInt>>("channels.id"),
+            sql::<Nullable<BigInt>>(SUBSCRIBER_COUNT),
+        ))
+        .into_boxed()
+}
+
+pub fn get_channels(connection: &SqliteConnection, key_id: i32) -> Result<Vec<String>, Error> {
+    channel_links_table
+        .inner_join(channels_table.on(channels_id.eq(channel_links_link_to_channel_id.nullable())))
+        .select(channels_channel)
+        .filter(channel_links_link_from_key_id.eq(key_id))
+        .order(channels_channel.asc())
+        .load::<String>(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::channels::{find_hashtags, normalize_channel};
+
+    #[test]
+    fn normalize_channel_names() {
+        assert_eq!(normalize_channel("#SSB"), Some("ssb".to_string()));
+        assert_eq!(
+            normalize_channel("new zealand"),
+            Some("newzealand".to_string())
+        );
+        assert_eq!(normalize_channel("#"), None);
+        assert_eq!(
+            normalize_channel("#abcdefghijklmnopqrstuvwxyz0123456789"),
+            Some("abcdefghijklmnopqrstuvwxyz0123".to_string())
+        );
+    }
+
+    #[test]
+    fn find_hashtags_in_text() {
+        let text = "# A heading\n\nHello #scuttlebutt, have you seen #Patchwork? http://example.com/#anchor [#ssb](#ssb)";
+        assert_eq!(
+            find_hashtags(text),
+            vec!["scuttlebutt", "patchwork", "ssb", "ssb"]
+        );
+    }
+}
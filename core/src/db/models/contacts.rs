@@ -1,9 +1,10 @@
 use diesel::prelude::*;
 use diesel::replace_into;
+use diesel::result::Error;
 
 use super::authors::find_or_create_author;
 use crate::db::schema::contacts::dsl::{
-    author_id, contact_author_id, contacts, is_decrypted as is_decrypted_column, state,
+    author_id, contact_author_id, contacts, is_decrypted as is_decrypted_column, key_id, state,
 };
 use crate::db::SqliteConnection;
 use crate::ssb_message::*;
@@ -12,7 +13,7 @@ use serde_json::Value;
 pub fn insert_or_update_contacts(
     connection: &SqliteConnection,
     message: &SsbMessage,
-    _message_key_id: i32,
+    message_key_id: i32,
     is_decrypted: bool,
 ) {
     if let Value::String(contact) = &message.value.content["contact"] {
@@ -28,8 +29,8 @@ pub fn insert_or_update_contacts(
             None
         };
 
-        let author = find_or_create_author(&connection, &message.value.author).unwrap();
-        let contact_author = find_or_create_author(&connection, contact).unwrap();
+        let author = find_or_create_author(connection, &message.value.author).unwrap();
+        let contact_author = find_or_create_author(connection, contact).unwrap();
 
         replace_into(contacts)
             .values((
@@ -37,8 +38,62 @@ pub fn insert_or_update_contacts(
                 contact_author_id.eq(contact_author),
                 is_decrypted_column.eq(is_decrypted),
                 state.eq(follow_state),
+                key_id.eq(message_key_id),
             ))
             .execute(connection)
             .unwrap();
     }
 }
+
+/// The state of `author`'s public (`is_decrypted = false`) or private (`is_decrypted = true`)
+/// contact message about `contact_author`. 1 is following, -1 is blocking. `None` means neutral or
+/// that there is no contact message.
+pub fn get_contact_state(
+    connection: &SqliteConnection,
+    author: i32,
+    contact_author: i32,
+    is_decrypted: bool,
+) -> Result<Option<i32>, Error> {
+    contacts
+        .select(state)
+        .filter(author_id.eq(author))
+        .filter(contact_author_id.eq(contact_author))
+        .filter(is_decrypted_column.eq(is_decrypted))
+        .first::<Option<i32>>(connection)
+        .optional()
+        .map(|contact_state| contact_state.unwrap_or(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::authors::find_or_create_author;
+    use crate::db::models::contacts::{get_contact_state, insert_or_update_contacts};
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::json;
+
+    #[test]
+    fn private_and_public_contacts_are_separate() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let mut msg = SsbMessage::default();
+            msg.value.author = "@piet".to_string();
+
+            msg.value.content = json!({"type": "contact", "contact": "@mix", "following": true});
+            insert_or_update_contacts(&connection, &msg, 1, false);
+
+            msg.value.content = json!({"type": "contact", "contact": "@mix", "blocking": true});
+            insert_or_update_contacts(&connection, &msg, 2, true);
+
+            let piet = find_or_create_author(&connection, "@piet")?;
+            let mix = find_or_create_author(&connection, "@mix")?;
+
+            assert_eq!(get_contact_state(&connection, piet, mix, false)?, Some(1));
+            assert_eq!(get_contact_state(&connection, piet, mix, true)?, Some(-1));
+            assert_eq!(get_contact_state(&connection, mix, piet, false)?, None);
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,40 @@
+use crate::db::schema::content_warnings::dsl::{
+    content_warning as content_warnings_content_warning,
+    content_warnings as content_warnings_table, key_id as content_warnings_key_id,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::insert_or_ignore_into;
+use diesel::prelude::*;
+use serde_json::Value;
+
+// Caller must check that the message is actually a post.
+pub fn insert_content_warning(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+) {
+    match &message.value.content["contentWarning"] {
+        Value::String(content_warning) if !content_warning.trim().is_empty() => {
+            insert_or_ignore_into(content_warnings_table)
+                .values((
+                    content_warnings_key_id.eq(message_key_id),
+                    content_warnings_content_warning.eq(content_warning.trim()),
+                ))
+                .execute(connection)
+                .unwrap();
+        }
+        _ => {}
+    }
+}
+
+pub fn get_content_warning(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Option<String>, Error> {
+    content_warnings_table
+        .select(content_warnings_content_warning)
+        .filter(content_warnings_key_id.eq(key_id))
+        .first(connection)
+        .optional()
+}
@@ -0,0 +1,181 @@
+use super::abouts::image_link;
+use crate::db::schema::abouts::dsl::{
+    abouts as abouts_table, link_from_key_id as abouts_link_from_key_id,
+    link_to_key_id as abouts_link_to_key_id,
+};
+use crate::db::schema::authors::dsl::{
+    author as authors_author, authors as authors_table, id as authors_id,
+};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::schema::messages;
+use crate::db::schema::messages::dsl::{
+    author_id as messages_author_id, content as messages_content,
+    content_type as messages_content_type, flume_seq as messages_flume_seq,
+    key_id as messages_key_id, messages as messages_table,
+};
+use crate::db::{Error, SqliteConnection};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Integer};
+use diesel::sqlite::Sqlite;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A gathering with the details its author set about it and who is attending.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gathering {
+    pub key_id: i32,
+    pub key: String,
+    pub author_id: i32,
+    pub flume_seq: i64,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    /// The blob id of the image.
+    pub image: Option<String>,
+    /// The start time in milliseconds, if the gathering has one.
+    pub start_time: Option<i64>,
+    pub attendee_ids: Vec<i32>,
+}
+
+impl Gathering {
+    /// Fills in the details and attendees from the abouts about the gathering, newest first.
+    ///
+    /// Only the gathering's author can change its details. Authors can only say that they
+    /// themselves are attending, `attendee` links to anyone else are ignored. An attendee with
+    /// `remove: true` is no longer attending.
+    fn with_abouts(mut self, abouts: &[(i32, String, Value)]) -> Gathering {
+        let author_id = self.author_id;
+        let detail = |field: &str| {
+            abouts
+                .iter()
+                .filter(|(about_author_id, _, _)| *about_author_id == author_id)
+                .map(|(_, _, content)| &content[field])
+                .find(|value| !value.is_null())
+        };
+        let detail_text = |field: &str| {
+            detail(field)
+                .and_then(|value| value.as_str())
+                .map(|text| text.to_owned())
+        };
+
+        self.title = detail_text("title");
+        self.description = detail_text("description");
+        self.location = detail_text("location");
+        self.image = detail("image").and_then(image_link);
+        self.start_time = detail("startDateTime")
+            .and_then(|start| start["epoch"].as_f64())
+            .map(|epoch| epoch as i64);
+
+        abouts
+            .iter()
+            .rev()
+            .filter(|(_, author, content)| content["attendee"]["link"] == author.as_str())
+            .for_each(|(author_id, _, content)| {
+                self.attendee_ids
+                    .retain(|attendee_id| attendee_id != author_id);
+                if content["attendee"]["remove"] != true {
+                    self.attendee_ids.push(*author_id);
+                }
+            });
+
+        self
+    }
+}
+
+/// When a gathering starts, in sql, for ordering and filtering gatherings in the db. Like
+/// `Gathering::with_abouts`, it's the `startDateTime` of the newest about by the gathering's
+/// author that sets one.
+pub const START_TIME: &str = "\
+    (SELECT CASE WHEN json_type(about.content, '$.startDateTime.epoch') IN ('integer', 'real') \
+        THEN CAST(json_extract(about.content, '$.startDateTime.epoch') AS INTEGER) END \
+    FROM abouts \
+    JOIN messages AS about ON about.key_id = abouts.link_from_key_id \
+    WHERE abouts.link_to_key_id = messages.key_id \
+        AND about.author_id = messages.author_id \
+        AND about.content_type = 'about' \
+        AND json_type(about.content, '$.startDateTime') != 'null' \
+    ORDER BY about.flume_seq DESC LIMIT 1)";
+
+/// Whether an author is attending a gathering, in sql, for filtering gatherings in the db. Like
+/// `Gathering::with_abouts`, it's the newest about where the author said they themselves are
+/// attending, unless it has `remove: true`.
+pub fn is_attending(
+    author_id: i32,
+) -> Box<dyn BoxableExpression<messages::table, Sqlite, SqlType = Bool>> {
+    Box::new(
+        sql::<Bool>(
+            "IFNULL((SELECT json_type(about.content, '$.attendee.remove') IS NOT 'true' \
+             FROM abouts \
+             JOIN messages AS about ON about.key_id = abouts.link_from_key_id \
+             JOIN authors ON authors.id = about.author_id \
+             WHERE abouts.link_to_key_id = messages.key_id \
+                 AND about.content_type = 'about' \
+                 AND json_extract(about.content, '$.attendee.link') = authors.author \
+                 AND about.author_id = ",
+        )
+        .bind::<Integer, _>(author_id)
+        .sql(" ORDER BY about.flume_seq DESC LIMIT 1), 0)"),
+    )
+}
+
+/// The gatherings with the given key ids, with their details and attendees.
+///
+/// The abouts for all the gatherings are loaded in one query rather than once per gathering.
+pub fn get_gatherings(
+    connection: &SqliteConnection,
+    key_ids: &[i32],
+) -> Result<Vec<Gathering>, Error> {
+    let mut abouts_by_key_id: HashMap<i32, Vec<(i32, String, Value)>> = HashMap::new();
+    abouts_table
+        .inner_join(messages_table.on(messages_key_id.nullable().eq(abouts_link_from_key_id)))
+        .inner_join(authors_table.on(authors_id.eq(messages_author_id.nullable())))
+        .select((
+            abouts_link_to_key_id,
+            messages_author_id,
+            authors_author,
+            messages_content,
+        ))
+        .filter(abouts_link_to_key_id.eq_any(key_ids))
+        .filter(messages_content_type.eq("about"))
+        .order(messages_flume_seq.desc())
+        .load::<(Option<i32>, i32, String, Option<String>)>(connection)?
+        .into_iter()
+        .for_each(|(key_id, author_id, author, content)| {
+            let content = content.and_then(|content| serde_json::from_str(&content).ok());
+            if let (Some(key_id), Some(content)) = (key_id, content) {
+                abouts_by_key_id
+                    .entry(key_id)
+                    .or_default()
+                    .push((author_id, author, content));
+            }
+        });
+
+    let gatherings = messages_table
+        .inner_join(keys_table.on(keys_id.eq(messages_key_id.nullable())))
+        .select((
+            messages_key_id,
+            keys_key,
+            messages_author_id,
+            messages_flume_seq,
+        ))
+        .filter(messages_key_id.eq_any(key_ids))
+        .load::<(i32, String, i32, Option<i64>)>(connection)?
+        .into_iter()
+        .map(|(key_id, key, author_id, flume_seq)| {
+            let gathering = Gathering {
+                key_id,
+                key,
+                author_id,
+                flume_seq: flume_seq.unwrap_or(0),
+                ..Gathering::default()
+            };
+            match abouts_by_key_id.get(&key_id) {
+                Some(abouts) => gathering.with_abouts(abouts),
+                None => gathering,
+            }
+        })
+        .collect();
+
+    Ok(gatherings)
+}
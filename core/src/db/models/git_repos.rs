@@ -0,0 +1,305 @@
+use super::abouts::get_key_detail;
+use super::keys::find_or_create_key;
+use crate::db::schema::git_refs::dsl::{
+    git_refs as git_refs_table, name as git_refs_name, sha1 as git_refs_sha1,
+    update_flume_seq as git_refs_update_flume_seq,
+};
+use crate::db::schema::git_repos;
+use crate::db::schema::git_repos::dsl::{
+    author_id as git_repos_author_id, git_repos as git_repos_table, key_id as git_repos_key_id,
+};
+use crate::db::schema::git_updates;
+use crate::db::schema::git_updates::dsl::{
+    asserted_timestamp as git_updates_asserted_timestamp, author_id as git_updates_author_id,
+    flume_seq as git_updates_flume_seq, git_updates as git_updates_table,
+    key_id as git_updates_key_id, repo_key_id as git_updates_repo_key_id,
+};
+use crate::db::schema::messages::dsl::{
+    content as messages_content, key_id as messages_key_id, messages as messages_table,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::insert_into;
+use diesel::prelude::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Queryable, Insertable, Identifiable, Debug, Default)]
+#[table_name = "git_repos"]
+#[primary_key(flume_seq)]
+pub struct GitRepo {
+    pub flume_seq: i64,
+    pub asserted_timestamp: i64,
+    pub key_id: i32,
+    pub author_id: i32,
+}
+
+#[derive(Queryable, Insertable, Identifiable, Debug, Default)]
+#[table_name = "git_updates"]
+#[primary_key(flume_seq)]
+pub struct GitUpdate {
+    pub flume_seq: i64,
+    pub asserted_timestamp: i64,
+    pub key_id: i32,
+    /// The repo being pushed to.
+    pub repo_key_id: i32,
+    pub author_id: i32,
+}
+
+// Caller must check that the message is actually a git-repo.
+pub fn insert_git_repo(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let repo = GitRepo {
+        flume_seq: seq,
+        asserted_timestamp: message.value.timestamp as i64,
+        key_id: message_key_id,
+        author_id,
+    };
+
+    insert_into(git_repos_table)
+        .values(repo)
+        .execute(connection)
+        .map(|_| ())
+}
+
+// Caller must check that the message is actually a git-update.
+pub fn insert_git_update(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    if let Value::String(repo) = &message.value.content["repo"] {
+        let update = GitUpdate {
+            flume_seq: seq,
+            asserted_timestamp: message.value.timestamp as i64,
+            key_id: message_key_id,
+            repo_key_id: find_or_create_key(connection, repo)?,
+            author_id,
+        };
+
+        insert_into(git_updates_table)
+            .values(update)
+            .execute(connection)?;
+
+        if let Value::Object(refs) = &message.value.content["refs"] {
+            for (name, sha1) in refs {
+                insert_into(git_refs_table)
+                    .values((
+                        git_refs_update_flume_seq.eq(seq),
+                        git_refs_name.eq(name),
+                        git_refs_sha1.eq(sha1.as_str()),
+                    ))
+                    .execute(connection)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Is the message a repo?
+pub fn is_git_repo(connection: &SqliteConnection, key_id: i32) -> Result<bool, Error> {
+    git_repos_table
+        .select(git_repos_key_id)
+        .filter(git_repos_key_id.eq(key_id))
+        .first::<i32>(connection)
+        .optional()
+        .map(|repo| repo.is_some())
+}
+
+/// The author id of a repo, if the repo is in the db.
+pub fn get_git_repo_author(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Option<i32>, Error> {
+    git_repos_table
+        .select(git_repos_author_id)
+        .filter(git_repos_key_id.eq(key_id))
+        .first(connection)
+        .optional()
+}
+
+/// The name of a repo, from the latest about its author posted or the `name` it was created with.
+pub fn get_git_repo_name(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Option<String>, Error> {
+    if let Some(Value::String(name)) = get_key_detail(connection, key_id, "name")? {
+        return Ok(Some(name));
+    }
+
+    let name = messages_table
+        .select(messages_content)
+        .filter(messages_key_id.eq(key_id))
+        .first::<Option<String>>(connection)
+        .optional()?
+        .and_then(|content| content)
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|content| content["name"].as_str().map(|name| name.to_owned()));
+
+    Ok(name)
+}
+
+/// The key ids and asserted timestamps of the pushes to a repo, newest first.
+///
+/// Only the repo's author can push to it, updates by anyone else are ignored. Like post edits,
+/// updates are checked against the repo's author when they're queried because they can arrive
+/// before the repo.
+pub fn get_git_updates(
+    connection: &SqliteConnection,
+    repo_key_id: i32,
+) -> Result<Vec<(i32, i64)>, Error> {
+    git_updates_table
+        .inner_join(
+            git_repos_table.on(git_repos_key_id
+                .eq(git_updates_repo_key_id)
+                .and(git_repos_author_id.eq(git_updates_author_id))),
+        )
+        .select((git_updates_key_id, git_updates_asserted_timestamp))
+        .filter(git_updates_repo_key_id.eq(repo_key_id))
+        .order(git_updates_flume_seq.desc())
+        .load(connection)
+}
+
+/// The refs an update pushed, by name. A ref without a sha1 was deleted.
+pub fn get_git_update_refs(
+    connection: &SqliteConnection,
+    update_key_id: i32,
+) -> Result<Vec<(String, Option<String>)>, Error> {
+    git_refs_table
+        .inner_join(git_updates_table.on(git_updates_flume_seq.eq(git_refs_update_flume_seq)))
+        .select((git_refs_name, git_refs_sha1))
+        .filter(git_updates_key_id.eq(update_key_id))
+        .order(git_refs_name.asc())
+        .load(connection)
+}
+
+/// The sha1s and titles of the commits an update pushed, in the order they were listed.
+pub fn get_git_update_commits(
+    connection: &SqliteConnection,
+    update_key_id: i32,
+) -> Result<Vec<(String, Option<String>)>, Error> {
+    let content = messages_table
+        .select(messages_content)
+        .filter(messages_key_id.eq(update_key_id))
+        .first::<Option<String>>(connection)?
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+
+    let commits = match &content["commits"] {
+        Value::Array(commits) => commits
+            .iter()
+            .filter_map(|commit| {
+                commit["sha1"].as_str().map(|sha1| {
+                    let title = commit["title"].as_str().map(|title| title.to_owned());
+                    (sha1.to_owned(), title)
+                })
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(commits)
+}
+
+/// The current refs of a repo and the sha1s they point at, by name. Later updates replace or
+/// delete the refs of earlier ones.
+pub fn get_git_refs(
+    connection: &SqliteConnection,
+    repo_key_id: i32,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut refs = BTreeMap::new();
+
+    git_refs_table
+        .inner_join(git_updates_table.on(git_updates_flume_seq.eq(git_refs_update_flume_seq)))
+        .inner_join(
+            git_repos_table.on(git_repos_key_id
+                .eq(git_updates_repo_key_id)
+                .and(git_repos_author_id.eq(git_updates_author_id))),
+        )
+        .select((git_refs_name, git_refs_sha1))
+        .filter(git_updates_repo_key_id.eq(repo_key_id))
+        .order(git_refs_update_flume_seq.asc())
+        .load::<(String, Option<String>)>(connection)?
+        .into_iter()
+        .for_each(|(name, sha1)| match sha1 {
+            Some(sha1) => {
+                refs.insert(name, sha1);
+            }
+            None => {
+                refs.remove(&name);
+            }
+        });
+
+    Ok(refs.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::git_repos::{
+        get_git_refs, get_git_updates, insert_git_repo, insert_git_update,
+    };
+    use crate::db::models::keys::find_or_create_key;
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn only_the_author_can_push() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let message = |key: &str, content: Value| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.content = content;
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                (msg, key_id)
+            };
+            let update = |refs: Value| json!({"type": "git-update", "repo": "%repo", "refs": refs});
+
+            // The first push arrives before the repo.
+            let (first, first_id) = message(
+                "%first",
+                update(json!({"refs/heads/master": "aaa", "refs/heads/wip": "bbb"})),
+            );
+            insert_git_update(&connection, &first, first_id, 1, 1)?;
+
+            let (repo, repo_id) = message("%repo", json!({"type": "git-repo"}));
+            insert_git_repo(&connection, &repo, repo_id, 1, 2)?;
+
+            let (second, second_id) = message(
+                "%second",
+                update(json!({"refs/heads/master": "ccc", "refs/heads/wip": null})),
+            );
+            insert_git_update(&connection, &second, second_id, 1, 3)?;
+
+            let (vandalism, vandalism_id) =
+                message("%vandalism", update(json!({"refs/heads/master": "ddd"})));
+            insert_git_update(&connection, &vandalism, vandalism_id, 2, 4)?;
+
+            assert_eq!(
+                get_git_updates(&connection, repo_id)?
+                    .into_iter()
+                    .map(|(key_id, _)| key_id)
+                    .collect::<Vec<_>>(),
+                vec![second_id, first_id]
+            );
+            assert_eq!(
+                get_git_refs(&connection, repo_id)?,
+                vec![("refs/heads/master".to_owned(), "ccc".to_owned())]
+            );
+            Ok(())
+        });
+    }
+}
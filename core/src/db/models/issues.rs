@@ -0,0 +1,353 @@
+use super::git_repos::get_git_repo_author;
+use super::keys::find_or_create_key;
+use crate::db::schema::issue_edits::dsl::{
+    author_id as issue_edits_author_id, flume_seq as issue_edits_flume_seq,
+    is_open as issue_edits_is_open, issue_edits as issue_edits_table,
+    issue_key_id as issue_edits_issue_key_id, title as issue_edits_title,
+};
+use crate::db::schema::issues::dsl::{
+    asserted_timestamp as issues_asserted_timestamp, author_id as issues_author_id,
+    flume_seq as issues_flume_seq, issues as issues_table, key_id as issues_key_id,
+    repo_key_id as issues_repo_key_id,
+};
+use crate::db::schema::messages::dsl::{
+    content as messages_content, key_id as messages_key_id, messages as messages_table,
+};
+use crate::db::schema::pull_requests::dsl::{
+    asserted_timestamp as pull_requests_asserted_timestamp, author_id as pull_requests_author_id,
+    branch as pull_requests_branch, flume_seq as pull_requests_flume_seq,
+    head_branch as pull_requests_head_branch, head_repo_key_id as pull_requests_head_repo_key_id,
+    key_id as pull_requests_key_id, pull_requests as pull_requests_table,
+    repo_key_id as pull_requests_repo_key_id,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::prelude::*;
+use diesel::{insert_into, insert_or_ignore_into};
+use serde_json::Value;
+
+/// An issue, with its state after the edits by its author and the repo's author.
+#[derive(Debug, PartialEq)]
+pub struct Issue {
+    pub key_id: i32,
+    pub author_id: i32,
+    pub asserted_timestamp: i64,
+    pub is_open: bool,
+    /// The title from the latest edit, the issue itself or the first line of its text.
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// A pull request. Pull requests are issues too, so they can be closed and commented on.
+#[derive(Debug, PartialEq)]
+pub struct PullRequest {
+    pub issue: Issue,
+    /// The branch to merge into.
+    pub branch: Option<String>,
+    /// The repo with the changes, it's often a fork.
+    pub head_repo_key_id: Option<i32>,
+    /// The branch with the changes.
+    pub head_branch: Option<String>,
+}
+
+// Caller must check that the message is actually an issue.
+pub fn insert_issue(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    if let Value::String(project) = &message.value.content["project"] {
+        insert_into(issues_table)
+            .values((
+                issues_flume_seq.eq(seq),
+                issues_asserted_timestamp.eq(message.value.timestamp as i64),
+                issues_key_id.eq(message_key_id),
+                issues_repo_key_id.eq(find_or_create_key(connection, project)?),
+                issues_author_id.eq(author_id),
+            ))
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+// Caller must check that the message is actually a pull-request.
+pub fn insert_pull_request(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let content = &message.value.content;
+
+    if let Value::String(repo) = &content["repo"] {
+        let head_repo_key_id = match &content["head_repo"] {
+            Value::String(head_repo) => Some(find_or_create_key(connection, head_repo)?),
+            _ => None,
+        };
+
+        insert_into(pull_requests_table)
+            .values((
+                pull_requests_flume_seq.eq(seq),
+                pull_requests_asserted_timestamp.eq(message.value.timestamp as i64),
+                pull_requests_key_id.eq(message_key_id),
+                pull_requests_repo_key_id.eq(find_or_create_key(connection, repo)?),
+                pull_requests_author_id.eq(author_id),
+                pull_requests_branch.eq(content["branch"].as_str()),
+                pull_requests_head_repo_key_id.eq(head_repo_key_id),
+                pull_requests_head_branch.eq(content["head_branch"].as_str()),
+            ))
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+/// Stores the changes a message makes to issues. Changes are `{link, open, title}`s in an
+/// `issues` list, which `issue-edit`s and comments can both have. Old `issue-edit`s change a
+/// single `issue` instead.
+pub fn insert_issue_edits(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let content = &message.value.content;
+
+    let edits = match (&content["issues"], &content["issue"]) {
+        (Value::Array(edits), _) => edits.iter().collect(),
+        (_, Value::String(_)) => vec![content],
+        _ => Vec::new(),
+    };
+
+    for edit in edits {
+        let issue = edit["link"].as_str().or_else(|| edit["issue"].as_str());
+
+        if let Some(issue) = issue {
+            insert_or_ignore_into(issue_edits_table)
+                .values((
+                    issue_edits_flume_seq.eq(seq),
+                    issue_edits_issue_key_id.eq(find_or_create_key(connection, issue)?),
+                    issue_edits_author_id.eq(author_id),
+                    issue_edits_is_open.eq(edit["open"].as_bool()),
+                    issue_edits_title.eq(edit["title"].as_str()),
+                ))
+                .execute(connection)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The issues of a repo, newest first.
+pub fn get_issues(connection: &SqliteConnection, repo_key_id: i32) -> Result<Vec<Issue>, Error> {
+    let repo_author_id = get_git_repo_author(connection, repo_key_id)?;
+
+    issues_table
+        .inner_join(messages_table.on(messages_key_id.eq(issues_key_id)))
+        .select((
+            issues_key_id,
+            issues_author_id,
+            issues_asserted_timestamp,
+            messages_content,
+        ))
+        .filter(issues_repo_key_id.eq(repo_key_id))
+        .order(issues_flume_seq.desc())
+        .load::<(i32, i32, i64, Option<String>)>(connection)?
+        .into_iter()
+        .map(|(key_id, author_id, asserted_timestamp, content)| {
+            build_issue(
+                connection,
+                key_id,
+                author_id,
+                asserted_timestamp,
+                content,
+                repo_author_id,
+            )
+        })
+        .collect()
+}
+
+/// The pull requests to a repo, newest first.
+pub fn get_pull_requests(
+    connection: &SqliteConnection,
+    repo_key_id: i32,
+) -> Result<Vec<PullRequest>, Error> {
+    let repo_author_id = get_git_repo_author(connection, repo_key_id)?;
+
+    pull_requests_table
+        .inner_join(messages_table.on(messages_key_id.eq(pull_requests_key_id)))
+        .select((
+            pull_requests_key_id,
+            pull_requests_author_id,
+            pull_requests_asserted_timestamp,
+            messages_content,
+            pull_requests_branch,
+            pull_requests_head_repo_key_id,
+            pull_requests_head_branch,
+        ))
+        .filter(pull_requests_repo_key_id.eq(repo_key_id))
+        .order(pull_requests_flume_seq.desc())
+        .load::<(
+            i32,
+            i32,
+            i64,
+            Option<String>,
+            Option<String>,
+            Option<i32>,
+            Option<String>,
+        )>(connection)?
+        .into_iter()
+        .map(
+            |(
+                key_id,
+                author_id,
+                asserted_timestamp,
+                content,
+                branch,
+                head_repo_key_id,
+                head_branch,
+            )| {
+                let issue = build_issue(
+                    connection,
+                    key_id,
+                    author_id,
+                    asserted_timestamp,
+                    content,
+                    repo_author_id,
+                )?;
+
+                Ok(PullRequest {
+                    issue,
+                    branch,
+                    head_repo_key_id,
+                    head_branch,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Applies the edits by the issue's author and the repo's author, oldest first. Edits by anyone
+/// else are ignored.
+fn build_issue(
+    connection: &SqliteConnection,
+    key_id: i32,
+    author_id: i32,
+    asserted_timestamp: i64,
+    content: Option<String>,
+    repo_author_id: Option<i32>,
+) -> Result<Issue, Error> {
+    let content = content
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+    let text = content["text"].as_str().unwrap_or("").to_owned();
+
+    let mut is_open = true;
+    let mut title = content["title"]
+        .as_str()
+        .map(|title| title.to_owned())
+        .or_else(|| title_from_text(&text));
+
+    let editor_ids: Vec<i32> = Some(author_id).into_iter().chain(repo_author_id).collect();
+
+    issue_edits_table
+        .select((issue_edits_is_open, issue_edits_title))
+        .filter(issue_edits_issue_key_id.eq(key_id))
+        .filter(issue_edits_author_id.eq_any(editor_ids))
+        .order(issue_edits_flume_seq.asc())
+        .load::<(Option<bool>, Option<String>)>(connection)?
+        .into_iter()
+        .for_each(|(edit_is_open, edit_title)| {
+            is_open = edit_is_open.unwrap_or(is_open);
+            title = edit_title.or_else(|| title.take());
+        });
+
+    Ok(Issue {
+        key_id,
+        author_id,
+        asserted_timestamp,
+        is_open,
+        title,
+        text,
+    })
+}
+
+/// Issues without a title are titled by the first line of their text, like a commit message.
+fn title_from_text(text: &str) -> Option<String> {
+    text.lines()
+        .map(|line| line.trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::git_repos::insert_git_repo;
+    use crate::db::models::issues::{get_issues, insert_issue, insert_issue_edits};
+    use crate::db::models::keys::find_or_create_key;
+    use crate::db::models::messages::insert_message;
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn only_the_authors_can_close_issues() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let message = |key: &str, seq: i64, author_id: i32, content: Value| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.content = content;
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                insert_message(&connection, &msg, seq, key_id, false, author_id).unwrap();
+                (msg, key_id)
+            };
+            let close = |open: bool| {
+                json!({"type": "issue-edit", "issues": [{"link": "%issue", "open": open}]})
+            };
+
+            let (repo, repo_id) = message("%repo", 1, 1, json!({"type": "git-repo"}));
+            insert_git_repo(&connection, &repo, repo_id, 1, 1)?;
+
+            let (issue, issue_id) = message(
+                "%issue",
+                2,
+                2,
+                json!({"type": "issue", "project": "%repo", "text": "# It's broken\n\nHelp"}),
+            );
+            insert_issue(&connection, &issue, issue_id, 2, 2)?;
+
+            let (vandalism, _) = message("%vandalism", 3, 3, close(false));
+            insert_issue_edits(&connection, &vandalism, 3, 3)?;
+
+            let issues = get_issues(&connection, repo_id)?;
+            assert!(issues[0].is_open);
+            assert_eq!(issues[0].title, Some("It's broken".to_owned()));
+
+            let (fix, _) = message("%fix", 4, 1, close(false));
+            insert_issue_edits(&connection, &fix, 1, 4)?;
+            assert!(!get_issues(&connection, repo_id)?[0].is_open);
+
+            let (reopen, _) = message(
+                "%reopen",
+                5,
+                2,
+                json!({"type": "issue-edit", "issue": "%issue", "open": true, "title": "Still broken"}),
+            );
+            insert_issue_edits(&connection, &reopen, 2, 5)?;
+
+            let issues = get_issues(&connection, repo_id)?;
+            assert!(issues[0].is_open);
+            assert_eq!(issues[0].title, Some("Still broken".to_owned()));
+            Ok(())
+        });
+    }
+}
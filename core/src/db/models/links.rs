@@ -1,28 +1,204 @@
-use crate::db::SqliteConnection;
-use crate::db::*;
+use crate::db::{Error, SqliteConnection};
 
 use super::keys::find_or_create_key;
-use crate::db::schema::links::dsl::{link_from_key_id, link_to_key_id, links as links_table};
+use crate::db::schema::links::dsl::{link_from_key_id, link_to_key_id, links as links_table, rel};
 use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
 
-pub fn insert_links(
-    connection: &SqliteConnection,
-    links: &[&serde_json::Value],
-    message_key_id: i32,
-) {
+pub fn insert_links(connection: &SqliteConnection, links: &[(String, &str)], message_key_id: i32) {
     links
         .iter()
-        .filter(|link| link.is_string())
-        .map(|link| link.as_str().unwrap())
-        .filter(|link| link.starts_with('%'))
-        .map(|link| find_or_create_key(&connection, link).unwrap())
-        .for_each(|link_id| {
+        .filter(|(_, link)| link.starts_with('%'))
+        .map(|(link_rel, link)| (find_or_create_key(connection, link).unwrap(), link_rel))
+        .for_each(|(link_id, link_rel)| {
             insert_into(links_table)
                 .values((
                     link_from_key_id.eq(message_key_id),
                     link_to_key_id.eq(link_id),
+                    rel.eq(link_rel),
                 ))
                 .execute(connection)
                 .unwrap();
         });
 }
+
+/// What a link points to. Links to messages live in `links`, to authors in `mentions` and to
+/// blobs in `blob_links`.
+///
+/// The order of the variants is used to order links from the same message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LinkKind {
+    Message,
+    Author,
+    Blob,
+}
+
+#[derive(Clone, Debug)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub id: i32,
+    pub link_from_key_id: i32,
+    /// The key, author or blob id, depending on the `kind`.
+    pub link_to_id: i32,
+    pub rel: String,
+    pub flume_seq: i64,
+}
+
+/// Where a link sits in the order of all links: by the flume seq of the message it's from, then
+/// by kind, then by insertion.
+pub type LinkPosition = (i64, LinkKind, i32);
+
+impl Link {
+    pub fn position(&self) -> LinkPosition {
+        (self.flume_seq, self.kind, self.id)
+    }
+}
+
+impl From<i64> for LinkKind {
+    fn from(kind: i64) -> LinkKind {
+        match kind {
+            0 => LinkKind::Message,
+            1 => LinkKind::Author,
+            _ => LinkKind::Blob,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct LinkFilter {
+    pub from_key_id: Option<i32>,
+    pub to: Option<(LinkKind, i32)>,
+    pub rel: Option<String>,
+}
+
+pub enum LinkPage {
+    /// The oldest links first, after the position if there is one.
+    After(Option<LinkPosition>, i64),
+    /// The newest links first, before the position if there is one.
+    Before(Option<LinkPosition>, i64),
+}
+
+/// The links from all three link tables, with the kind of link and the flume seq of the message
+/// it's from.
+const ALL_LINKS: &str = "\
+    SELECT 0 AS kind, links.id, links.link_from_key_id, links.link_to_key_id AS link_to_id, \
+        links.rel, messages.flume_seq \
+    FROM links JOIN messages ON messages.key_id = links.link_from_key_id \
+    UNION ALL \
+    SELECT 1, mentions.id, mentions.link_from_key_id, mentions.link_to_author_id, \
+        mentions.rel, messages.flume_seq \
+    FROM mentions JOIN messages ON messages.key_id = mentions.link_from_key_id \
+    UNION ALL \
+    SELECT 2, blob_links.id, blob_links.link_from_key_id, blob_links.link_to_blob_id, \
+        blob_links.rel, messages.flume_seq \
+    FROM blob_links JOIN messages ON messages.key_id = blob_links.link_from_key_id";
+
+/// Filters `ALL_LINKS` by a `LinkFilter`, each part is skipped when its first value is null.
+const FILTER: &str = "\
+    (? IS NULL OR link_from_key_id = ?) \
+    AND (? IS NULL OR (kind = ? AND link_to_id = ?)) \
+    AND (? IS NULL OR rel = ?)";
+
+// Binds the values of a `LinkFilter` to the `?`s in `FILTER`.
+macro_rules! bind_filter {
+    ($query:expr, $filter:expr) => {{
+        let to_kind = $filter.to.map(|(kind, _)| kind as i64);
+        let to_id = $filter.to.map(|(_, id)| id);
+
+        $query
+            .bind::<Nullable<Integer>, _>($filter.from_key_id)
+            .bind::<Nullable<Integer>, _>($filter.from_key_id)
+            .bind::<Nullable<BigInt>, _>(to_kind)
+            .bind::<Nullable<BigInt>, _>(to_kind)
+            .bind::<Nullable<Integer>, _>(to_id)
+            .bind::<Nullable<Text>, _>($filter.rel.clone())
+            .bind::<Nullable<Text>, _>($filter.rel.clone())
+    }};
+}
+
+#[derive(QueryableByName)]
+struct LinkRow {
+    #[sql_type = "BigInt"]
+    kind: i64,
+    #[sql_type = "Nullable<Integer>"]
+    id: Option<i32>,
+    #[sql_type = "Integer"]
+    link_from_key_id: i32,
+    #[sql_type = "Integer"]
+    link_to_id: i32,
+    #[sql_type = "Text"]
+    rel: String,
+    #[sql_type = "Nullable<BigInt>"]
+    flume_seq: Option<i64>,
+}
+
+/// Finds a page of links from all three link tables. The link at the page's position is
+/// included when `inclusive` is set.
+pub fn get_links(
+    connection: &SqliteConnection,
+    filter: &LinkFilter,
+    page: &LinkPage,
+    inclusive: bool,
+) -> Result<Vec<Link>, Error> {
+    let (position, limit, comparison, order) = match (page, inclusive) {
+        (LinkPage::After(position, limit), false) => (position, limit, ">", "ASC"),
+        (LinkPage::After(position, limit), true) => (position, limit, ">=", "ASC"),
+        (LinkPage::Before(position, limit), false) => (position, limit, "<", "DESC"),
+        (LinkPage::Before(position, limit), true) => (position, limit, "<=", "DESC"),
+    };
+    let (seq, kind, id) = match *position {
+        Some((seq, kind, id)) => (Some(seq), Some(kind as i64), Some(id)),
+        None => (None, None, None),
+    };
+
+    let query = format!(
+        "SELECT * FROM ({links}) WHERE {filter} \
+         AND (? IS NULL OR (flume_seq, kind, id) {comparison} (?, ?, ?)) \
+         ORDER BY flume_seq {order}, kind {order}, id {order} \
+         LIMIT ?",
+        links = ALL_LINKS,
+        filter = FILTER,
+        comparison = comparison,
+        order = order,
+    );
+
+    let links = bind_filter!(sql_query(query), filter)
+        .bind::<Nullable<BigInt>, _>(seq)
+        .bind::<Nullable<BigInt>, _>(seq)
+        .bind::<Nullable<BigInt>, _>(kind)
+        .bind::<Nullable<Integer>, _>(id)
+        .bind::<BigInt, _>(*limit)
+        .load::<LinkRow>(connection)?
+        .into_iter()
+        .map(|row| Link {
+            kind: LinkKind::from(row.kind),
+            id: row.id.unwrap_or(0),
+            link_from_key_id: row.link_from_key_id,
+            link_to_id: row.link_to_id,
+            rel: row.rel,
+            flume_seq: row.flume_seq.unwrap_or(0),
+        })
+        .collect();
+
+    Ok(links)
+}
+
+/// Counts all the links that match the filter, from all three link tables.
+pub fn count_links(connection: &SqliteConnection, filter: &LinkFilter) -> Result<i64, Error> {
+    #[derive(QueryableByName)]
+    struct Count {
+        #[sql_type = "BigInt"]
+        count: i64,
+    }
+
+    let query = format!(
+        "SELECT COUNT(*) AS count FROM ({}) WHERE {}",
+        ALL_LINKS, FILTER
+    );
+
+    Ok(bind_filter!(sql_query(query), filter)
+        .get_result::<Count>(connection)?
+        .count)
+}
@@ -37,7 +37,7 @@ pub fn insert_message(
 ) -> Result<usize, Error> {
     let root_key_id = match message.value.content["root"] {
         Value::String(ref key) => {
-            let id = find_or_create_key(&connection, &key).unwrap();
+            let id = find_or_create_key(connection, key).unwrap();
             Some(id)
         }
         _ => None,
@@ -45,7 +45,7 @@ pub fn insert_message(
 
     let fork_key_id = match message.value.content["fork"] {
         Value::String(ref key) => {
-            let id = find_or_create_key(&connection, &key).unwrap();
+            let id = find_or_create_key(connection, key).unwrap();
             Some(id)
         }
         _ => None,
@@ -59,12 +59,12 @@ pub fn insert_message(
         asserted_time: Some(message.value.timestamp as i64),
         root_key_id,
         fork_key_id,
-        author_id: author_id,
+        author_id,
         content_type: message.value.content["type"]
             .as_str()
             .map(|content_type| content_type.to_string()),
         content: Some(message.value.content.to_string()),
-        is_decrypted: is_decrypted,
+        is_decrypted,
     };
 
     insert_into(messages_table)
@@ -1,17 +1,31 @@
 pub mod abouts;
+pub mod author_texts;
 pub mod authors;
 pub mod blob_links;
 pub mod blobs;
+pub mod blogs;
 pub mod branches;
+pub mod channel_subscriptions;
+pub mod channels;
 pub mod contacts;
+pub mod content_warnings;
+pub mod gatherings;
+pub mod git_repos;
+pub mod issues;
 pub mod keys;
 pub mod links;
 pub mod mentions;
 pub mod messages;
+pub mod notifications;
+pub mod polls;
+pub mod post_edits;
 pub mod posts;
+pub mod recipients;
+pub mod tags;
 pub mod texts;
 pub mod votes;
 
+use crate::db::indexer::MessageIndexer;
 use crate::db::{Error, SqliteConnection};
 use crate::ssb_message::*;
 use base64::decode;
@@ -20,14 +34,25 @@ use private_box::SecretKey;
 use serde_json::Value;
 
 use abouts::insert_abouts;
+use author_texts::insert_or_update_author_texts;
 use blob_links::insert_blob_links;
+use blogs::insert_blog;
 use branches::insert_branches;
+use channel_subscriptions::insert_or_update_channel_subscriptions;
+use channels::insert_channels;
 use contacts::insert_or_update_contacts;
+use content_warnings::insert_content_warning;
+use git_repos::{insert_git_repo, insert_git_update};
+use issues::{insert_issue, insert_issue_edits, insert_pull_request};
 use keys::find_or_create_key;
 use links::insert_links;
 use mentions::insert_mentions;
 use messages::insert_message;
+use polls::{insert_poll, insert_position};
+use post_edits::insert_post_edit;
 use posts::insert_post;
+use recipients::insert_recipients;
+use tags::insert_tag;
 use texts::insert_texts;
 use votes::insert_or_update_votes;
 
@@ -36,13 +61,14 @@ use authors::find_or_create_author;
 pub fn append_item(
     connection: &SqliteConnection,
     secret_keys: &[SecretKey],
+    indexers: &[Box<dyn MessageIndexer>],
     seq: FlumeSequence,
     item: &[u8],
 ) -> Result<(), Error> {
     let result = serde_json::from_slice(item);
 
     // If there are deleted records with all bytes zerod then we should just skip this message.
-    if let Err(_) = result {
+    if result.is_err() {
         return Ok(());
     }
 
@@ -50,26 +76,58 @@ pub fn append_item(
 
     let (is_decrypted, message) = attempt_decryption(message, secret_keys);
 
-    let message_key_id = find_or_create_key(&connection, &message.key)?;
-    let author_id = find_or_create_author(&connection, &message.value.author)?;
+    let message_key_id = find_or_create_key(connection, &message.key)?;
+    let author_id = find_or_create_author(connection, &message.value.author)?;
+
+    let links = find_links(&message.value.content);
+    insert_links(connection, links.as_slice(), message_key_id);
+    insert_mentions(connection, links.as_slice(), message_key_id);
+    insert_blob_links(connection, links.as_slice(), message_key_id);
 
-    // votes are a kind of backlink, but we want to put them in their own table.
     match &message.value.content["type"] {
+        // votes are a kind of backlink, but we also want to put them in their own table.
         Value::String(type_string) if type_string == "vote" => {
-            insert_or_update_votes(connection, &message);
-        }
-        _ => {
-            let mut links = Vec::new();
-            find_values_in_object_by_key(&message.value.content, "link", &mut links);
-            insert_links(connection, links.as_slice(), message_key_id);
-            insert_mentions(connection, links.as_slice(), message_key_id);
-            insert_blob_links(connection, links.as_slice(), message_key_id);
+            insert_or_update_votes(connection, &message, message_key_id);
         }
-    }
-
-    match &message.value.content["type"] {
         Value::String(type_string) if type_string == "post" => {
             insert_post(connection, &message, message_key_id, author_id, seq as i64)?;
+            insert_channels(connection, &message, message_key_id);
+            insert_content_warning(connection, &message, message_key_id);
+            // Comments on issues can close or reopen them.
+            insert_issue_edits(connection, &message, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "post-edit" => {
+            insert_post_edit(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "git-repo" => {
+            insert_git_repo(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "git-update" => {
+            insert_git_update(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "issue" => {
+            insert_issue(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "issue-edit" => {
+            insert_issue_edits(connection, &message, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "pull-request" => {
+            insert_pull_request(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "blog" => {
+            insert_blog(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "poll" => {
+            insert_poll(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "position" => {
+            insert_position(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "tag" => {
+            insert_tag(connection, &message, message_key_id, author_id, seq as i64)?;
+        }
+        Value::String(type_string) if type_string == "channel" => {
+            insert_or_update_channel_subscriptions(connection, &message, author_id);
         }
         _ => {}
     }
@@ -84,9 +142,17 @@ pub fn append_item(
         author_id,
     )?;
     insert_or_update_contacts(connection, &message, message_key_id, is_decrypted);
+    if is_decrypted {
+        insert_recipients(connection, &message, message_key_id);
+    }
     insert_abouts(connection, &message, message_key_id);
+    insert_or_update_author_texts(connection, &message, author_id)?;
     insert_texts(connection, &message, message_key_id);
 
+    for indexer in indexers {
+        indexer.index(connection, &message, message_key_id)?;
+    }
+
     Ok(())
 }
 
@@ -117,8 +183,66 @@ fn attempt_decryption(mut message: SsbMessage, secret_keys: &[SecretKey]) -> (bo
     (is_decrypted, message)
 }
 
+/// The fields of a message's content that hold refs to link to, besides the `link`s of objects.
+const LINK_FIELDS: [&str; 4] = ["root", "branch", "mentions", "contact"];
+
+/// Finds the refs (message, feed or blob ids) that a message links to, along with their rel.
+///
+/// Those are the `link`s of any objects in the content, eg. `vote.link` or `image.link`, and
+/// the refs in the `root`, `branch`, `mentions` and `contact` fields. The rel is the path of keys
+/// where the ref was found, joined with `.`. Lists don't add to the path, and `link`s of objects
+/// in a list are named after the list, so `branch` and `mentions` are the rels of refs in those
+/// lists.
+pub fn find_links(content: &Value) -> Vec<(String, &str)> {
+    let mut links = Vec::new();
+    find_links_in_value(content, "", false, &mut links);
+    links
+}
+
+fn find_links_in_value<'a>(
+    value: &'a Value,
+    path: &str,
+    is_list_item: bool,
+    links: &mut Vec<(String, &'a str)>,
+) {
+    match value {
+        Value::Array(arr) => {
+            for val in arr {
+                find_links_in_value(val, path, true, links);
+            }
+        }
+        Value::Object(kv) => {
+            for (key, val) in kv {
+                let is_link_field = path.is_empty() && LINK_FIELDS.contains(&key.as_str());
+                let rel = match (path, key.as_str()) {
+                    ("", _) => key.to_owned(),
+                    (_, "link") if is_list_item => path.to_owned(),
+                    _ => format!("{}.{}", path, key),
+                };
+
+                match val {
+                    Value::String(link) if key == "link" || is_link_field => {
+                        links.push((rel, link))
+                    }
+                    Value::Array(items) if is_link_field => {
+                        for item in items {
+                            match item {
+                                Value::String(link) => links.push((rel.clone(), link)),
+                                _ => find_links_in_value(item, &rel, true, links),
+                            }
+                        }
+                    }
+                    _ => find_links_in_value(val, &rel, false, links),
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::db::models::find_links;
     use crate::db::models::keys::Key;
     use crate::db::models::messages::Message;
     use crate::db::schema::keys::dsl::*;
@@ -127,12 +251,14 @@ mod tests {
     use diesel::prelude::*;
     use diesel::result::Error;
 
-    //TODO: skipping this.
+    #[test]
     fn insert_message() {
         let connection = establish_connection();
         connection.test_transaction::<_, Error, _>(|| {
-            let mut new_message = Message::default();
-            new_message.flume_seq = Some(1234);
+            let new_message = Message {
+                flume_seq: Some(1234),
+                ..Message::default()
+            };
 
             diesel::insert_into(messages)
                 .values(&new_message)
@@ -149,6 +275,76 @@ mod tests {
         })
     }
     #[test]
+    fn find_links_with_rels() {
+        let post = serde_json::json!({
+            "type": "post",
+            "root": "%MPB9vxHO0pvi2ve2wh6Do05ZrV7P6HjMPlKYD9xb2ic=.sha256",
+            "branch": ["%K9VPgxEtTEfsDPlPd5B1yuXQDaHyPC+8Ps5ZXOqSs+8=.sha256"],
+            "mentions": [{"link": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519", "name": "piet"}],
+            "image": {"link": "&3HiaMpzd3zsXOJRwg+Lm2KNmxFRLVDx5FWAEn4V2dWo=.sha256"},
+            "text": "@piet, %hello and &friends"
+        });
+
+        let mut links = find_links(&post);
+        links.sort();
+
+        assert_eq!(
+            links,
+            vec![
+                (
+                    "branch".to_string(),
+                    "%K9VPgxEtTEfsDPlPd5B1yuXQDaHyPC+8Ps5ZXOqSs+8=.sha256"
+                ),
+                (
+                    "image.link".to_string(),
+                    "&3HiaMpzd3zsXOJRwg+Lm2KNmxFRLVDx5FWAEn4V2dWo=.sha256"
+                ),
+                (
+                    "mentions".to_string(),
+                    "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+                ),
+                (
+                    "root".to_string(),
+                    "%MPB9vxHO0pvi2ve2wh6Do05ZrV7P6HjMPlKYD9xb2ic=.sha256"
+                ),
+            ]
+        );
+    }
+    #[test]
+    fn find_links_only_in_link_fields() {
+        let vote = serde_json::json!({
+            "type": "vote",
+            "vote": {"link": "%MPB9vxHO0pvi2ve2wh6Do05ZrV7P6HjMPlKYD9xb2ic=.sha256", "value": 1}
+        });
+        assert_eq!(
+            find_links(&vote),
+            vec![(
+                "vote.link".to_string(),
+                "%MPB9vxHO0pvi2ve2wh6Do05ZrV7P6HjMPlKYD9xb2ic=.sha256"
+            )]
+        );
+
+        let contact = serde_json::json!({
+            "type": "contact",
+            "contact": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+            "following": true
+        });
+        assert_eq!(
+            find_links(&contact),
+            vec![(
+                "contact".to_string(),
+                "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519"
+            )]
+        );
+
+        let about = serde_json::json!({
+            "type": "about",
+            "about": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+            "name": "piet"
+        });
+        assert_eq!(find_links(&about), Vec::<(String, &str)>::new());
+    }
+    #[test]
     fn find_or_create_key_when_key_exists() {
         let connection = establish_connection();
         connection.test_transaction::<_, Error, _>(|| Ok(()))
@@ -169,31 +365,3 @@ mod tests {
         })
     }
 }
-
-pub fn find_values_in_object_by_key<'a>(
-    obj: &'a serde_json::Value,
-    key: &str,
-    values: &mut Vec<&'a serde_json::Value>,
-) {
-    if let Some(val) = obj.get(key) {
-        values.push(val)
-    }
-
-    match obj {
-        Value::Array(arr) => {
-            for val in arr {
-                find_values_in_object_by_key(val, key, values);
-            }
-        }
-        Value::Object(kv) => {
-            for val in kv.values() {
-                match val {
-                    Value::Object(_) => find_values_in_object_by_key(val, key, values),
-                    Value::Array(_) => find_values_in_object_by_key(val, key, values),
-                    _ => (),
-                }
-            }
-        }
-        _ => (),
-    }
-}
@@ -0,0 +1,263 @@
+use crate::db::schema::contacts::dsl::{
+    contact_author_id as contacts_contact_author_id, contacts as contacts_table,
+    is_decrypted as contacts_is_decrypted, key_id as contacts_key_id, state as contacts_state,
+};
+use crate::db::schema::mentions::dsl::{
+    link_from_key_id as mentions_link_from_key_id, link_to_author_id as mentions_link_to_author_id,
+    mentions as mentions_table, rel as mentions_rel,
+};
+use crate::db::schema::messages;
+use crate::db::schema::messages::dsl::{
+    author_id as messages_author_id, content_type as messages_content_type,
+    flume_seq as messages_flume_seq, key_id as messages_key_id, messages as messages_table,
+    root_key_id as messages_root_key_id,
+};
+use crate::db::schema::reply_posts::dsl::{
+    author_id as reply_posts_author_id, key_id as reply_posts_key_id,
+    reply_posts as reply_posts_table, root_post_id as reply_posts_root_post_id,
+};
+use crate::db::schema::root_posts::dsl::{
+    author_id as root_posts_author_id, key_id as root_posts_key_id, root_posts as root_posts_table,
+};
+use crate::db::schema::votes::dsl::{
+    link_from_key_id as votes_link_from_key_id, link_to_key_id as votes_link_to_key_id,
+    value as votes_value, votes as votes_table,
+};
+use crate::db::{Error, SqliteConnection};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::query_builder::{BoxedSelectStatement, QueryFragment};
+use diesel::query_source::QuerySource;
+use diesel::sql_types::{BigInt, Integer, Nullable};
+use diesel::sqlite::Sqlite;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    /// A post that mentions the author.
+    Mention,
+    /// A reply in a thread the author posted in, that doesn't mention them.
+    Reply,
+    /// A like on one of the author's posts.
+    Like,
+    /// Someone following the author.
+    Follow,
+    /// Someone blocking the author.
+    Block,
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    /// The flume seq of the message that caused the notification.
+    pub flume_seq: i64,
+    /// The message that caused the notification.
+    pub key_id: i32,
+    /// Who caused the notification.
+    pub author_id: i32,
+    /// The root of the thread for replies, the liked post for likes.
+    pub target_key_id: Option<i32>,
+}
+
+/// The first page of an author's notifications after a flume seq, and how many there are.
+#[derive(Default)]
+pub struct Notifications {
+    /// The first notifications after the flume seq, oldest first.
+    pub notifications: Vec<Notification>,
+    /// The number of each kind of notification after the flume seq, across all pages.
+    pub counts: HashMap<NotificationKind, i64>,
+}
+
+/// The `(flume_seq, key_id, author_id, target_key_id)` of the messages that cause notifications.
+type NotificationRow = (Nullable<BigInt>, Integer, Integer, Nullable<Integer>);
+
+/// Adds the first `limit` notifications of one kind, oldest first, and counts all of them.
+/// `query` selects the notifications after the flume seq.
+fn load_kind<'a, QS>(
+    connection: &SqliteConnection,
+    notifications: &mut Notifications,
+    kind: NotificationKind,
+    query: impl Fn() -> BoxedSelectStatement<'a, NotificationRow, QS, Sqlite>,
+    limit: i64,
+) -> Result<(), Error>
+where
+    QS: QuerySource + 'a,
+    QS::FromClause: QueryFragment<Sqlite>,
+    messages::flume_seq: AppearsOnTable<QS>,
+{
+    // Joins can repeat a message, eg. a post that mentions the author twice.
+    let page = query()
+        .distinct()
+        .order(messages_flume_seq.asc())
+        .limit(limit)
+        .load::<(Option<i64>, i32, i32, Option<i32>)>(connection)?
+        .into_iter()
+        .map(|(seq, key_id, author_id, target_key_id)| Notification {
+            kind,
+            flume_seq: seq.unwrap_or(0),
+            key_id,
+            author_id,
+            target_key_id,
+        });
+    notifications.notifications.extend(page);
+
+    let count = query()
+        .select(sql::<BigInt>("COUNT(DISTINCT messages.key_id)"))
+        .first::<i64>(connection)?;
+    notifications.counts.insert(kind, count);
+
+    Ok(())
+}
+
+/// Finds the first `limit` things that happened to an author after a flume seq, oldest first,
+/// and counts everything that happened.
+///
+/// Likes, follows and blocks are only included while they still stand, and only for the latest
+/// message that set them, so liking a post twice doesn't notify twice.
+pub fn get_notifications(
+    connection: &SqliteConnection,
+    author_id: i32,
+    after_seq: i64,
+    limit: i64,
+) -> Result<Notifications, Error> {
+    let no_target = || sql::<Nullable<Integer>>("NULL");
+
+    let mentions = || {
+        mentions_table
+            .inner_join(messages_table.on(messages_key_id.eq(mentions_link_from_key_id)))
+            .select((
+                messages_flume_seq,
+                messages_key_id,
+                messages_author_id,
+                no_target(),
+            ))
+            .filter(mentions_link_to_author_id.eq(author_id))
+            .filter(mentions_rel.eq("mentions"))
+            .filter(messages_content_type.eq("post"))
+            .filter(messages_author_id.ne(author_id))
+            .filter(messages_flume_seq.gt(after_seq))
+            .into_boxed()
+    };
+
+    let replies = || {
+        let own_roots = root_posts_table
+            .select(root_posts_key_id.nullable())
+            .filter(root_posts_author_id.eq(author_id));
+        let replied_roots = reply_posts_table
+            .select(reply_posts_root_post_id.nullable())
+            .filter(reply_posts_author_id.eq(author_id));
+
+        let mention_key_ids = mentions_table
+            .select(mentions_link_from_key_id)
+            .filter(mentions_link_to_author_id.eq(author_id))
+            .filter(mentions_rel.eq("mentions"));
+
+        messages_table
+            .select((
+                messages_flume_seq,
+                messages_key_id,
+                messages_author_id,
+                messages_root_key_id,
+            ))
+            .filter(messages_content_type.eq("post"))
+            .filter(messages_author_id.ne(author_id))
+            .filter(messages_flume_seq.gt(after_seq))
+            .filter(
+                messages_root_key_id
+                    .eq_any(own_roots)
+                    .or(messages_root_key_id.eq_any(replied_roots)),
+            )
+            .filter(messages_key_id.ne_all(mention_key_ids))
+            .into_boxed()
+    };
+
+    // The votes table only keeps each author's latest vote on a post.
+    let likes = || {
+        let own_root_posts = root_posts_table
+            .select(root_posts_key_id)
+            .filter(root_posts_author_id.eq(author_id));
+        let own_reply_posts = reply_posts_table
+            .select(reply_posts_key_id)
+            .filter(reply_posts_author_id.eq(author_id));
+
+        votes_table
+            .inner_join(messages_table.on(messages_key_id.eq(votes_link_from_key_id)))
+            .select((
+                messages_flume_seq,
+                messages_key_id,
+                messages_author_id,
+                votes_link_to_key_id.nullable(),
+            ))
+            .filter(messages_author_id.ne(author_id))
+            .filter(votes_value.eq(1))
+            .filter(
+                votes_link_to_key_id
+                    .eq_any(own_root_posts)
+                    .or(votes_link_to_key_id.eq_any(own_reply_posts)),
+            )
+            .filter(messages_flume_seq.gt(after_seq))
+            .into_boxed()
+    };
+
+    // The contacts table only keeps each author's latest public contact message about another.
+    let contacts = |state: i32| {
+        contacts_table
+            .inner_join(messages_table.on(messages_key_id.eq(contacts_key_id)))
+            .select((
+                messages_flume_seq,
+                messages_key_id,
+                messages_author_id,
+                no_target(),
+            ))
+            .filter(contacts_contact_author_id.eq(author_id))
+            .filter(contacts_is_decrypted.eq(false))
+            .filter(contacts_state.eq(state))
+            .filter(messages_flume_seq.gt(after_seq))
+            .into_boxed()
+    };
+
+    let mut notifications = Notifications::default();
+    load_kind(
+        connection,
+        &mut notifications,
+        NotificationKind::Mention,
+        mentions,
+        limit,
+    )?;
+    load_kind(
+        connection,
+        &mut notifications,
+        NotificationKind::Reply,
+        replies,
+        limit,
+    )?;
+    load_kind(
+        connection,
+        &mut notifications,
+        NotificationKind::Like,
+        likes,
+        limit,
+    )?;
+    load_kind(
+        connection,
+        &mut notifications,
+        NotificationKind::Follow,
+        || contacts(1),
+        limit,
+    )?;
+    load_kind(
+        connection,
+        &mut notifications,
+        NotificationKind::Block,
+        || contacts(-1),
+        limit,
+    )?;
+
+    // Each kind has its own first page, the first page of them all is somewhere in those.
+    notifications
+        .notifications
+        .sort_by_key(|notification| notification.flume_seq);
+    notifications.notifications.truncate(limit as usize);
+
+    Ok(notifications)
+}
@@ -0,0 +1,489 @@
+use super::keys::find_or_create_key;
+use crate::db::schema::messages::dsl::{
+    content as messages_content, key_id as messages_key_id, messages as messages_table,
+    received_time as messages_received_time,
+};
+use crate::db::schema::polls::dsl::{
+    asserted_timestamp as polls_asserted_timestamp, author_id as polls_author_id,
+    closes_at as polls_closes_at, flume_seq as polls_flume_seq, key_id as polls_key_id,
+    polls as polls_table,
+};
+use crate::db::schema::positions::dsl::{
+    asserted_timestamp as positions_asserted_timestamp, author_id as positions_author_id,
+    flume_seq as positions_flume_seq, key_id as positions_key_id,
+    poll_key_id as positions_poll_key_id, positions as positions_table,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::dsl::sql;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text};
+use serde_json::Value;
+use std::convert::TryFrom;
+
+/// The stances of a proposal poll.
+pub const PROPOSAL_OPTIONS: [&str; 4] = ["Agree", "Disagree", "Abstain", "Block"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PollType {
+    /// Voters spread up to `numDots` dots over the choices.
+    Dot,
+    /// Voters take one stance on a proposal.
+    Proposal,
+    /// Voters score each choice from 0 to `maxScore`.
+    Score,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Poll {
+    pub key_id: i32,
+    pub author_id: i32,
+    pub asserted_timestamp: i64,
+    /// When the poll closes, in milliseconds.
+    pub closes_at: Option<i64>,
+    pub content: Value,
+}
+
+/// A voter's position. The choices are `(option index, value)`s, where the value is the number of
+/// dots, the score, or 1 for a stance.
+#[derive(Debug, PartialEq)]
+pub struct Position {
+    pub key_id: i32,
+    pub author_id: i32,
+    pub asserted_timestamp: i64,
+    pub choices: Vec<(usize, i32)>,
+    pub reason: Option<String>,
+}
+
+/// The total for an option across every position, and the voters that chose it.
+#[derive(Debug, PartialEq)]
+pub struct PollResult {
+    pub option: String,
+    pub total: i32,
+    pub voter_ids: Vec<i32>,
+}
+
+impl Poll {
+    pub fn poll_type(&self) -> Option<PollType> {
+        match self.content["details"]["type"].as_str() {
+            Some("dot") => Some(PollType::Dot),
+            Some("proposal") => Some(PollType::Proposal),
+            Some("score") => Some(PollType::Score),
+            _ => None,
+        }
+    }
+
+    /// What voters choose between: the choices of dot and score polls, or the stances of a
+    /// proposal.
+    pub fn options(&self) -> Vec<String> {
+        match self.poll_type() {
+            Some(PollType::Proposal) => PROPOSAL_OPTIONS.iter().map(|o| o.to_string()).collect(),
+            Some(_) => match &self.content["details"]["choices"] {
+                Value::Array(choices) => choices
+                    .iter()
+                    .map(|choice| match choice {
+                        Value::String(choice) => choice.to_owned(),
+                        choice => choice.to_string(),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+            None => Vec::new(),
+        }
+    }
+
+    pub fn is_closed(&self, now: i64) -> bool {
+        self.closes_at.is_some_and(|closes_at| closes_at <= now)
+    }
+
+    /// Checks a position's `details` against the poll, into `(option index, value)`s. Returns
+    /// `None` when the position isn't valid for this poll, eg. it uses more dots than allowed.
+    pub fn parse_position(&self, details: &Value) -> Option<Vec<(usize, i32)>> {
+        let options = self.options().len();
+        let poll_details = &self.content["details"];
+
+        let choices = match self.poll_type()? {
+            PollType::Dot => {
+                let choices = indexed_values(&details["choices"])?;
+                let num_dots = poll_details["numDots"].as_i64().unwrap_or(1);
+                let dots: i64 = choices.iter().map(|(_, dots)| *dots as i64).sum();
+
+                if dots > num_dots || choices.iter().any(|(_, dots)| *dots < 0) {
+                    return None;
+                }
+                choices
+            }
+            PollType::Score => {
+                let choices = indexed_values(&details["scores"])?;
+                let max_score = poll_details["maxScore"].as_i64().unwrap_or(i64::MAX);
+
+                if choices
+                    .iter()
+                    .any(|(_, score)| *score < 0 || *score as i64 > max_score)
+                {
+                    return None;
+                }
+                choices
+            }
+            PollType::Proposal => {
+                let stance = details["stance"]
+                    .as_str()
+                    .or_else(|| details["choice"].as_str())?;
+                let index = PROPOSAL_OPTIONS
+                    .iter()
+                    .position(|option| option.eq_ignore_ascii_case(stance))?;
+                vec![(index, 1)]
+            }
+        };
+
+        if choices.iter().any(|(index, _)| *index >= options) {
+            return None;
+        }
+
+        Some(choices)
+    }
+}
+
+/// Reads choices that are either a list of option indexes, each counting once, or an object of
+/// option indexes to values. Returns `None` when an index or value doesn't fit, including when the
+/// values of a repeated index (eg. `"0"` and `"00"`) add up to more than fits.
+fn indexed_values(value: &Value) -> Option<Vec<(usize, i32)>> {
+    let mut choices: Vec<(usize, i32)> = Vec::new();
+    let mut add = |index: usize, value: i32| {
+        match choices.iter_mut().find(|(i, _)| *i == index) {
+            Some(choice) => choice.1 = choice.1.checked_add(value)?,
+            None => choices.push((index, value)),
+        }
+        Some(())
+    };
+
+    match value {
+        Value::Array(indexes) => {
+            for index in indexes {
+                add(usize::try_from(index.as_u64()?).ok()?, 1)?;
+            }
+        }
+        Value::Object(values) => {
+            for (index, value) in values {
+                add(index.parse().ok()?, i32::try_from(value.as_i64()?).ok()?)?;
+            }
+        }
+        _ => return None,
+    }
+
+    choices.sort();
+    Some(choices)
+}
+
+/// Parses `closesAt`, an ISO 8601 date or a timestamp in milliseconds. Sqlite parses the dates so
+/// we don't need a date library.
+fn parse_closes_at(connection: &SqliteConnection, closes_at: &Value) -> Result<Option<i64>, Error> {
+    match closes_at {
+        Value::Number(closes_at) => Ok(closes_at.as_f64().map(|closes_at| closes_at as i64)),
+        Value::String(closes_at) => diesel::select(
+            sql::<Nullable<BigInt>>("CAST(ROUND((julianday(")
+                .bind::<Text, _>(closes_at)
+                .sql(") - 2440587.5) * 86400000) AS INTEGER)"),
+        )
+        .get_result(connection),
+        _ => Ok(None),
+    }
+}
+
+// Caller must check that the message is actually a poll.
+pub fn insert_poll(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let closes_at = parse_closes_at(connection, &message.value.content["closesAt"])?;
+
+    insert_into(polls_table)
+        .values((
+            polls_flume_seq.eq(seq),
+            polls_asserted_timestamp.eq(message.value.timestamp as i64),
+            polls_key_id.eq(message_key_id),
+            polls_author_id.eq(author_id),
+            polls_closes_at.eq(closes_at),
+        ))
+        .execute(connection)
+        .map(|_| ())
+}
+
+// Caller must check that the message is actually a position.
+pub fn insert_position(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    if let Value::String(poll) = &message.value.content["root"] {
+        insert_into(positions_table)
+            .values((
+                positions_flume_seq.eq(seq),
+                positions_asserted_timestamp.eq(message.value.timestamp as i64),
+                positions_key_id.eq(message_key_id),
+                positions_poll_key_id.eq(find_or_create_key(connection, poll)?),
+                positions_author_id.eq(author_id),
+            ))
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+/// The polls with the given key ids.
+pub fn get_polls(connection: &SqliteConnection, key_ids: &[i32]) -> Result<Vec<Poll>, Error> {
+    let polls = polls_table
+        .inner_join(messages_table.on(messages_key_id.eq(polls_key_id)))
+        .select((
+            polls_key_id,
+            polls_author_id,
+            polls_asserted_timestamp,
+            polls_closes_at,
+            messages_content,
+        ))
+        .filter(polls_key_id.eq_any(key_ids))
+        .load::<(i32, i32, i64, Option<i64>, Option<String>)>(connection)?
+        .into_iter()
+        .map(
+            |(key_id, author_id, asserted_timestamp, closes_at, content)| Poll {
+                key_id,
+                author_id,
+                asserted_timestamp,
+                closes_at,
+                content: content
+                    .and_then(|content| serde_json::from_str(&content).ok())
+                    .unwrap_or(Value::Null),
+            },
+        )
+        .collect();
+
+    Ok(polls)
+}
+
+pub fn get_poll(connection: &SqliteConnection, key_id: i32) -> Result<Option<Poll>, Error> {
+    Ok(get_polls(connection, &[key_id])?.pop())
+}
+
+/// Each voter's latest valid position, in the order they were taken.
+///
+/// Positions that don't fit the poll, or that were asserted or received after the poll closed, are
+/// rejected, so a voter's earlier position still counts. Asserted times can't be trusted, so a
+/// position backdated to before the poll closed still has to arrive in time.
+pub fn get_positions(connection: &SqliteConnection, poll: &Poll) -> Result<Vec<Position>, Error> {
+    let mut positions: Vec<Position> = Vec::new();
+
+    positions_table
+        .inner_join(messages_table.on(messages_key_id.eq(positions_key_id)))
+        .select((
+            positions_key_id,
+            positions_author_id,
+            positions_asserted_timestamp,
+            messages_received_time,
+            messages_content,
+        ))
+        .filter(positions_poll_key_id.eq(poll.key_id))
+        .order(positions_flume_seq.asc())
+        .load::<(i32, i32, i64, i64, Option<String>)>(connection)?
+        .into_iter()
+        .filter(|(_, _, asserted_timestamp, received_time, _)| {
+            !poll.is_closed((*asserted_timestamp).max(*received_time))
+        })
+        .filter_map(|(key_id, author_id, asserted_timestamp, _, content)| {
+            let content: Value = serde_json::from_str(&content?).ok()?;
+            let choices = poll.parse_position(&content["details"])?;
+            let reason = content["reason"].as_str().map(|reason| reason.to_owned());
+
+            Some(Position {
+                key_id,
+                author_id,
+                asserted_timestamp,
+                choices,
+                reason,
+            })
+        })
+        .for_each(|position| {
+            positions.retain(|previous| previous.author_id != position.author_id);
+            positions.push(position);
+        });
+
+    Ok(positions)
+}
+
+/// Totals the positions for each of the poll's options, in the order of the options. A position
+/// that would take a total past what fits is rejected.
+pub fn tally(poll: &Poll, positions: &[Position]) -> Vec<PollResult> {
+    let mut results = poll
+        .options()
+        .into_iter()
+        .map(|option| PollResult {
+            option,
+            total: 0,
+            voter_ids: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    for position in positions {
+        let totals = position
+            .choices
+            .iter()
+            .map(|(index, value)| results[*index].total.checked_add(*value))
+            .collect::<Option<Vec<_>>>();
+        let totals = match totals {
+            Some(totals) => totals,
+            None => continue,
+        };
+
+        for ((index, value), total) in position.choices.iter().zip(totals) {
+            let result = &mut results[*index];
+            result.total = total;
+            if *value > 0 {
+                result.voter_ids.push(position.author_id);
+            }
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::polls::{Poll, PollResult, Position};
+    use serde_json::json;
+
+    fn poll(details: serde_json::Value) -> Poll {
+        Poll {
+            key_id: 1,
+            author_id: 1,
+            asserted_timestamp: 0,
+            closes_at: Some(100),
+            content: json!({"type": "poll", "title": "Lunch?", "details": details}),
+        }
+    }
+
+    #[test]
+    fn parse_dot_positions() {
+        let poll =
+            poll(json!({"type": "dot", "choices": ["Pizza", "Soup", "Salad"], "numDots": 2}));
+
+        assert_eq!(
+            poll.parse_position(&json!({"type": "dot", "choices": [0, 2]})),
+            Some(vec![(0, 1), (2, 1)])
+        );
+        assert_eq!(
+            poll.parse_position(&json!({"type": "dot", "choices": {"1": 2}})),
+            Some(vec![(1, 2)])
+        );
+        // Too many dots.
+        assert_eq!(
+            poll.parse_position(&json!({"type": "dot", "choices": [0, 1, 2]})),
+            None
+        );
+        // Not a choice.
+        assert_eq!(
+            poll.parse_position(&json!({"type": "dot", "choices": [3]})),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_proposal_and_score_positions() {
+        let proposal = poll(json!({"type": "proposal", "proposal": "Lunch at noon"}));
+        assert_eq!(
+            proposal.parse_position(&json!({"type": "proposal", "stance": "block"})),
+            Some(vec![(3, 1)])
+        );
+        assert_eq!(
+            proposal.parse_position(&json!({"type": "proposal", "stance": "Maybe"})),
+            None
+        );
+
+        let score = poll(json!({"type": "score", "choices": ["Pizza", "Soup"], "maxScore": 5}));
+        assert_eq!(
+            score.parse_position(&json!({"type": "score", "scores": {"0": 5, "1": 2}})),
+            Some(vec![(0, 5), (1, 2)])
+        );
+        assert_eq!(
+            score.parse_position(&json!({"type": "score", "scores": {"0": 6}})),
+            None
+        );
+        // Would wrap around to a score of 1 as an i32.
+        assert_eq!(
+            score.parse_position(&json!({"type": "score", "scores": {"0": 4294967297i64}})),
+            None
+        );
+
+        // The same choice twice adds up to more than fits in an i32.
+        let unlimited = poll(json!({"type": "score", "choices": ["Pizza", "Soup"]}));
+        assert_eq!(
+            unlimited
+                .parse_position(&json!({"type": "score", "scores": {"0": 2147483647, "00": 1}})),
+            None
+        );
+        assert_eq!(
+            unlimited.parse_position(&json!({"type": "score", "scores": {"0": 2, "00": 3}})),
+            Some(vec![(0, 5)])
+        );
+    }
+
+    #[test]
+    fn tally_positions() {
+        let poll = poll(json!({"type": "dot", "choices": ["Pizza", "Soup"], "numDots": 2}));
+        let position = |author_id, choices| Position {
+            key_id: author_id,
+            author_id,
+            asserted_timestamp: 0,
+            choices,
+            reason: None,
+        };
+
+        assert_eq!(
+            super::tally(
+                &poll,
+                &[position(2, vec![(0, 2)]), position(3, vec![(0, 1), (1, 1)])]
+            ),
+            vec![
+                PollResult {
+                    option: "Pizza".to_owned(),
+                    total: 3,
+                    voter_ids: vec![2, 3],
+                },
+                PollResult {
+                    option: "Soup".to_owned(),
+                    total: 1,
+                    voter_ids: vec![3],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_positions_that_overflow_the_tally() {
+        let poll = poll(json!({"type": "score", "choices": ["Pizza", "Soup"]}));
+        let position = |author_id, choices| Position {
+            key_id: author_id,
+            author_id,
+            asserted_timestamp: 0,
+            choices,
+            reason: None,
+        };
+
+        let results = super::tally(
+            &poll,
+            &[
+                position(2, vec![(0, i32::MAX)]),
+                position(3, vec![(0, 1), (1, 1)]),
+                position(4, vec![(1, 2)]),
+            ],
+        );
+        assert_eq!(results[0].total, i32::MAX);
+        assert_eq!(results[0].voter_ids, vec![2]);
+        assert_eq!(results[1].total, 2);
+        assert_eq!(results[1].voter_ids, vec![4]);
+    }
+}
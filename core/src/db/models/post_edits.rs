@@ -0,0 +1,142 @@
+use super::keys::find_or_create_key;
+use crate::db::schema::messages::dsl::{
+    author_id as messages_author_id, key_id as messages_key_id, messages as messages_table,
+};
+use crate::db::schema::post_edits;
+use crate::db::schema::post_edits::dsl::{
+    asserted_timestamp as post_edits_asserted_timestamp, author_id as post_edits_author_id,
+    flume_seq as post_edits_flume_seq, key_id as post_edits_key_id, post_edits as post_edits_table,
+    revision_root_key_id as post_edits_revision_root_key_id,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::insert_into;
+use diesel::prelude::*;
+use serde_json::Value;
+
+#[derive(Queryable, Insertable, Identifiable, Debug, Default)]
+#[table_name = "post_edits"]
+#[primary_key(flume_seq)]
+pub struct PostEdit {
+    pub flume_seq: i64,
+    pub asserted_timestamp: i64,
+    pub key_id: i32,
+    /// The post being edited.
+    pub revision_root_key_id: i32,
+    pub author_id: i32,
+}
+
+// Caller must check that the message is actually a post-edit.
+pub fn insert_post_edit(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    if let Value::String(revision_root) = &message.value.content["revisionRoot"] {
+        let edit = PostEdit {
+            flume_seq: seq,
+            asserted_timestamp: message.value.timestamp as i64,
+            key_id: message_key_id,
+            revision_root_key_id: find_or_create_key(connection, revision_root)?,
+            author_id,
+        };
+
+        insert_into(post_edits_table)
+            .values(edit)
+            .execute(connection)?;
+    }
+
+    Ok(())
+}
+
+/// The key ids and asserted timestamps of the edits to a post, oldest first.
+///
+/// Only the post's author can edit it, edits by anyone else are ignored. Edits are checked against
+/// the author of the post when it's queried because the edits can arrive before the post.
+pub fn get_revisions(connection: &SqliteConnection, key_id: i32) -> Result<Vec<(i32, i64)>, Error> {
+    post_edits_table
+        .inner_join(
+            messages_table.on(messages_key_id
+                .eq(post_edits_revision_root_key_id)
+                .and(messages_author_id.eq(post_edits_author_id))),
+        )
+        .select((post_edits_key_id, post_edits_asserted_timestamp))
+        .filter(post_edits_revision_root_key_id.eq(key_id))
+        .order(post_edits_flume_seq.asc())
+        .load(connection)
+}
+
+/// The key id of the newest edit of a post, if it has been edited.
+pub fn get_latest_revision(
+    connection: &SqliteConnection,
+    key_id: i32,
+) -> Result<Option<i32>, Error> {
+    post_edits_table
+        .inner_join(
+            messages_table.on(messages_key_id
+                .eq(post_edits_revision_root_key_id)
+                .and(messages_author_id.eq(post_edits_author_id))),
+        )
+        .select(post_edits_key_id)
+        .filter(post_edits_revision_root_key_id.eq(key_id))
+        .order(post_edits_flume_seq.desc())
+        .first(connection)
+        .optional()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::keys::find_or_create_key;
+    use crate::db::models::messages::insert_message;
+    use crate::db::models::post_edits::{get_latest_revision, get_revisions, insert_post_edit};
+    use crate::db::models::posts::get_text;
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn only_the_author_can_edit() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let message = |key: &str, timestamp: f64, content: Value| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.timestamp = timestamp;
+                msg.value.content = content;
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                (msg, key_id)
+            };
+            let edit = |text: &str| {
+                json!({"type": "post-edit", "text": text, "root": "%post", "revisionRoot": "%post"})
+            };
+
+            // The first edit arrives before the post.
+            let (typo_fix, typo_fix_id) = message("%typo_fix", 20.0, edit("Hello world"));
+            insert_message(&connection, &typo_fix, 1, typo_fix_id, false, 1)?;
+            insert_post_edit(&connection, &typo_fix, typo_fix_id, 1, 1)?;
+
+            let (post, post_id) =
+                message("%post", 10.0, json!({"type": "post", "text": "Helo world"}));
+            insert_message(&connection, &post, 2, post_id, false, 1)?;
+
+            let (vandalism, vandalism_id) = message("%vandalism", 30.0, edit("Goodbye world"));
+            insert_message(&connection, &vandalism, 3, vandalism_id, false, 2)?;
+            insert_post_edit(&connection, &vandalism, vandalism_id, 2, 3)?;
+
+            assert_eq!(
+                get_revisions(&connection, post_id)?,
+                vec![(typo_fix_id, 20)]
+            );
+            let latest = get_latest_revision(&connection, post_id)?;
+            assert_eq!(latest, Some(typo_fix_id));
+            assert_eq!(get_text(&connection, latest.unwrap())?, "Hello world");
+            Ok(())
+        });
+    }
+}
@@ -5,11 +5,18 @@ use serde_json::Value;
 
 use crate::db::schema::reply_posts;
 use crate::db::schema::reply_posts::dsl::reply_posts as reply_posts_table;
+use crate::db::schema::reply_posts::dsl::{
+    flume_seq as reply_posts_flume_seq, root_post_id as reply_posts_root_post_id,
+};
 use crate::db::schema::root_posts;
-use crate::db::schema::root_posts::dsl::root_posts as root_posts_table;
+use crate::db::schema::root_posts::dsl::{
+    key_id as root_posts_key_id, last_activity_flume_seq as root_posts_last_activity_flume_seq,
+    root_posts as root_posts_table,
+};
 use diesel::dsl::sql;
-use diesel::insert_into;
+use diesel::expression::dsl::max;
 use diesel::prelude::*;
+use diesel::{insert_into, update};
 
 use crate::db::schema::messages::dsl::{
     content as messages_content, key_id as messages_key_id, messages as messages_table,
@@ -23,6 +30,8 @@ pub struct RootPost {
     pub asserted_timestamp: i64,
     pub key_id: i32,
     pub author_id: i32,
+    /// The flume seq of the newest reply, or of the root itself if there are no replies yet.
+    pub last_activity_flume_seq: i64,
 }
 
 #[derive(Queryable, Insertable, Associations, Identifiable, Debug, Default)]
@@ -51,7 +60,7 @@ pub fn insert_post(
     match message.value.content["root"] {
         //A reply
         Value::String(ref key) => {
-            let id = find_or_create_key(&connection, &key).unwrap();
+            let id = find_or_create_key(connection, key).unwrap();
             let reply = ReplyPost {
                 flume_seq: seq,
                 asserted_timestamp: message.value.timestamp as i64,
@@ -62,16 +71,30 @@ pub fn insert_post(
 
             insert_into(reply_posts_table)
                 .values(reply)
+                .execute(connection)?;
+
+            // Bump the thread. The root might not be in the db yet, then this does nothing and the
+            // root picks up its replies when it's inserted.
+            update(root_posts_table)
+                .filter(root_posts_key_id.eq(id))
+                .filter(root_posts_last_activity_flume_seq.lt(seq))
+                .set(root_posts_last_activity_flume_seq.eq(seq))
                 .execute(connection)
                 .map(|_| ())
         }
         // A root
         _ => {
+            let last_reply_seq = reply_posts_table
+                .select(max(reply_posts_flume_seq))
+                .filter(reply_posts_root_post_id.eq(message_key_id))
+                .first::<Option<i64>>(connection)?;
+
             let root = RootPost {
                 flume_seq: seq,
                 asserted_timestamp: message.value.timestamp as i64,
                 key_id: message_key_id,
                 author_id,
+                last_activity_flume_seq: last_reply_seq.map_or(seq, |reply_seq| reply_seq.max(seq)),
             };
 
             insert_into(root_posts_table)
@@ -97,8 +120,12 @@ pub fn get_text(connection: &SqliteConnection, key_id: i32) -> Result<String, Er
 
 #[cfg(test)]
 mod tests {
+    use crate::db::models::keys::find_or_create_key;
     use crate::db::models::messages::insert_message;
-    use crate::db::models::posts::{get_text, insert_post, PostText};
+    use crate::db::models::posts::{get_text, insert_post};
+    use crate::db::schema::root_posts::dsl::{
+        key_id as root_posts_key_id, last_activity_flume_seq, root_posts as root_posts_table,
+    };
     use crate::ssb_message::{SsbMessage, SsbValue};
     use crate::utils::establish_connection;
     use diesel::prelude::*;
@@ -120,10 +147,47 @@ mod tests {
             msg.value = val;
             msg.key = "test_key_123".to_string();
 
-            let id = insert_message(&connection, &msg, 1, 1, false, 1).unwrap();
+            insert_message(&connection, &msg, 1, 1, false, 1).unwrap();
             let actual = get_text(&connection, 1).unwrap();
             assert_eq!(actual, expected_text);
             Ok(())
         });
     }
+
+    #[test]
+    fn last_activity_follows_replies() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let root_key_id = find_or_create_key(&connection, "%root").unwrap();
+            let post = |content: Value| {
+                let mut msg = SsbMessage::default();
+                msg.value.content = content;
+                msg
+            };
+            let last_activity = || {
+                root_posts_table
+                    .select(last_activity_flume_seq)
+                    .filter(root_posts_key_id.eq(root_key_id))
+                    .first::<i64>(&connection)
+                    .unwrap()
+            };
+            let reply = post(json!({"type": "post", "root": "%root", "text": "reply"}));
+
+            // The first reply arrives before its root.
+            insert_post(&connection, &reply, 100, 1, 5).unwrap();
+            insert_post(
+                &connection,
+                &post(json!({"type": "post"})),
+                root_key_id,
+                1,
+                2,
+            )
+            .unwrap();
+            assert_eq!(last_activity(), 5);
+
+            insert_post(&connection, &reply, 101, 1, 7).unwrap();
+            assert_eq!(last_activity(), 7);
+            Ok(())
+        });
+    }
 }
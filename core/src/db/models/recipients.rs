@@ -0,0 +1,338 @@
+use crate::cursor::{before_cursor, cursor_order, OrderExpression};
+use crate::db::schema::recipients::dsl::{
+    id as recipients_id, link_from_key_id as recipients_link_from_key_id,
+    link_to_author_id as recipients_link_to_author_id, recipients as recipients_table,
+};
+use crate::db::schema::root_posts;
+use crate::db::schema::root_posts::dsl::{
+    flume_seq as root_posts_flume_seq, key_id as root_posts_key_id,
+    last_activity_flume_seq as root_posts_last_activity_flume_seq, root_posts as root_posts_table,
+};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::dsl::{not, sql};
+use diesel::insert_or_ignore_into;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Integer};
+use diesel::sqlite::Sqlite;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::authors::find_or_create_author;
+
+/// The private threads that share a set of recipients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrivateConversation {
+    /// The author ids of the recipients, in ascending order.
+    pub recipient_ids: Vec<i32>,
+    /// The key id, flume seq and last activity flume seq of each thread's root, most recently
+    /// active first.
+    pub threads: Vec<(i32, i64, i64)>,
+}
+
+impl PrivateConversation {
+    /// The last activity flume seq and flume seq of the most recently active thread, which
+    /// conversations are ordered by.
+    pub fn position(&self) -> (i64, i64) {
+        self.threads
+            .first()
+            .map_or((0, 0), |(_, seq, last_activity_seq)| {
+                (*last_activity_seq, *seq)
+            })
+    }
+}
+
+/// Finds the feeds in a message's `recps`. Recipients may be feed ids or `{link, name}` mentions.
+pub fn find_recipients(content: &Value) -> Vec<&str> {
+    let mut recipients = Vec::new();
+
+    if let Value::Array(recps) = &content["recps"] {
+        recps
+            .iter()
+            .filter_map(|recp| recp.as_str().or_else(|| recp["link"].as_str()))
+            .filter(|recp| recp.starts_with('@'))
+            .for_each(|recp| {
+                if !recipients.contains(&recp) {
+                    recipients.push(recp)
+                }
+            });
+    }
+
+    recipients
+}
+
+// Caller must check that the message was decrypted, `recps` on a public message mean nothing.
+pub fn insert_recipients(connection: &SqliteConnection, message: &SsbMessage, message_key_id: i32) {
+    find_recipients(&message.value.content)
+        .iter()
+        .map(|recp| find_or_create_author(connection, recp).unwrap())
+        .for_each(|author_id| {
+            insert_or_ignore_into(recipients_table)
+                .values((
+                    recipients_link_from_key_id.eq(message_key_id),
+                    recipients_link_to_author_id.eq(author_id),
+                ))
+                .execute(connection)
+                .unwrap();
+        });
+}
+
+/// The author ids of a message's recipients, in the order they were listed.
+pub fn get_recipients(connection: &SqliteConnection, key_id: i32) -> Result<Vec<i32>, Error> {
+    recipients_table
+        .select(recipients_link_to_author_id)
+        .filter(recipients_link_from_key_id.eq(key_id))
+        .order(recipients_id.asc())
+        .load(connection)
+}
+
+/// The private threads with the position of their conversation: the last activity flume seq and
+/// flume seq of the most recently active thread with the same recipients. Recipients are compared
+/// as lists of ascending author ids.
+const PRIVATE_THREADS: &str = "\
+    SELECT root_posts.key_id, \
+        FIRST_VALUE(root_posts.key_id) OVER conversation AS conversation_key_id, \
+        FIRST_VALUE(root_posts.last_activity_flume_seq) OVER conversation \
+            AS conversation_last_activity_flume_seq, \
+        FIRST_VALUE(root_posts.flume_seq) OVER conversation AS conversation_flume_seq \
+    FROM root_posts \
+    JOIN messages ON messages.key_id = root_posts.key_id \
+    LEFT JOIN (\
+        SELECT DISTINCT link_from_key_id, GROUP_CONCAT(link_to_author_id) OVER (\
+            PARTITION BY link_from_key_id ORDER BY link_to_author_id \
+            ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING\
+        ) AS recipient_ids \
+        FROM recipients\
+    ) AS thread_recipients ON thread_recipients.link_from_key_id = root_posts.key_id \
+    WHERE messages.is_decrypted = 1 \
+    WINDOW conversation AS (\
+        PARTITION BY thread_recipients.recipient_ids \
+        ORDER BY root_posts.last_activity_flume_seq DESC, root_posts.flume_seq DESC\
+    )";
+
+/// The root of the most recently active thread in each private conversation, which stands in for
+/// the conversation when paging.
+fn latest_threads<'a>() -> root_posts::BoxedQuery<'a, Sqlite, (Integer, BigInt, BigInt)> {
+    root_posts_table
+        .select((
+            root_posts_key_id,
+            root_posts_flume_seq,
+            root_posts_last_activity_flume_seq,
+        ))
+        .filter(sql::<Bool>(&format!(
+            "root_posts.key_id IN (SELECT conversation_key_id FROM ({}))",
+            PRIVATE_THREADS
+        )))
+        .into_boxed()
+}
+
+fn last_activity<'a>() -> OrderExpression<'a, root_posts::table> {
+    Box::new(root_posts_last_activity_flume_seq.nullable())
+}
+
+fn seq<'a>() -> OrderExpression<'a, root_posts::table> {
+    Box::new(root_posts_flume_seq.nullable())
+}
+
+/// Groups the private threads by the recipients of their roots, most recently active first.
+/// Gets up to `limit` conversations before the `(last activity flume seq, flume seq)` position.
+pub fn get_private_conversations(
+    connection: &SqliteConnection,
+    before: Option<(i64, Option<i64>)>,
+    limit: i64,
+) -> Result<Vec<PrivateConversation>, Error> {
+    let query = latest_threads().order(cursor_order(last_activity, seq, true));
+    let latest = match before {
+        Some(position) => query.filter(before_cursor(last_activity, seq, position)),
+        None => query,
+    }
+    .limit(limit)
+    .load::<(i32, i64, i64)>(connection)?;
+
+    let (newest, oldest) = match (latest.first(), latest.last()) {
+        (Some(newest), Some(oldest)) => (newest, oldest),
+        _ => return Ok(Vec::new()),
+    };
+
+    // The threads of the conversations in the page are the ones whose conversation sorts between
+    // the newest and oldest conversation, which stays within the variable limit for any `limit`.
+    let in_page = || {
+        sql::<Bool>(&format!(
+            "root_posts.key_id IN (SELECT key_id FROM ({}) \
+             WHERE (conversation_last_activity_flume_seq, conversation_flume_seq) \
+             BETWEEN (",
+            PRIVATE_THREADS
+        ))
+        .bind::<BigInt, _>(oldest.2)
+        .sql(", ")
+        .bind::<BigInt, _>(oldest.1)
+        .sql(") AND (")
+        .bind::<BigInt, _>(newest.2)
+        .sql(", ")
+        .bind::<BigInt, _>(newest.1)
+        .sql("))")
+    };
+
+    let threads = root_posts_table
+        .select((
+            root_posts_key_id,
+            root_posts_flume_seq,
+            root_posts_last_activity_flume_seq,
+        ))
+        .filter(in_page())
+        .order((
+            root_posts_last_activity_flume_seq.desc(),
+            root_posts_flume_seq.desc(),
+        ))
+        .load::<(i32, i64, i64)>(connection)?;
+
+    let mut recipients_by_key_id = HashMap::new();
+    recipients_table
+        .inner_join(root_posts_table.on(root_posts_key_id.eq(recipients_link_from_key_id)))
+        .select((recipients_link_from_key_id, recipients_link_to_author_id))
+        .filter(in_page())
+        .load::<(i32, i32)>(connection)?
+        .into_iter()
+        .for_each(|(key_id, author_id)| {
+            recipients_by_key_id
+                .entry(key_id)
+                .or_insert_with(Vec::new)
+                .push(author_id)
+        });
+
+    // The threads are most recently active first, so the conversations are too.
+    let mut conversations: Vec<PrivateConversation> = Vec::new();
+    let mut index_by_recipients: HashMap<Vec<i32>, usize> = HashMap::new();
+    threads.into_iter().for_each(|thread| {
+        let mut recipient_ids = recipients_by_key_id.remove(&thread.0).unwrap_or_default();
+        recipient_ids.sort();
+
+        match index_by_recipients.get(&recipient_ids) {
+            Some(index) => conversations[*index].threads.push(thread),
+            None => {
+                index_by_recipients.insert(recipient_ids.clone(), conversations.len());
+                conversations.push(PrivateConversation {
+                    recipient_ids,
+                    threads: vec![thread],
+                })
+            }
+        }
+    });
+
+    Ok(conversations)
+}
+
+/// Whether there are private conversations that aren't before the position, ie. that are at it or
+/// more recently active.
+pub fn has_private_conversations_from(
+    connection: &SqliteConnection,
+    position: (i64, Option<i64>),
+) -> Result<bool, Error> {
+    let other = latest_threads()
+        .filter(not(before_cursor(last_activity, seq, position)))
+        .limit(1)
+        .load::<(i32, i64, i64)>(connection)?;
+
+    Ok(!other.is_empty())
+}
+
+pub fn count_private_conversations(connection: &SqliteConnection) -> Result<i64, Error> {
+    latest_threads().count().get_result(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::keys::find_or_create_key;
+    use crate::db::models::messages::insert_message;
+    use crate::db::models::posts::insert_post;
+    use crate::db::models::recipients::{
+        count_private_conversations, find_recipients, get_private_conversations, get_recipients,
+        has_private_conversations_from, insert_recipients, PrivateConversation,
+    };
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::{json, Value};
+
+    const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+    const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+    const MIX: &str = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+
+    #[test]
+    fn find_recipients_in_recps() {
+        let content = json!({
+            "type": "post",
+            "text": "hi",
+            "recps": [PIET, {"link": KATIE, "name": "katie"}, PIET, "%notafeed.sha256"]
+        });
+
+        assert_eq!(find_recipients(&content), vec![PIET, KATIE]);
+        assert_eq!(
+            find_recipients(&json!({"type": "post"})),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn group_private_threads_by_recipients() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let insert = |key: &str, seq: i64, content: Value| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.content = content;
+
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                insert_post(&connection, &msg, key_id, 1, seq).unwrap();
+                insert_message(&connection, &msg, seq, key_id, true, 1).unwrap();
+                insert_recipients(&connection, &msg, key_id);
+                key_id
+            };
+
+            let first = insert("%first", 1, json!({"type": "post", "recps": [PIET, KATIE]}));
+            let second = insert("%second", 2, json!({"type": "post", "recps": [PIET, MIX]}));
+            let third = insert("%third", 3, json!({"type": "post", "recps": [KATIE, PIET]}));
+            insert(
+                "%reply",
+                4,
+                json!({"type": "post", "root": "%second", "recps": [PIET, MIX]}),
+            );
+
+            let mut piet_and_katie = get_recipients(&connection, first).unwrap();
+            piet_and_katie.sort();
+            let mut piet_and_mix = get_recipients(&connection, second).unwrap();
+            piet_and_mix.sort();
+
+            let conversations = get_private_conversations(&connection, None, 10).unwrap();
+            assert_eq!(
+                conversations,
+                vec![
+                    PrivateConversation {
+                        recipient_ids: piet_and_mix,
+                        threads: vec![(second, 2, 4)],
+                    },
+                    PrivateConversation {
+                        recipient_ids: piet_and_katie,
+                        threads: vec![(third, 3, 3), (first, 1, 1)],
+                    },
+                ]
+            );
+            assert_eq!(conversations[0].position(), (4, 2));
+            assert_eq!(conversations[1].position(), (3, 3));
+            assert_eq!(count_private_conversations(&connection).unwrap(), 2);
+
+            let older = get_private_conversations(&connection, Some((4, Some(2))), 10).unwrap();
+            assert_eq!(older, conversations[1..].to_vec());
+            assert_eq!(
+                get_private_conversations(&connection, None, 1).unwrap(),
+                conversations[..1].to_vec()
+            );
+            assert!(has_private_conversations_from(&connection, (3, Some(3))).unwrap());
+            assert!(!has_private_conversations_from(&connection, (5, None)).unwrap());
+            Ok(())
+        });
+    }
+}
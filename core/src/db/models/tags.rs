@@ -0,0 +1,251 @@
+use super::keys::find_or_create_key;
+use crate::db::schema::messages::dsl::{
+    content_type as messages_content_type, key_id as messages_key_id, messages as messages_table,
+};
+use crate::db::schema::tag_operations::dsl::{
+    author_id as tag_operations_author_id, flume_seq as tag_operations_flume_seq,
+    message_key_id as tag_operations_message_key_id, tag_key_id as tag_operations_tag_key_id,
+    tag_operations as tag_operations_table, tagged as tag_operations_tagged,
+};
+use crate::db::schema::tags::dsl::{
+    author_id as tags_author_id, flume_seq as tags_flume_seq, key_id as tags_key_id,
+    tags as tags_table,
+};
+use crate::db::schema::{tag_operations, tags};
+use crate::db::{Error, SqliteConnection};
+use crate::ssb_message::*;
+use diesel::dsl::sql;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Integer, Nullable};
+use diesel::sqlite::Sqlite;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Caller must check that the message is actually a tag.
+//
+// A tag message without a `message` creates a tag. One with a `message` tags that message with
+// its `root` tag, or untags it when `tagged` is false.
+pub fn insert_tag(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+    author_id: i32,
+    seq: i64,
+) -> Result<(), Error> {
+    let content = &message.value.content;
+
+    match (&content["root"], &content["message"]) {
+        (Value::String(tag), Value::String(tagged_message)) => {
+            insert_into(tag_operations_table)
+                .values((
+                    tag_operations_flume_seq.eq(seq),
+                    tag_operations_tag_key_id.eq(find_or_create_key(connection, tag)?),
+                    tag_operations_message_key_id
+                        .eq(find_or_create_key(connection, tagged_message)?),
+                    tag_operations_author_id.eq(author_id),
+                    tag_operations_tagged.eq(content["tagged"] == true),
+                ))
+                .execute(connection)?;
+        }
+        (_, Value::Null) => {
+            insert_into(tags_table)
+                .values((
+                    tags_flume_seq.eq(seq),
+                    tags_key_id.eq(message_key_id),
+                    tags_author_id.eq(author_id),
+                ))
+                .execute(connection)?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The `(key_id, flume_seq, flume_seq)` of the tags an author created, or every tag, for paging in
+/// the order they were created.
+pub fn tags_query<'a>(
+    author_id: Option<i32>,
+) -> tags::BoxedQuery<'a, Sqlite, (Integer, Nullable<BigInt>, Nullable<BigInt>)> {
+    let mut query = tags_table
+        .select((
+            tags_key_id,
+            tags_flume_seq.nullable(),
+            tags_flume_seq.nullable(),
+        ))
+        .into_boxed();
+
+    if let Some(author_id) = author_id {
+        query = query.filter(tags_author_id.eq(author_id));
+    }
+
+    query
+}
+
+/// The author id of a tag, if the tag is in the db.
+pub fn get_tag_author(connection: &SqliteConnection, key_id: i32) -> Result<Option<i32>, Error> {
+    tags_table
+        .select(tags_author_id)
+        .filter(tags_key_id.eq(key_id))
+        .first(connection)
+        .optional()
+}
+
+/// The `(key_id, flume_seq, flume_seq)` of the posts tagged with a tag and the operations that
+/// tagged them, for paging in the order they were tagged. Posts that aren't in the db yet are left
+/// out.
+///
+/// Only the tag's author can tag and untag messages with it, everyone else's operations are
+/// ignored. A post stays tagged while the author's latest operation on it is a tag.
+pub fn tagged_posts_query<'a>(
+    tag_key_id: i32,
+) -> tag_operations::BoxedQuery<'a, Sqlite, (Integer, Nullable<BigInt>, Nullable<BigInt>)> {
+    let posts = messages_table
+        .select(messages_key_id)
+        .filter(messages_content_type.eq("post"));
+
+    tag_operations_table
+        .select((
+            tag_operations_message_key_id,
+            tag_operations_flume_seq.nullable(),
+            tag_operations_flume_seq.nullable(),
+        ))
+        .filter(tag_operations_tag_key_id.eq(tag_key_id))
+        .filter(tag_operations_tagged.eq(true))
+        .filter(tag_operations_message_key_id.eq_any(posts))
+        .filter(sql::<Bool>(
+            "tag_operations.author_id = \
+             (SELECT author_id FROM tags WHERE tags.key_id = tag_operations.tag_key_id) \
+             AND NOT EXISTS (SELECT 1 FROM tag_operations AS later \
+             WHERE later.tag_key_id = tag_operations.tag_key_id \
+             AND later.message_key_id = tag_operations.message_key_id \
+             AND later.author_id = tag_operations.author_id \
+             AND later.flume_seq > tag_operations.flume_seq)",
+        ))
+        .into_boxed()
+}
+
+/// The key ids of the tags a message is tagged with by the tags' authors, in the order they were
+/// applied.
+pub fn get_message_tags(
+    connection: &SqliteConnection,
+    message_key_id: i32,
+) -> Result<Vec<i32>, Error> {
+    let operations = tag_operations_table
+        .inner_join(
+            tags_table.on(tags_key_id
+                .eq(tag_operations_tag_key_id)
+                .and(tags_author_id.eq(tag_operations_author_id))),
+        )
+        .select((
+            tag_operations_tag_key_id,
+            tag_operations_message_key_id,
+            tag_operations_tagged,
+            tag_operations_flume_seq,
+        ))
+        .filter(tag_operations_message_key_id.eq(message_key_id))
+        .order(tag_operations_flume_seq.asc())
+        .load(connection)?;
+
+    let tags = current_tags(operations)
+        .into_iter()
+        .map(|(tag_key_id, _, _)| tag_key_id)
+        .collect();
+
+    Ok(tags)
+}
+
+/// Replays tag operations, oldest first, into the `(tag_key_id, message_key_id, flume_seq)`s that
+/// are still tagged, in the order they were tagged.
+///
+/// The operations must all be by their tag's author. A message stays tagged while the latest
+/// operation for it is a tag, and the flume seq is when that happened.
+fn current_tags(operations: Vec<(i32, i32, bool, i64)>) -> Vec<(i32, i32, i64)> {
+    let mut latest = HashMap::new();
+    operations
+        .into_iter()
+        .for_each(|(tag_key_id, message_key_id, tagged, seq)| {
+            latest.insert((tag_key_id, message_key_id), (tagged, seq));
+        });
+
+    let mut tags = latest
+        .into_iter()
+        .filter(|(_, (tagged, _))| *tagged)
+        .map(|((tag_key_id, message_key_id), (_, seq))| (tag_key_id, message_key_id, seq))
+        .collect::<Vec<_>>();
+    tags.sort_by_key(|(_, _, seq)| *seq);
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::models::keys::find_or_create_key;
+    use crate::db::models::messages::insert_message;
+    use crate::db::models::tags::{get_message_tags, insert_tag, tagged_posts_query};
+    use crate::ssb_message::SsbMessage;
+    use crate::utils::establish_connection;
+    use diesel::prelude::*;
+    use diesel::result::Error;
+    use serde_json::json;
+
+    #[test]
+    fn tag_and_untag_messages() {
+        let connection = establish_connection();
+        connection.test_transaction::<_, Error, _>(|| {
+            let insert = |key: &str, author_id: i32, seq: i64, tagged: Option<(&str, bool)>| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.content = match tagged {
+                    Some((message, tagged)) => json!({
+                        "type": "tag",
+                        "version": 1,
+                        "root": "%tag",
+                        "message": message,
+                        "tagged": tagged
+                    }),
+                    None => json!({"type": "tag", "version": 1}),
+                };
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                insert_tag(&connection, &msg, key_id, author_id, seq).unwrap();
+                key_id
+            };
+            let post = |key: &str, seq: i64| {
+                let mut msg = SsbMessage {
+                    key: key.to_owned(),
+                    ..SsbMessage::default()
+                };
+                msg.value.content = json!({"type": "post", "text": key});
+                let key_id = find_or_create_key(&connection, key).unwrap();
+                insert_message(&connection, &msg, seq, key_id, false, 3).unwrap();
+                key_id
+            };
+
+            let tag = insert("%tag", 1, 1, None);
+            insert("%tag_first", 1, 2, Some(("%first", true)));
+            insert("%tag_second", 1, 3, Some(("%second", true)));
+            insert("%katie_tags_first", 2, 4, Some(("%first", true)));
+            insert("%untag_second", 1, 5, Some(("%second", false)));
+            insert("%untag_first", 1, 6, Some(("%first", false)));
+            insert("%katie_tags_third", 2, 7, Some(("%third", true)));
+            insert("%tag_third", 1, 8, Some(("%third", true)));
+
+            let first = post("%first", 9);
+            let second = post("%second", 10);
+            let third = post("%third", 11);
+
+            // Katie didn't create the tag, so her tagging the first post doesn't count.
+            assert_eq!(
+                tagged_posts_query(tag).load::<(i32, Option<i64>, Option<i64>)>(&connection)?,
+                vec![(third, Some(8), Some(8))]
+            );
+            assert_eq!(get_message_tags(&connection, first)?, Vec::<i32>::new());
+            assert_eq!(get_message_tags(&connection, second)?, Vec::<i32>::new());
+            assert_eq!(get_message_tags(&connection, third)?, vec![tag]);
+            Ok(())
+        });
+    }
+}
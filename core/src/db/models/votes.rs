@@ -2,8 +2,9 @@ use super::authors::{find_or_create_author, Author};
 use super::keys::{find_or_create_key, Key};
 use crate::db::schema::votes;
 use crate::db::schema::votes::dsl::{
-    link_from_author_id as link_from_author_col, link_to_key_id as link_to_key_col, value,
-    votes as votes_table,
+    asserted_timestamp as asserted_timestamp_col, expression as expression_col,
+    link_from_author_id as link_from_author_col, link_from_key_id as link_from_key_col,
+    link_to_key_id as link_to_key_col, value, votes as votes_table,
 };
 use crate::db::SqliteConnection;
 use crate::ssb_message::*;
@@ -19,20 +20,36 @@ pub struct Vote {
     pub link_from_author_id: i32,
     pub link_to_key_id: i32,
     pub value: i32,
+    /// The vote message.
+    pub link_from_key_id: i32,
+    pub asserted_timestamp: i64,
+    /// How the author reacted, eg. "Like", "Dig" or an emoji. Older clients don't set one.
+    pub expression: Option<String>,
 }
 
-pub fn insert_or_update_votes(connection: &SqliteConnection, message: &SsbMessage) {
+pub fn insert_or_update_votes(
+    connection: &SqliteConnection,
+    message: &SsbMessage,
+    message_key_id: i32,
+) {
     if let Value::Number(vote_value) = &message.value.content["vote"]["value"] {
         if let Value::String(link) = &message.value.content["vote"]["link"] {
-            let author_id = find_or_create_author(&connection, &message.value.author).unwrap();
+            let author_id = find_or_create_author(connection, &message.value.author).unwrap();
             let link_to_key = find_or_create_key(connection, link).unwrap();
 
+            let expression = message.value.content["vote"]["expression"]
+                .as_str()
+                .filter(|expression| !expression.is_empty());
+
             if let Some(vote_num) = vote_value.as_i64().map(|num| num as i32) {
                 replace_into(votes_table)
                     .values((
                         link_from_author_col.eq(author_id),
                         link_to_key_col.eq(link_to_key),
                         value.eq(vote_num),
+                        link_from_key_col.eq(message_key_id),
+                        asserted_timestamp_col.eq(message.value.timestamp as i64),
+                        expression_col.eq(expression),
                     ))
                     .execute(connection)
                     .unwrap();
@@ -7,6 +7,14 @@ table! {
     }
 }
 
+table! {
+    author_texts (rowid) {
+        rowid -> Integer,
+        name -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
 table! {
     authors (id) {
         id -> Nullable<Integer>,
@@ -20,6 +28,7 @@ table! {
         id -> Nullable<Integer>,
         link_from_key_id -> Integer,
         link_to_blob_id -> Integer,
+        rel -> Text,
     }
 }
 
@@ -38,12 +47,55 @@ table! {
     }
 }
 
+table! {
+    channel_links (id) {
+        id -> Nullable<Integer>,
+        link_from_key_id -> Integer,
+        link_to_channel_id -> Integer,
+    }
+}
+
+table! {
+    channel_subscriptions (id) {
+        id -> Nullable<Integer>,
+        author_id -> Integer,
+        channel_id -> Integer,
+        subscribed -> Bool,
+    }
+}
+
+table! {
+    channels (id) {
+        id -> Nullable<Integer>,
+        channel -> Text,
+    }
+}
+
 table! {
     root_posts (flume_seq) {
         flume_seq -> BigInt,
         asserted_timestamp -> BigInt,
         key_id -> Integer,
         author_id -> Integer,
+        last_activity_flume_seq -> BigInt,
+    }
+}
+
+table! {
+    post_edits (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        revision_root_key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
+table! {
+    recipients (id) {
+        id -> Nullable<Integer>,
+        link_from_key_id -> Integer,
+        link_to_author_id -> Integer,
     }
 }
 
@@ -64,6 +116,21 @@ table! {
         contact_author_id -> Integer,
         is_decrypted -> Bool,
         state -> Nullable<Integer>,
+        key_id -> Integer,
+    }
+}
+
+table! {
+    content_warnings (key_id) {
+        key_id -> Integer,
+        content_warning -> Text,
+    }
+}
+
+table! {
+    indexer_migrations (indexer, version) {
+        indexer -> Text,
+        version -> Integer,
     }
 }
 
@@ -79,6 +146,7 @@ table! {
         id -> Nullable<Integer>,
         link_from_key_id -> Integer,
         link_to_key_id -> Integer,
+        rel -> Text,
     }
 }
 
@@ -87,6 +155,7 @@ table! {
         id -> Nullable<Integer>,
         link_from_key_id -> Integer,
         link_to_author_id -> Integer,
+        rel -> Text,
     }
 }
 
@@ -130,6 +199,9 @@ table! {
         link_from_author_id -> Integer,
         link_to_key_id -> Integer,
         value -> Integer,
+        link_from_key_id -> Integer,
+        asserted_timestamp -> BigInt,
+        expression -> Nullable<Text>,
     }
 }
 
@@ -140,13 +212,136 @@ table! {
     }
 }
 
+table! {
+    git_repos (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
+table! {
+    git_updates (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        repo_key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
+table! {
+    git_refs (update_flume_seq, name) {
+        update_flume_seq -> BigInt,
+        name -> Text,
+        sha1 -> Nullable<Text>,
+    }
+}
+
+table! {
+    issues (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        repo_key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
+table! {
+    pull_requests (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        repo_key_id -> Integer,
+        author_id -> Integer,
+        branch -> Nullable<Text>,
+        head_repo_key_id -> Nullable<Integer>,
+        head_branch -> Nullable<Text>,
+    }
+}
+
+table! {
+    issue_edits (flume_seq, issue_key_id) {
+        flume_seq -> BigInt,
+        issue_key_id -> Integer,
+        author_id -> Integer,
+        is_open -> Nullable<Bool>,
+        title -> Nullable<Text>,
+    }
+}
+
+table! {
+    tags (flume_seq) {
+        flume_seq -> BigInt,
+        key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
+table! {
+    tag_operations (flume_seq) {
+        flume_seq -> BigInt,
+        tag_key_id -> Integer,
+        message_key_id -> Integer,
+        author_id -> Integer,
+        tagged -> Bool,
+    }
+}
+
+table! {
+    blogs (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        author_id -> Integer,
+        title -> Nullable<Text>,
+        summary -> Nullable<Text>,
+        thumbnail -> Nullable<Text>,
+        blog -> Nullable<Text>,
+    }
+}
+
+table! {
+    polls (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        author_id -> Integer,
+        closes_at -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    positions (flume_seq) {
+        flume_seq -> BigInt,
+        asserted_timestamp -> BigInt,
+        key_id -> Integer,
+        poll_key_id -> Integer,
+        author_id -> Integer,
+    }
+}
+
 allow_tables_to_appear_in_same_query!(
     abouts,
+    author_texts,
     authors,
     blob_links,
     blobs,
+    blogs,
     branches,
+    channel_links,
+    channel_subscriptions,
+    channels,
     contacts,
+    content_warnings,
+    git_refs,
+    git_repos,
+    git_updates,
+    indexer_migrations,
+    issue_edits,
+    issues,
     keys,
     links,
     mentions,
@@ -154,6 +349,13 @@ allow_tables_to_appear_in_same_query!(
     threads,
     votes,
     texts,
+    polls,
+    positions,
+    post_edits,
+    pull_requests,
+    recipients,
     reply_posts,
-    root_posts
+    root_posts,
+    tag_operations,
+    tags
 );
@@ -1,13 +1,28 @@
+use super::channel::Channel;
+use super::input_objects::Privacy;
 use crate::db::models::abouts::{get_author_abouts, AboutDescription, AboutImage, AboutName};
+use crate::db::models::contacts::get_contact_state;
 use crate::db::schema::authors::dsl::{
-    author as authors_author, authors as authors_table, id as authors_id,
+    author as authors_author, authors as authors_table, id as authors_id, is_me as authors_is_me,
 };
+use crate::db::schema::channel_subscriptions::dsl::{
+    author_id as channel_subscriptions_author_id, channel_id as channel_subscriptions_channel_id,
+    channel_subscriptions as channel_subscriptions_table,
+    subscribed as channel_subscriptions_subscribed,
+};
+use crate::db::schema::channels::dsl::{
+    channel as channels_channel, channels as channels_table, id as channels_id,
+};
+use crate::db::schema::contacts;
 use crate::db::schema::contacts::dsl::{
     author_id as contacts_author_id, contact_author_id as contacts_contact_author_id,
-    contacts as contacts_table, state as contacts_state,
+    contacts as contacts_table, is_decrypted as contacts_is_decrypted, state as contacts_state,
 };
 use crate::db::Context;
 use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::Integer;
+use diesel::sqlite::Sqlite;
 use juniper::FieldResult;
 
 #[derive(Default)]
@@ -38,6 +53,74 @@ pub struct PublicPrivateContactStatus {
     pub public: ContactState,
 }
 
+impl From<Option<i32>> for ContactState {
+    fn from(state: Option<i32>) -> ContactState {
+        match state {
+            Some(1) => ContactState::Follow,
+            Some(-1) => ContactState::Block,
+            _ => ContactState::Neutral,
+        }
+    }
+}
+
+/// Is the author the current author?
+fn is_me(connection: &SqliteConnection, author_id: i32) -> Result<bool, Error> {
+    let is_me = authors_table
+        .select(authors_is_me)
+        .filter(authors_id.eq(author_id))
+        .first::<Option<bool>>(connection)
+        .optional()?
+        .flatten()
+        .unwrap_or(false);
+
+    Ok(is_me)
+}
+
+/// Private contact messages can only be decrypted by the current author, so the private state is
+/// only looked up when `author_id` is the current author.
+fn get_contact_status(
+    connection: &SqliteConnection,
+    author_id: i32,
+    contact_author_id: i32,
+) -> Result<PublicPrivateContactStatus, Error> {
+    let public = get_contact_state(connection, author_id, contact_author_id, false)?;
+
+    let private = if is_me(connection, author_id)? {
+        let state = get_contact_state(connection, author_id, contact_author_id, true)?;
+        Some(state.into())
+    } else {
+        None
+    };
+
+    Ok(PublicPrivateContactStatus {
+        public: public.into(),
+        private,
+    })
+}
+
+/// Loads the authors selected by a query on the contacts table, only using public contact messages,
+/// private contact messages or both.
+fn load_contact_authors(
+    connection: &SqliteConnection,
+    query: contacts::BoxedQuery<Sqlite, Integer>,
+    privacy: &Privacy,
+) -> Result<Vec<Author>, Error> {
+    let query = match privacy {
+        Privacy::Private => query.filter(contacts_is_decrypted.eq(true)),
+        Privacy::Public => query.filter(contacts_is_decrypted.eq(false)),
+        Privacy::All => query,
+    };
+
+    let authors = query
+        .distinct()
+        .load::<i32>(connection)?
+        .into_iter()
+        .map(|author_id| Author { author_id })
+        .collect();
+
+    Ok(authors)
+}
+
 graphql_object!(Author: Context |&self| {
 
     description: "The author of a feed."
@@ -45,19 +128,19 @@ graphql_object!(Author: Context |&self| {
     /// The self assigned name of the author, if given.
     field name(&executor) -> FieldResult<Option<String>> {
         let connection = &executor.context().connection.get()?;
-        let name = get_author_abouts::<AboutName>(&connection, self.author_id)?;
+        let name = get_author_abouts::<AboutName>(connection, self.author_id)?;
         Ok(name)
     }
     /// The self assigned description of the author, if given.
     field description(&executor) -> FieldResult<Option<String>> {
         let connection = &executor.context().connection.get()?;
-        let description = get_author_abouts::<AboutDescription>(&connection, self.author_id)?;
+        let description = get_author_abouts::<AboutDescription>(connection, self.author_id)?;
         Ok(description)
     }
     /// The self assigned image_link of the author, if given.
     field image_link(&executor) -> FieldResult<Option<String>> {
         let connection = &executor.context().connection.get()?;
-        let image_link = get_author_abouts::<AboutImage>(&connection, self.author_id)?;
+        let image_link = get_author_abouts::<AboutImage>(connection, self.author_id)?;
         Ok(image_link)
     }
     /// The public key of the author on the SSB network.
@@ -70,6 +153,7 @@ graphql_object!(Author: Context |&self| {
         Ok(id)
     }
     /// The author's relationship toward another author. Eg, Does this author block the other?
+    /// The private state is only available when this author is the current author.
     field contact_status_to(&executor, other_author: String) -> FieldResult<PublicPrivateContactStatus> {
 
         let connection = &executor.context().connection.get()?;
@@ -77,40 +161,28 @@ graphql_object!(Author: Context |&self| {
         let other_author_id = authors_table
             .select(authors_id)
             .filter(authors_author.eq(other_author))
-            .first::<Option<i32>>(connection)?;
-
-        let state = contacts_table
-            .select(contacts_state)
-            .filter(contacts_author_id.eq(self.author_id))
-            .filter(contacts_contact_author_id.nullable().eq(other_author_id))
             .first::<Option<i32>>(connection)
-            .optional()?;
-
-        let status = match state {
-            Some(Some(1)) => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Follow,
-                    private: None
-                }
-            },
-            Some(Some(-1)) => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Block,
-                    private: None
-                }
+            .optional()?
+            .flatten();
+
+        let status = match other_author_id {
+            Some(other_author_id) => get_contact_status(connection, self.author_id, other_author_id)?,
+            // Nothing is known about the other author, so there's no contact message for them.
+            None => PublicPrivateContactStatus {
+                public: ContactState::Neutral,
+                private: if is_me(connection, self.author_id)? {
+                    Some(ContactState::Neutral)
+                } else {
+                    None
+                },
             },
-            _ => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Neutral,
-                    private: None
-                }
-            }
         };
 
         Ok(status)
     }
 
     /// Another author's relationship toward this author. Eg, do they block this author?
+    /// The private state is only available when the other author is the current author.
     field contact_status_from(&executor, other_author: String) -> FieldResult<PublicPrivateContactStatus> {
 
         let connection = &executor.context().connection.get()?;
@@ -118,121 +190,115 @@ graphql_object!(Author: Context |&self| {
         let other_author_id = authors_table
             .select(authors_id)
             .filter(authors_author.eq(other_author))
-            .first::<Option<i32>>(connection)?;
-
-        let state = contacts_table
-            .select(contacts_state)
-            .filter(contacts_contact_author_id.eq(self.author_id))
-            .filter(contacts_author_id.nullable().eq(other_author_id))
             .first::<Option<i32>>(connection)
-            .optional()?;
-
-        let status = match state {
-            Some(Some(1)) => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Follow,
-                    private: None
-                }
-            },
-            Some(Some(-1)) => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Block,
-                    private: None
-                }
+            .optional()?
+            .flatten();
+
+        let status = match other_author_id {
+            Some(other_author_id) => get_contact_status(connection, other_author_id, self.author_id)?,
+            // Nothing is known about the other author, so they haven't published a contact message
+            // and they can't be the current author.
+            None => PublicPrivateContactStatus {
+                public: ContactState::Neutral,
+                private: None,
             },
-            _ => {
-                PublicPrivateContactStatus{
-                    public: ContactState::Neutral,
-                    private: None
-                }
-            }
         };
 
         Ok(status)
     }
-    // TODO: Think about how to do private follows / blocks. This could be exposed at the root
-    // query level?
 
-    /// The authors that this author follows. 
-    field follows(&executor) -> FieldResult<Vec<Author>> {
+    /// The authors that this author follows.
+    field follows(
+        &executor,
+        /// Use public contact messages, private contact messages or both.
+        privacy = (Privacy::Public): Privacy,
+        ) -> FieldResult<Vec<Author>> {
         let connection = &executor.context().connection.get()?;
 
-        let authors = contacts_table
-            .inner_join(
-                authors_table.on(authors_id.eq(contacts_author_id.nullable()))
-                )
+        let query = contacts_table
             .select(contacts_contact_author_id)
-            .filter(authors_id.eq(self.author_id))
+            .filter(contacts_author_id.eq(self.author_id))
             .filter(contacts_state.eq(1))
-            .load::<i32>(connection)?
-            .into_iter()
-            .map(|author_id|{
-                Author{author_id}
-            })
-            .collect();
+            .into_boxed();
+
+        let authors = load_contact_authors(connection, query, &privacy)?;
 
         Ok(authors)
     }
-    /// The authors that this author blocks. 
-    field blocks(&executor) -> FieldResult<Vec<Author>> {
+    /// The authors that this author blocks.
+    field blocks(
+        &executor,
+        /// Use public contact messages, private contact messages or both.
+        privacy = (Privacy::Public): Privacy,
+        ) -> FieldResult<Vec<Author>> {
         let connection = &executor.context().connection.get()?;
 
-        let authors = authors_table
-            .inner_join(
-                contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
-                )
+        let query = contacts_table
             .select(contacts_contact_author_id)
-            .filter(authors_id.eq(self.author_id))
+            .filter(contacts_author_id.eq(self.author_id))
             .filter(contacts_state.eq(-1))
-            .load::<i32>(connection)?
-            .into_iter()
-            .map(|author_id|{
-                Author{author_id}
-            })
-            .collect();
+            .into_boxed();
+
+        let authors = load_contact_authors(connection, query, &privacy)?;
 
         Ok(authors)
     }
     /// Other authors that follow this author.
-    field followedBy(&executor) -> FieldResult<Vec<Author>> {
+    field followedBy(
+        &executor,
+        /// Use public contact messages, private contact messages or both.
+        privacy = (Privacy::Public): Privacy,
+        ) -> FieldResult<Vec<Author>> {
         let connection = &executor.context().connection.get()?;
 
-        let authors = authors_table
-            .inner_join(
-                contacts_table.on(authors_id.eq(contacts_contact_author_id.nullable()))
-                )
+        let query = contacts_table
             .select(contacts_author_id)
-            .filter(authors_id.eq(self.author_id))
+            .filter(contacts_contact_author_id.eq(self.author_id))
             .filter(contacts_state.eq(1))
-            .load::<i32>(connection)?
-            .into_iter()
-            .map(|author_id|{
-                Author{author_id}
-            })
-            .collect();
+            .into_boxed();
+
+        let authors = load_contact_authors(connection, query, &privacy)?;
 
         Ok(authors)
     }
     /// Other authors that block this author.
-    field blockedBy(&executor) -> FieldResult<Vec<Author>> {
+    field blockedBy(
+        &executor,
+        /// Use public contact messages, private contact messages or both.
+        privacy = (Privacy::Public): Privacy,
+        ) -> FieldResult<Vec<Author>> {
+        let connection = &executor.context().connection.get()?;
+
+        let query = contacts_table
+            .select(contacts_author_id)
+            .filter(contacts_contact_author_id.eq(self.author_id))
+            .filter(contacts_state.eq(-1))
+            .into_boxed();
 
+        let authors = load_contact_authors(connection, query, &privacy)?;
+
+        Ok(authors)
+    }
+    /// The channels this author is currently subscribed to.
+    field subscribed_channels(&executor) -> FieldResult<Vec<Channel>> {
         let connection = &executor.context().connection.get()?;
 
-        let authors = authors_table
+        let channels = channel_subscriptions_table
             .inner_join(
-                contacts_table.on(authors_id.eq(contacts_contact_author_id.nullable()))
+                channels_table.on(channels_id.eq(channel_subscriptions_channel_id.nullable()))
                 )
-            .select(contacts_author_id)
-            .filter(authors_id.eq(self.author_id))
-            .filter(contacts_state.eq(-1))
+            .select(channel_subscriptions_channel_id)
+            .filter(channel_subscriptions_author_id.eq(self.author_id))
+            .filter(channel_subscriptions_subscribed.eq(true))
+            .order(channels_channel.asc())
             .load::<i32>(connection)?
             .into_iter()
-            .map(|author_id|{
-                Author{author_id}
+            .map(|channel_id|{
+                Channel{channel_id, cursor: None}
             })
             .collect();
 
-        Ok(authors)
+        Ok(channels)
     }
 });
 
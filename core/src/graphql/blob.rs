@@ -0,0 +1,23 @@
+use crate::db::schema::blobs::dsl::{blob as blobs_blob, blobs as blobs_table, id as blobs_id};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+pub struct Blob {
+    pub blob_id: i32,
+}
+
+graphql_object!(Blob: Context |&self| {
+    description: "A blob (a file, eg. an image) that messages can link to."
+
+    /// The id of the blob, derived from the hash of its contents.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let blob = blobs_table
+            .select(blobs_blob)
+            .filter(blobs_id.eq(self.blob_id))
+            .first::<String>(&connection)?;
+
+        Ok(blob)
+    }
+});
@@ -0,0 +1,81 @@
+use super::author::Author;
+use super::page_info::{decode_order_cursor, Page};
+use super::post_connection::PostConnection;
+use crate::db::models::abouts::get_key_detail;
+use crate::db::models::blogs::{get_blog_comments, Blog as DbBlog};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct Blog {
+    pub blog: DbBlog,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(Blog: Context |&self| {
+    description: "A long-form blog post. The body is in a blob, the message only has a title, summary and thumbnail."
+
+    /// The id of the blog message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.blog.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The author of the blog.
+    field author() -> Author {
+        Author{author_id: self.blog.author_id}
+    }
+    /// The title. The author can change it with an about message.
+    field title(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+
+        let about_title = get_key_detail(&connection, self.blog.key_id, "title")?
+            .and_then(|title| title.as_str().map(|title| title.to_owned()));
+
+        Ok(about_title.or_else(|| self.blog.title.clone()))
+    }
+    /// A short summary of the blog.
+    field summary() -> Option<&str> {
+        self.blog.summary.as_deref()
+    }
+    /// The blob id of the thumbnail image.
+    field thumbnail_link() -> Option<&str> {
+        self.blog.thumbnail.as_deref()
+    }
+    /// The blob id of the blog's body. Fetch the blob to read it.
+    field blog_link() -> Option<&str> {
+        self.blog.blog.as_deref()
+    }
+    /// The asserted timestamp of the blog. Like all asserted times, it can't be totally trusted.
+    field asserted_timestamp() -> f64 {
+        self.blog.asserted_timestamp as f64
+    }
+    /// The posts that reply to the blog, in the order they were received.
+    field comments(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+    ) -> FieldResult<PostConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+        let comments = get_blog_comments(&connection, self.blog.key_id)?;
+
+        PostConnection::from_sorted(&comments, page, next, "Cursor doesn't match a comment on this blog")
+    }
+});
@@ -0,0 +1,60 @@
+use super::blog::Blog;
+use super::page_info::PageInfo;
+use crate::db::models::blogs::Blog as DbBlog;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct BlogConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub blogs_and_cursor: Vec<(DbBlog, String)>,
+}
+
+graphql_object!(BlogConnection: Context |&self| {
+    description: "Connection to collections of blogs"
+
+    /// The total count of blogs that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<BlogEdge>{
+        self.blogs_and_cursor
+            .iter()
+            .map(|(blog, cursor)|{
+                Blog{blog: blog.clone(), cursor: Some(cursor.to_owned())}
+            })
+            .map(|blog|{
+                BlogEdge{
+                    node: blog
+                }
+            })
+            .collect::<Vec<BlogEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+#[derive(Default)]
+pub struct BlogEdge {
+    pub node: Blog,
+}
+
+graphql_object!(BlogEdge: Context |&self| {
+    description: "Edge connection to a blog"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Blog {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
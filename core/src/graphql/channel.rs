@@ -0,0 +1,44 @@
+use crate::db::schema::channel_subscriptions::dsl::{
+    channel_id as channel_subscriptions_channel_id,
+    channel_subscriptions as channel_subscriptions_table,
+    subscribed as channel_subscriptions_subscribed,
+};
+use crate::db::schema::channels::dsl::{
+    channel as channels_channel, channels as channels_table, id as channels_id,
+};
+use crate::db::Context;
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+pub struct Channel {
+    pub channel_id: i32,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(Channel: Context |&self| {
+    description: "A channel that posts can be published in, either with the `channel` field or a `#hashtag`. Authors can subscribe to channels."
+
+    /// The normalized name of the channel, without the leading `#`.
+    field name(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let name = channels_table
+            .select(channels_channel)
+            .filter(channels_id.eq(self.channel_id))
+            .first::<String>(&connection)?;
+
+        Ok(name)
+    }
+
+    /// The number of authors currently subscribed to this channel.
+    field subscriber_count(&executor) -> FieldResult<i32> {
+        let connection = executor.context().connection.get()?;
+        let count = channel_subscriptions_table
+            .select(count_star())
+            .filter(channel_subscriptions_channel_id.eq(self.channel_id))
+            .filter(channel_subscriptions_subscribed.eq(true))
+            .first::<i64>(&connection)?;
+
+        Ok(count as i32)
+    }
+});
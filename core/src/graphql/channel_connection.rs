@@ -0,0 +1,57 @@
+use super::channel::Channel;
+use super::page_info::PageInfo;
+use crate::db::Context;
+use juniper::FieldResult;
+
+pub struct ChannelConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub channel_ids_and_cursor: Vec<(i32, String)>,
+}
+
+graphql_object!(ChannelConnection: Context |&self| {
+    description: "Connection to collections of channels"
+
+    /// The total count of channels that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<ChannelEdge>{
+        self.channel_ids_and_cursor
+            .iter()
+            .map(|(channel_id, cursor)|{
+                Channel{channel_id: *channel_id, cursor: Some(cursor.to_owned())}
+            })
+            .map(|channel|{
+                ChannelEdge{
+                    node: channel
+                }
+            })
+            .collect::<Vec<ChannelEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct ChannelEdge {
+    pub node: Channel,
+}
+
+graphql_object!(ChannelEdge: Context |&self| {
+    description: "Edge connection to a channel"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Channel {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
@@ -2,6 +2,7 @@ use crate::db::*;
 use itertools::Itertools;
 use juniper::FieldResult;
 
+use crate::db::local_state::{mark_read, mark_unread};
 use crate::db::models::append_item;
 use diesel::prelude::*;
 use diesel::result::Error;
@@ -70,7 +71,7 @@ graphql_object!(DbMutation: Context |&self| {
                 connection.transaction::<_, Error, _>(||{
                     chunk
                         .for_each(|log_entry|{
-                            append_item(&(*connection), &context.keys, log_entry.offset, &log_entry.data).unwrap_or_else(|err|{
+                            append_item(&connection, &context.keys, &context.indexers, log_entry.offset, &log_entry.data).unwrap_or_else(|err|{
                                 println!("error appending item. offset: {:?}, data: {:?}, err: {:?}", log_entry.offset, log_entry.data, err);
                                 panic!("error appending item")
                             });
@@ -82,6 +83,23 @@ graphql_object!(DbMutation: Context |&self| {
         let new_latest = get_latest(&connection)?;
         Ok(ProcessResults{chunk_size, latest_sequence: new_latest})
     }
+
+    /// Marks messages as read. Passing the id of a thread's root marks the root and all its
+    /// current replies as read. Read state is local to this db and is never published. Returns the
+    /// ids that were passed in.
+    field mark_read(&executor, ids: Vec<String>) -> FieldResult<Vec<String>> {
+        let connection = executor.context().rw_connection.lock()?;
+        mark_read(&connection, &ids)?;
+        Ok(ids)
+    }
+
+    /// Marks messages as unread. Passing the id of a thread's root marks the root and all its
+    /// current replies as unread. Returns the ids that were passed in.
+    field mark_unread(&executor, ids: Vec<String>) -> FieldResult<Vec<String>> {
+        let connection = executor.context().rw_connection.lock()?;
+        mark_unread(&connection, &ids)?;
+        Ok(ids)
+    }
 });
 
 #[derive(Default)]
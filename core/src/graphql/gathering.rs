@@ -0,0 +1,58 @@
+use super::author::Author;
+use crate::db::models::authors::get_my_author_id;
+use crate::db::models::gatherings::Gathering as DbGathering;
+use crate::db::Context;
+use juniper::FieldResult;
+
+pub struct Gathering {
+    pub gathering: DbGathering,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(Gathering: Context |&self| {
+    description: "A gathering (event). Its details and attendance come from about messages that point at it."
+
+    /// The id of the gathering message.
+    field id() -> &str {
+        &self.gathering.key
+    }
+    /// The author that created the gathering. Only their about messages can change its details.
+    field author() -> Author {
+        Author{author_id: self.gathering.author_id}
+    }
+    /// The title of the gathering, if given.
+    field title() -> Option<&str> {
+        self.gathering.title.as_deref()
+    }
+    /// The description of the gathering, if given.
+    field description() -> Option<&str> {
+        self.gathering.description.as_deref()
+    }
+    /// Where the gathering is, if given.
+    field location() -> Option<&str> {
+        self.gathering.location.as_deref()
+    }
+    /// The blob id of the gathering's image, if given.
+    field image_link() -> Option<&str> {
+        self.gathering.image.as_deref()
+    }
+    /// When the gathering starts, in milliseconds since the unix epoch.
+    field start_time() -> Option<f64> {
+        self.gathering.start_time.map(|time| time as f64)
+    }
+    /// The authors attending, in the order they said they would attend.
+    field attendees() -> Vec<Author> {
+        self.gathering
+            .attendee_ids
+            .iter()
+            .map(|author_id| Author{author_id: *author_id})
+            .collect()
+    }
+    /// Whether the current author is attending.
+    field is_attending(&executor) -> FieldResult<bool> {
+        let connection = executor.context().connection.get()?;
+        let my_author_id = get_my_author_id(&connection)?;
+
+        Ok(my_author_id.is_some_and(|author_id| self.gathering.attendee_ids.contains(&author_id)))
+    }
+});
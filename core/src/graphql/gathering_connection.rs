@@ -0,0 +1,59 @@
+use super::gathering::Gathering;
+use super::page_info::PageInfo;
+use crate::db::models::gatherings::Gathering as DbGathering;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct GatheringConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub gatherings_and_cursor: Vec<(DbGathering, String)>,
+}
+
+graphql_object!(GatheringConnection: Context |&self| {
+    description: "Connection to collections of gatherings"
+
+    /// The total count of gatherings, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<GatheringEdge>{
+        self.gatherings_and_cursor
+            .iter()
+            .map(|(gathering, cursor)|{
+                Gathering{gathering: gathering.clone(), cursor: Some(cursor.to_owned())}
+            })
+            .map(|gathering|{
+                GatheringEdge{
+                    node: gathering
+                }
+            })
+            .collect::<Vec<GatheringEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct GatheringEdge {
+    pub node: Gathering,
+}
+
+graphql_object!(GatheringEdge: Context |&self| {
+    description: "Edge connection to a gathering"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Gathering {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
@@ -0,0 +1,188 @@
+use super::author::Author;
+use super::issue::{Issue, PullRequest};
+use crate::db::models::git_repos::{
+    get_git_refs, get_git_repo_author, get_git_repo_name, get_git_update_commits,
+    get_git_update_refs, get_git_updates,
+};
+use crate::db::models::issues::{get_issues, get_pull_requests};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+pub struct GitRepo {
+    pub key_id: i32,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(GitRepo: Context |&self| {
+    description: "A git-ssb repository."
+
+    /// The id of the git-repo message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The author that created the repo. Only they can push to it.
+    field author(&executor) -> FieldResult<Option<Author>> {
+        let connection = executor.context().connection.get()?;
+        let author = get_git_repo_author(&connection, self.key_id)?
+            .map(|author_id| Author{author_id});
+
+        Ok(author)
+    }
+    /// The name of the repo, if it has one.
+    field name(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let name = get_git_repo_name(&connection, self.key_id)?;
+        Ok(name)
+    }
+    /// The branches and the commits they point at, by name.
+    field branches(&executor) -> FieldResult<Vec<GitRef>> {
+        let connection = executor.context().connection.get()?;
+        let branches = get_git_refs(&connection, self.key_id)?
+            .into_iter()
+            .filter(|(name, _)| name.starts_with("refs/heads/"))
+            .map(|(name, sha1)| {
+                GitRef{name: name.trim_start_matches("refs/heads/").to_owned(), sha1: Some(sha1)}
+            })
+            .collect();
+
+        Ok(branches)
+    }
+    /// All the refs, including branches and tags, by their full name.
+    field refs(&executor) -> FieldResult<Vec<GitRef>> {
+        let connection = executor.context().connection.get()?;
+        let refs = get_git_refs(&connection, self.key_id)?
+            .into_iter()
+            .map(|(name, sha1)| GitRef{name, sha1: Some(sha1)})
+            .collect();
+
+        Ok(refs)
+    }
+    /// The pushes to the repo, newest first.
+    field updates(&executor) -> FieldResult<Vec<GitUpdate>> {
+        let connection = executor.context().connection.get()?;
+        let updates = get_git_updates(&connection, self.key_id)?
+            .into_iter()
+            .map(|(key_id, asserted_timestamp)| GitUpdate{key_id, asserted_timestamp})
+            .collect();
+
+        Ok(updates)
+    }
+    /// The issues, newest first.
+    field issues(
+        &executor,
+        /// Only include open issues when true, or closed issues when false.
+        open: Option<bool>,
+    ) -> FieldResult<Vec<Issue>> {
+        let connection = executor.context().connection.get()?;
+        let issues = get_issues(&connection, self.key_id)?
+            .into_iter()
+            .filter(|issue| open.is_none_or(|open| issue.is_open == open))
+            .map(|issue| Issue{issue})
+            .collect();
+
+        Ok(issues)
+    }
+    /// The pull requests, newest first.
+    field pull_requests(
+        &executor,
+        /// Only include open pull requests when true, or closed ones when false.
+        open: Option<bool>,
+    ) -> FieldResult<Vec<PullRequest>> {
+        let connection = executor.context().connection.get()?;
+        let pull_requests = get_pull_requests(&connection, self.key_id)?
+            .into_iter()
+            .filter(|pull_request| open.is_none_or(|open| pull_request.issue.is_open == open))
+            .map(|pull_request| PullRequest{pull_request})
+            .collect();
+
+        Ok(pull_requests)
+    }
+});
+
+pub struct GitRef {
+    pub name: String,
+    pub sha1: Option<String>,
+}
+
+graphql_object!(GitRef: Context |&self| {
+    description: "A git ref, like a branch or a tag."
+
+    /// The name of the ref.
+    field name() -> &str {
+        &self.name
+    }
+    /// The commit the ref points at. Null when an update deleted the ref.
+    field sha1() -> Option<&str> {
+        self.sha1.as_deref()
+    }
+});
+
+pub struct GitCommit {
+    pub sha1: String,
+    pub title: Option<String>,
+}
+
+graphql_object!(GitCommit: Context |&self| {
+    description: "A commit pushed in an update."
+
+    /// The commit's sha1.
+    field sha1() -> &str {
+        &self.sha1
+    }
+    /// The first line of the commit message.
+    field title() -> Option<&str> {
+        self.title.as_deref()
+    }
+});
+
+pub struct GitUpdate {
+    pub key_id: i32,
+    pub asserted_timestamp: i64,
+}
+
+graphql_object!(GitUpdate: Context |&self| {
+    description: "A push to a git-ssb repository."
+
+    /// The id of the git-update message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The refs that were updated. Deleted refs have no sha1.
+    field refs(&executor) -> FieldResult<Vec<GitRef>> {
+        let connection = executor.context().connection.get()?;
+        let refs = get_git_update_refs(&connection, self.key_id)?
+            .into_iter()
+            .map(|(name, sha1)| GitRef{name, sha1})
+            .collect();
+
+        Ok(refs)
+    }
+    /// The commits that were pushed.
+    field commits(&executor) -> FieldResult<Vec<GitCommit>> {
+        let connection = executor.context().connection.get()?;
+        let commits = get_git_update_commits(&connection, self.key_id)?
+            .into_iter()
+            .map(|(sha1, title)| GitCommit{sha1, title})
+            .collect();
+
+        Ok(commits)
+    }
+    /// The asserted timestamp of the push. Like all asserted times, it can't be totally trusted.
+    field asserted_timestamp() -> f64 {
+        self.asserted_timestamp as f64
+    }
+});
@@ -0,0 +1,57 @@
+use super::git_repo::GitRepo;
+use super::page_info::PageInfo;
+use crate::db::Context;
+use juniper::FieldResult;
+
+pub struct GitRepoConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub repo_keys_and_cursor: Vec<(i32, String)>,
+}
+
+graphql_object!(GitRepoConnection: Context |&self| {
+    description: "Connection to collections of git-ssb repositories"
+
+    /// The total count of repositories that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<GitRepoEdge>{
+        self.repo_keys_and_cursor
+            .iter()
+            .map(|(key_id, cursor)|{
+                GitRepo{key_id: *key_id, cursor: Some(cursor.to_owned())}
+            })
+            .map(|repo|{
+                GitRepoEdge{
+                    node: repo
+                }
+            })
+            .collect::<Vec<GitRepoEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct GitRepoEdge {
+    pub node: GitRepo,
+}
+
+graphql_object!(GitRepoEdge: Context |&self| {
+    description: "Edge connection to a git-ssb repository"
+
+    /// The nodes in this connection
+    field node(&executor) -> &GitRepo {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
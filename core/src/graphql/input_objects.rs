@@ -9,6 +9,17 @@ pub enum Privacy {
     Public,
 }
 
+#[derive(GraphQLEnum)]
+/// Retrieve posts with a content warning, without one, or both.
+pub enum ContentWarningFilter {
+    /// Posts with and without content warnings.
+    Include,
+    /// Only posts without a content warning.
+    Exclude,
+    /// Only posts with a content warning.
+    Only,
+}
+
 #[derive(GraphQLEnum, Clone)]
 /// Retrieve objects ordered by asserted publish time or by received time 
 pub enum OrderBy {
@@ -25,4 +36,10 @@ pub enum OrderBy {
     /// all at once (like during onboarding to the network) because feeds are inserted into your db
     /// in a random order.
     Received,
+
+    /// Order threads by their latest activity, so a thread with a new reply comes after threads
+    /// that have been quiet for longer. Activity is measured in received order.
+    ///
+    /// Only threads have activity, everything else falls back to `Received`.
+    LastActivity,
 }
@@ -0,0 +1,132 @@
+use super::author::Author;
+use super::git_repo::GitRepo;
+use super::post::Post;
+use crate::db::models::issues::{Issue as DbIssue, PullRequest as DbPullRequest};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::schema::reply_posts::dsl::{
+    flume_seq as reply_posts_flume_seq, key_id as reply_posts_key_id,
+    reply_posts as reply_posts_table, root_post_id as reply_posts_root_post_id,
+};
+use crate::db::Context;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+use juniper::FieldResult;
+
+pub struct Issue {
+    pub issue: DbIssue,
+}
+
+graphql_object!(Issue: Context |&self| {
+    description: "An issue on a git-ssb repository."
+
+    /// The id of the issue message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = get_key(&connection, self.issue.key_id)?;
+        Ok(key)
+    }
+    /// The author of the issue.
+    field author() -> Author {
+        Author{author_id: self.issue.author_id}
+    }
+    /// The title from the latest edit, or the first line of the text.
+    field title() -> Option<&str> {
+        self.issue.title.as_deref()
+    }
+    /// The text of the issue.
+    field text() -> &str {
+        &self.issue.text
+    }
+    /// Whether the issue is open. Only the issue's author and the repo's author can close it.
+    field is_open() -> bool {
+        self.issue.is_open
+    }
+    /// The asserted timestamp of the issue. Like all asserted times, it can't be totally trusted.
+    field asserted_timestamp() -> f64 {
+        self.issue.asserted_timestamp as f64
+    }
+    /// The posts with the issue as their root, oldest first.
+    field comments(&executor) -> FieldResult<Vec<Post>> {
+        let connection = executor.context().connection.get()?;
+        let comments = get_comments(&connection, self.issue.key_id)?;
+        Ok(comments)
+    }
+});
+
+pub struct PullRequest {
+    pub pull_request: DbPullRequest,
+}
+
+graphql_object!(PullRequest: Context |&self| {
+    description: "A request to merge a branch into a git-ssb repository."
+
+    /// The id of the pull-request message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = get_key(&connection, self.pull_request.issue.key_id)?;
+        Ok(key)
+    }
+    /// The author of the pull request.
+    field author() -> Author {
+        Author{author_id: self.pull_request.issue.author_id}
+    }
+    /// The title from the latest edit, or the first line of the text.
+    field title() -> Option<&str> {
+        self.pull_request.issue.title.as_deref()
+    }
+    /// The text of the pull request.
+    field text() -> &str {
+        &self.pull_request.issue.text
+    }
+    /// Whether the pull request is open. Only its author and the repo's author can close it.
+    field is_open() -> bool {
+        self.pull_request.issue.is_open
+    }
+    /// The asserted timestamp of the pull request. Like all asserted times, it can't be totally
+    /// trusted.
+    field asserted_timestamp() -> f64 {
+        self.pull_request.issue.asserted_timestamp as f64
+    }
+    /// The branch to merge into.
+    field branch() -> Option<&str> {
+        self.pull_request.branch.as_deref()
+    }
+    /// The repo with the changes, often a fork.
+    field head_repo() -> Option<GitRepo> {
+        self.pull_request.head_repo_key_id.map(|key_id| GitRepo{key_id, cursor: None})
+    }
+    /// The branch with the changes.
+    field head_branch() -> Option<&str> {
+        self.pull_request.head_branch.as_deref()
+    }
+    /// The posts with the pull request as their root, oldest first.
+    field comments(&executor) -> FieldResult<Vec<Post>> {
+        let connection = executor.context().connection.get()?;
+        let comments = get_comments(&connection, self.pull_request.issue.key_id)?;
+        Ok(comments)
+    }
+});
+
+fn get_key(connection: &SqliteConnection, key_id: i32) -> Result<String, Error> {
+    keys_table
+        .select(keys_key)
+        .filter(keys_id.eq(key_id))
+        .first::<String>(connection)
+}
+
+fn get_comments(connection: &SqliteConnection, key_id: i32) -> Result<Vec<Post>, Error> {
+    let comments = reply_posts_table
+        .select(reply_posts_key_id)
+        .filter(reply_posts_root_post_id.eq(key_id))
+        .order(reply_posts_flume_seq.asc())
+        .load::<i32>(connection)?
+        .into_iter()
+        .map(|key_id| Post {
+            key_id,
+            cursor: None,
+        })
+        .collect();
+
+    Ok(comments)
+}
@@ -0,0 +1,76 @@
+use juniper::parser::{ParseError, ScalarToken, Token};
+use juniper::{DefaultScalarValue, InputValue, Object, ParseScalarResult, Value};
+use serde_json::Value as JsonValue;
+
+/// Arbitrary json, eg. the content of a message.
+pub struct Json(pub JsonValue);
+
+fn to_graphql_value(json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::null(),
+        JsonValue::Bool(b) => Value::scalar(*b),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) if i >= i64::from(i32::MIN) && i <= i64::from(i32::MAX) => {
+                Value::scalar(i as i32)
+            }
+            _ => Value::scalar(n.as_f64().unwrap_or(0.0)),
+        },
+        JsonValue::String(s) => Value::scalar(s.to_owned()),
+        JsonValue::Array(arr) => Value::list(arr.iter().map(to_graphql_value).collect()),
+        JsonValue::Object(kv) => {
+            let mut object = Object::with_capacity(kv.len());
+            kv.iter().for_each(|(key, value)| {
+                object.add_field(key.as_str(), to_graphql_value(value));
+            });
+            Value::object(object)
+        }
+    }
+}
+
+fn to_json_value(input: &InputValue) -> Option<JsonValue> {
+    match input {
+        InputValue::Null => Some(JsonValue::Null),
+        InputValue::Scalar(DefaultScalarValue::Int(i)) => Some(JsonValue::from(*i)),
+        InputValue::Scalar(DefaultScalarValue::Float(f)) => Some(JsonValue::from(*f)),
+        InputValue::Scalar(DefaultScalarValue::String(s)) => Some(JsonValue::from(s.as_str())),
+        InputValue::Scalar(DefaultScalarValue::Boolean(b)) => Some(JsonValue::from(*b)),
+        InputValue::Enum(e) => Some(JsonValue::from(e.as_str())),
+        InputValue::Variable(_) => None,
+        InputValue::List(items) => items
+            .iter()
+            .map(|item| to_json_value(&item.item))
+            .collect::<Option<Vec<_>>>()
+            .map(JsonValue::Array),
+        InputValue::Object(kv) => kv
+            .iter()
+            .map(|(key, value)| to_json_value(&value.item).map(|value| (key.item.clone(), value)))
+            .collect::<Option<serde_json::Map<_, _>>>()
+            .map(JsonValue::Object),
+    }
+}
+
+graphql_scalar!(Json as "JSON" {
+    description: "Arbitrary json, eg. the content of a message."
+
+    resolve(&self) -> Value {
+        to_graphql_value(&self.0)
+    }
+
+    from_input_value(v: &InputValue) -> Option<Json> {
+        to_json_value(v).map(Json)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a> {
+        match value {
+            ScalarToken::String(s) => Ok(DefaultScalarValue::String(s.to_owned())),
+            ScalarToken::Int(i) => i
+                .parse()
+                .map(DefaultScalarValue::Int)
+                .map_err(|_| ParseError::UnexpectedToken(Token::Scalar(value))),
+            ScalarToken::Float(f) => f
+                .parse()
+                .map(DefaultScalarValue::Float)
+                .map_err(|_| ParseError::UnexpectedToken(Token::Scalar(value))),
+        }
+    }
+});
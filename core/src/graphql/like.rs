@@ -1,11 +1,17 @@
 use super::author::*;
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
 use crate::db::*;
+use diesel::prelude::*;
 use juniper::FieldResult;
 
 #[derive(Default)]
 pub struct Like {
     pub author_id: i32,
     pub value: i32,
+    /// The vote message.
+    pub key_id: i32,
+    pub asserted_timestamp: i64,
+    pub expression: Option<String>,
 }
 
 graphql_object!(Like: Context |&self| {
@@ -18,5 +24,26 @@ graphql_object!(Like: Context |&self| {
     },
 
     /// The integer value of the like, may be positive or negative.
-    field value()-> i32{self.value}
+    field value()-> i32{self.value},
+
+    /// How the author reacted, eg. "Like", "Dig" or an emoji. Null if their client didn't say.
+    field expression() -> Option<&str> {
+        self.expression.as_deref()
+    },
+
+    /// The asserted timestamp of the vote message.
+    field timestamp() -> f64 {
+        self.asserted_timestamp as f64
+    },
+
+    /// The id of the vote message.
+    field message_id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
 });
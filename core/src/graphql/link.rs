@@ -0,0 +1,59 @@
+use super::author::Author;
+use super::blob::Blob;
+use super::message::Message;
+use crate::db::models::links::{Link as DbLink, LinkKind};
+use crate::db::Context;
+use juniper::FieldResult;
+
+pub struct Link {
+    pub link: DbLink,
+    pub cursor: Option<String>,
+}
+
+/// The message, author or blob a link points to.
+pub enum LinkTarget {
+    Message(Message),
+    Author(Author),
+    Blob(Blob),
+}
+
+graphql_union!(LinkTarget: Context |&self| {
+    description: "The message, author or blob a link points to."
+
+    instance_resolvers: |_| {
+        &Message => match *self { LinkTarget::Message(ref message) => Some(message), _ => None },
+        &Author => match *self { LinkTarget::Author(ref author) => Some(author), _ => None },
+        &Blob => match *self { LinkTarget::Blob(ref blob) => Some(blob), _ => None },
+    }
+});
+
+graphql_object!(Link: Context |&self| {
+    description: "A link from a message to another message, an author or a blob."
+
+    /// The message the link is in.
+    field from(&executor) -> FieldResult<Message> {
+        let connection = executor.context().connection.get()?;
+        let message = Message::load(&connection, self.link.link_from_key_id, None)?;
+
+        Ok(message)
+    }
+
+    /// The message, author or blob the link points to.
+    field to(&executor) -> FieldResult<LinkTarget> {
+        let connection = executor.context().connection.get()?;
+
+        let target = match self.link.kind {
+            LinkKind::Message => LinkTarget::Message(Message::load(&connection, self.link.link_to_id, None)?),
+            LinkKind::Author => LinkTarget::Author(Author{author_id: self.link.link_to_id}),
+            LinkKind::Blob => LinkTarget::Blob(Blob{blob_id: self.link.link_to_id}),
+        };
+
+        Ok(target)
+    }
+
+    /// Where in the message content the link was found, eg. `root`, `branch`, `mentions`,
+    /// `vote.link` or `contact`.
+    field rel(&executor) -> &str {
+        &self.link.rel
+    }
+});
@@ -0,0 +1,59 @@
+use super::link::Link;
+use super::page_info::PageInfo;
+use crate::db::models::links::Link as DbLink;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct LinkConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub links_and_cursor: Vec<(DbLink, String)>,
+}
+
+graphql_object!(LinkConnection: Context |&self| {
+    description: "Connection to collections of links"
+
+    /// The total count of links that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<LinkEdge>{
+        self.links_and_cursor
+            .iter()
+            .map(|(link, cursor)|{
+                Link{link: link.clone(), cursor: Some(cursor.to_owned())}
+            })
+            .map(|link|{
+                LinkEdge{
+                    node: link
+                }
+            })
+            .collect::<Vec<LinkEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct LinkEdge {
+    pub node: Link,
+}
+
+graphql_object!(LinkEdge: Context |&self| {
+    description: "Edge connection to a link"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Link {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
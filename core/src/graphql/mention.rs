@@ -1 +1,22 @@
+use super::author::Author;
+use super::post::Post;
+use crate::db::Context;
 
+pub struct Mention {
+    pub key_id: i32,
+    pub author_id: i32,
+}
+
+graphql_object!(Mention: Context |&self| {
+    description: "A post that mentions an author."
+
+    /// The author of the post.
+    field author(&executor) -> Author {
+        Author{author_id: self.author_id}
+    }
+
+    /// The post with the mention.
+    field post(&executor) -> Post {
+        Post{key_id: self.key_id, cursor: None}
+    }
+});
@@ -0,0 +1,163 @@
+use super::author::*;
+use super::json::Json;
+use super::message_about::MessageAbouts;
+use crate::db::models::abouts::get_message_abouts;
+use crate::db::models::messages::Message as DbMessage;
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::schema::messages::dsl::{
+    fork_key_id as messages_fork_key_id, key_id as messages_key_id, messages as messages_table,
+    root_key_id as messages_root_key_id,
+};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct Message {
+    pub key_id: i32,
+    pub cursor: Option<String>,
+    /// The row for this message. `None` if it's only linked to and we don't have it.
+    pub message: Option<DbMessage>,
+}
+
+impl Message {
+    /// Loads the message's row once, so the fields don't each query for it.
+    pub fn load(
+        connection: &SqliteConnection,
+        key_id: i32,
+        cursor: Option<String>,
+    ) -> Result<Message, diesel::result::Error> {
+        let message = messages_table
+            .filter(messages_key_id.eq(key_id))
+            .first::<DbMessage>(connection)
+            .optional()?;
+
+        Ok(Message {
+            key_id,
+            cursor,
+            message,
+        })
+    }
+
+    fn message(&self) -> FieldResult<&DbMessage> {
+        let message = self.message.as_ref().ok_or("Message not found")?;
+        Ok(message)
+    }
+}
+
+graphql_object!(Message: Context |&self| {
+
+    description: "Any ssb message. Use this for message types patchql doesn't have a more specific type for."
+
+    /// The globally unique identifier of this message, derived from the hash of this message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+
+    /// The author of this message.
+    field author(&executor) -> FieldResult<Author> {
+        let message = self.message()?;
+
+        Ok(Author{author_id: message.author_id})
+    }
+
+    /// What authors said about this message with about messages, eg. its title.
+    field abouts(&executor) -> FieldResult<MessageAbouts> {
+        let connection = executor.context().connection.get()?;
+        let message = self.message()?;
+
+        let mut by_others = get_message_abouts(&connection, self.key_id)?;
+        let by_author = match by_others.first() {
+            Some(about) if about.author_id == message.author_id => Some(by_others.remove(0)),
+            _ => None,
+        };
+
+        Ok(MessageAbouts{by_author, by_others})
+    }
+
+    /// The position of this message in its author's feed.
+    field sequence(&executor) -> FieldResult<i32> {
+        let message = self.message()?;
+
+        Ok(message.seq)
+    }
+
+    /// The asserted timestamp of the message. The time the author claims that they published the
+    /// message.
+    field asserted_timestamp(&executor) -> FieldResult<Option<f64>> {
+        let message = self.message()?;
+
+        Ok(message.asserted_time.map(|n| n as f64))
+    }
+
+    /// The received timestamp of the message. The time the message was inserted into your db on
+    /// this machine.
+    field received_timestamp(&executor) -> FieldResult<f64> {
+        let message = self.message()?;
+
+        Ok(message.received_time as f64)
+    }
+
+    /// The type of the message content, eg. `post` or `git-repo`. Null for private messages that
+    /// could not be decrypted.
+    field type(&executor) -> FieldResult<Option<String>> {
+        let message = self.message()?;
+
+        Ok(message.content_type.clone())
+    }
+
+    /// The id of the root message, if the content has a `root` field.
+    field root_key(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let root_key = messages_table
+            .inner_join(keys_table.on(
+                    messages_root_key_id.eq(keys_id)
+                    ))
+            .select(keys_key)
+            .filter(messages_key_id.eq(self.key_id))
+            .first::<String>(&connection)
+            .optional()?;
+
+        Ok(root_key)
+    }
+
+    /// The id of the message this one forks from, if the content has a `fork` field.
+    field fork_key(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let fork_key = messages_table
+            .inner_join(keys_table.on(
+                    messages_fork_key_id.eq(keys_id)
+                    ))
+            .select(keys_key)
+            .filter(messages_key_id.eq(self.key_id))
+            .first::<String>(&connection)
+            .optional()?;
+
+        Ok(fork_key)
+    }
+
+    /// Whether this message was private and decrypted by us.
+    field is_private(&executor) -> FieldResult<bool> {
+        let message = self.message()?;
+
+        Ok(message.is_decrypted)
+    }
+
+    /// The content of the message, as json.
+    field content(&executor) -> FieldResult<Option<Json>> {
+        let message = self.message()?;
+
+        let content = match &message.content {
+            Some(content) => Some(Json(serde_json::from_str(content)?)),
+            None => None,
+        };
+
+        Ok(content)
+    }
+});
@@ -0,0 +1,61 @@
+use super::author::Author;
+use crate::db::models::abouts::MessageAbout as DbMessageAbout;
+use crate::db::Context;
+
+pub struct MessageAbout {
+    pub about: DbMessageAbout,
+}
+
+graphql_object!(MessageAbout: Context |&self| {
+    description: "The latest title, description and image one author gave a message with about messages."
+
+    /// The author of the about messages.
+    field author() -> Author {
+        Author{author_id: self.about.author_id}
+    }
+    /// The title, if they gave one.
+    field title() -> Option<&str> {
+        self.about.title.as_deref()
+    }
+    /// The description, if they gave one.
+    field description() -> Option<&str> {
+        self.about.description.as_deref()
+    }
+    /// The blob id of the image, if they gave one.
+    field image_link() -> Option<&str> {
+        self.about.image.as_deref()
+    }
+});
+
+pub struct MessageAbouts {
+    /// What the message's author said, if anything.
+    pub by_author: Option<DbMessageAbout>,
+    pub by_others: Vec<DbMessageAbout>,
+}
+
+graphql_object!(MessageAbouts: Context |&self| {
+    description: "What authors said about a message with about messages. Only the message's author decides its title, description and image, but what everyone else said is available too."
+
+    /// The title the message's author gave it.
+    field title() -> Option<&str> {
+        self.by_author.as_ref().and_then(|about| about.title.as_deref())
+    }
+    /// The description the message's author gave it.
+    field description() -> Option<&str> {
+        self.by_author
+            .as_ref()
+            .and_then(|about| about.description.as_deref())
+    }
+    /// The blob id of the image the message's author gave it.
+    field image_link() -> Option<&str> {
+        self.by_author.as_ref().and_then(|about| about.image.as_deref())
+    }
+    /// What other authors said about the message, most recent first.
+    field by_others() -> Vec<MessageAbout> {
+        self.by_others
+            .iter()
+            .cloned()
+            .map(|about| MessageAbout{about})
+            .collect()
+    }
+});
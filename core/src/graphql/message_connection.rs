@@ -0,0 +1,81 @@
+use super::message::Message;
+use super::page_info::PageInfo;
+use crate::db::models::messages::Message as DbMessage;
+use crate::db::schema::messages::dsl::{key_id as messages_key_id, messages as messages_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct MessageConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub message_keys_and_cursor: Vec<(i32, String)>,
+}
+
+graphql_object!(MessageConnection: Context |&self| {
+    description: "Connection to collections of messages"
+
+    /// The total count of messages that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> FieldResult<Vec<MessageEdge>>{
+        let connection = executor.context().connection.get()?;
+
+        // Load all the rows in the page at once.
+        let key_ids = self.message_keys_and_cursor
+            .iter()
+            .map(|(key_id, _)| *key_id)
+            .collect::<Vec<i32>>();
+
+        let mut messages = messages_table
+            .filter(messages_key_id.eq_any(key_ids))
+            .load::<DbMessage>(&connection)?
+            .into_iter()
+            .map(|message| (message.key_id, message))
+            .collect::<HashMap<i32, DbMessage>>();
+
+        let edges = self.message_keys_and_cursor
+            .iter()
+            .map(|(key_id, cursor)|{
+                Message{key_id: *key_id, cursor: Some(cursor.to_owned()), message: messages.remove(key_id)}
+            })
+            .map(|message|{
+                MessageEdge{
+                    node: message
+                }
+            })
+            .collect::<Vec<MessageEdge>>();
+
+        Ok(edges)
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+#[derive(Default)]
+pub struct MessageEdge {
+    pub node: Message,
+}
+
+graphql_object!(MessageEdge: Context |&self| {
+    description: "Edge connection to a message"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Message {
+        &self.node
+    }
+
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
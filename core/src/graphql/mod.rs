@@ -1,13 +1,39 @@
 pub mod author;
+pub mod blob;
+pub mod blog;
+pub mod blog_connection;
+pub mod channel;
+pub mod channel_connection;
 pub mod db;
+pub mod gathering;
+pub mod gathering_connection;
+pub mod git_repo;
+pub mod git_repo_connection;
 pub mod input_objects;
+pub mod issue;
+pub mod json;
 pub mod like;
+pub mod link;
+pub mod link_connection;
 pub mod mention;
 pub mod mention_connection;
+pub mod message;
+pub mod message_about;
+pub mod message_connection;
 pub mod notification;
+pub mod notification_item;
 pub mod page_info;
+pub mod poll;
+pub mod poll_connection;
 pub mod post;
 pub mod post_connection;
+pub mod private_conversation;
+pub mod private_conversation_connection;
+pub mod reaction;
+pub mod reply_tree;
+pub mod revision;
 pub mod root;
+pub mod tag;
+pub mod tag_connection;
 pub mod thread;
 pub mod thread_connection;
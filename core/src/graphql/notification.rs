@@ -1,7 +1,11 @@
 use super::mention_connection::MentionConnection;
+use super::notification_item::NotificationEdge;
+use super::page_info::PageInfo;
+use crate::cursor::encode_cursor;
+use crate::db::models::notifications::{NotificationKind, Notifications};
 use crate::db::schema::mentions::dsl::{
     link_from_key_id as mentions_link_from_key_id, link_to_author_id as mentions_link_to_author_id,
-    mentions as mentions_table,
+    mentions as mentions_table, rel as mentions_rel,
 };
 use crate::db::schema::messages::dsl::{
     flume_seq as messages_flume_seq, key_id as messages_key_id, messages as messages_table,
@@ -15,9 +19,22 @@ use juniper::FieldResult;
 pub struct Notification {
     pub after_cursor: i64,
     pub author_id: i32,
+    /// The first page of notifications after the cursor, and how many there are.
+    pub notifications: Notifications,
+}
+
+impl Notification {
+    fn count(&self, kind: NotificationKind) -> i32 {
+        self.notifications.counts.get(&kind).cloned().unwrap_or(0) as i32
+    }
+
+    fn total_count(&self) -> i32 {
+        self.notifications.counts.values().sum::<i64>() as i32
+    }
 }
 
 graphql_object!(Notification: Context |&self| {
+    description: "What happened to the current author since a cursor: mentions, replies in threads they posted in, likes on their posts, new followers and new blocks."
 
     field mentions_connection(&executor) -> FieldResult<MentionConnection> {
         let connection = executor.context().connection.get()?;
@@ -26,9 +43,63 @@ graphql_object!(Notification: Context |&self| {
             .inner_join(messages_table.on(mentions_link_from_key_id.eq(messages_key_id)))
             .select(count_star())
             .filter(mentions_link_to_author_id.eq(self.author_id))
+            .filter(mentions_rel.eq("mentions"))
             .filter(messages_flume_seq.gt(self.after_cursor))
             .first::<i64>(&connection)?;
 
         Ok(MentionConnection{count: count as i32})
     }
+
+    /// The number of notifications after the cursor, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count()
+    }
+    /// The number of posts that mention the author.
+    field mention_count(&executor) -> i32 {
+        self.count(NotificationKind::Mention)
+    }
+    /// The number of replies in threads the author posted in. Replies that mention the author
+    /// are counted as mentions instead.
+    field reply_count(&executor) -> i32 {
+        self.count(NotificationKind::Reply)
+    }
+    /// The number of likes on the author's posts.
+    field like_count(&executor) -> i32 {
+        self.count(NotificationKind::Like)
+    }
+    /// The number of authors that started following the author.
+    field follower_count(&executor) -> i32 {
+        self.count(NotificationKind::Follow)
+    }
+    /// The number of authors that started blocking the author.
+    field block_count(&executor) -> i32 {
+        self.count(NotificationKind::Block)
+    }
+
+    /// The edges in this connection, oldest first.
+    field edges(&executor) -> Vec<NotificationEdge> {
+        self.notifications
+            .notifications
+            .iter()
+            .map(|notification| {
+                NotificationEdge{
+                    notification: notification.clone(),
+                    cursor: encode_cursor(notification.flume_seq),
+                }
+            })
+            .collect()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> PageInfo {
+        let page = &self.notifications.notifications;
+
+        PageInfo{
+            has_next_page: self.total_count() > page.len() as i32,
+            // The relay spec allows false when paginating forwards.
+            has_previous_page: false,
+            start_cursor: page.first().map(|notification| encode_cursor(notification.flume_seq)),
+            end_cursor: page.last().map(|notification| encode_cursor(notification.flume_seq)),
+        }
+    }
 });
@@ -0,0 +1,130 @@
+use super::author::Author;
+use super::mention::Mention;
+use super::post::Post;
+use crate::db::models::notifications::{Notification as DbNotification, NotificationKind};
+use crate::db::Context;
+
+pub struct Reply {
+    pub notification: DbNotification,
+}
+
+graphql_object!(Reply: Context |&self| {
+    description: "A reply in a thread the current author has posted in."
+
+    /// The author of the reply.
+    field author(&executor) -> Author {
+        Author{author_id: self.notification.author_id}
+    }
+
+    /// The reply post.
+    field post(&executor) -> Post {
+        Post{key_id: self.notification.key_id, cursor: None}
+    }
+
+    /// The root post of the thread.
+    field root(&executor) -> Option<Post> {
+        self.notification.target_key_id.map(|key_id| Post{key_id, cursor: None})
+    }
+});
+
+pub struct PostLike {
+    pub notification: DbNotification,
+}
+
+graphql_object!(PostLike: Context |&self| {
+    description: "A like on one of the current author's posts."
+
+    /// The author who liked the post.
+    field author(&executor) -> Author {
+        Author{author_id: self.notification.author_id}
+    }
+
+    /// The liked post.
+    field post(&executor) -> Option<Post> {
+        self.notification.target_key_id.map(|key_id| Post{key_id, cursor: None})
+    }
+});
+
+pub struct NewFollower {
+    pub author_id: i32,
+}
+
+graphql_object!(NewFollower: Context |&self| {
+    description: "Another author following the current author."
+
+    /// The author who followed.
+    field author(&executor) -> Author {
+        Author{author_id: self.author_id}
+    }
+});
+
+pub struct NewBlock {
+    pub author_id: i32,
+}
+
+graphql_object!(NewBlock: Context |&self| {
+    description: "Another author blocking the current author."
+
+    /// The author who blocked.
+    field author(&executor) -> Author {
+        Author{author_id: self.author_id}
+    }
+});
+
+pub enum NotificationItem {
+    Mention(Mention),
+    Reply(Reply),
+    PostLike(PostLike),
+    NewFollower(NewFollower),
+    NewBlock(NewBlock),
+}
+
+impl From<DbNotification> for NotificationItem {
+    fn from(notification: DbNotification) -> NotificationItem {
+        match notification.kind {
+            NotificationKind::Mention => NotificationItem::Mention(Mention {
+                key_id: notification.key_id,
+                author_id: notification.author_id,
+            }),
+            NotificationKind::Reply => NotificationItem::Reply(Reply { notification }),
+            NotificationKind::Like => NotificationItem::PostLike(PostLike { notification }),
+            NotificationKind::Follow => NotificationItem::NewFollower(NewFollower {
+                author_id: notification.author_id,
+            }),
+            NotificationKind::Block => NotificationItem::NewBlock(NewBlock {
+                author_id: notification.author_id,
+            }),
+        }
+    }
+}
+
+graphql_union!(NotificationItem: Context |&self| {
+    description: "Something that happened to the current author."
+
+    instance_resolvers: |_| {
+        &Mention => match *self { NotificationItem::Mention(ref item) => Some(item), _ => None },
+        &Reply => match *self { NotificationItem::Reply(ref item) => Some(item), _ => None },
+        &PostLike => match *self { NotificationItem::PostLike(ref item) => Some(item), _ => None },
+        &NewFollower => match *self { NotificationItem::NewFollower(ref item) => Some(item), _ => None },
+        &NewBlock => match *self { NotificationItem::NewBlock(ref item) => Some(item), _ => None },
+    }
+});
+
+pub struct NotificationEdge {
+    pub notification: DbNotification,
+    pub cursor: String,
+}
+
+graphql_object!(NotificationEdge: Context |&self| {
+    description: "Edge connection to a notification"
+
+    /// The nodes in this connection
+    field node(&executor) -> NotificationItem {
+        NotificationItem::from(self.notification.clone())
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> &str {
+        &self.cursor
+    }
+});
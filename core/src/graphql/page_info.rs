@@ -1,3 +1,13 @@
+use super::input_objects::OrderBy;
+use crate::cursor::*;
+use diesel::dsl::not;
+use diesel::prelude::*;
+use diesel::query_builder::{BoxedSelectStatement, QueryFragment};
+use diesel::query_source::QuerySource;
+use diesel::sql_types::{BigInt, Integer, Nullable};
+use diesel::sqlite::Sqlite;
+use juniper::FieldResult;
+
 /// A relay-spec PageInfo object used for pagination of queries.
 #[derive(GraphQLObject, Default)]
 pub struct PageInfo {
@@ -10,3 +20,185 @@ pub struct PageInfo {
     /// The cursor for the first item in the page.
     pub start_cursor: Option<String>,
 }
+
+/// Which page of results to get, from the `before`, `after`, `last` and `first` arguments.
+pub enum Page<C> {
+    /// The newest results first, before the cursor if there is one.
+    Last(Option<C>, i64),
+    /// The oldest results first, after the cursor if there is one.
+    First(Option<C>, i64),
+}
+
+impl<C> Page<C> {
+    pub fn new(
+        before: &Option<String>,
+        after: &Option<String>,
+        last: Option<i32>,
+        first: Option<i32>,
+        next: i32,
+        decode: fn(&str) -> Result<C, String>,
+    ) -> FieldResult<Page<C>> {
+        let page = match (before, after, last, first) {
+            (Some(b), None, Some(l), None) => Page::Last(Some(decode(b)?), l as i64),
+            (None, Some(a), None, Some(f)) => Page::First(Some(decode(a)?), f as i64),
+            (None, None, Some(l), _) => Page::Last(None, l as i64),
+            (None, None, None, Some(f)) => Page::First(None, f as i64),
+            (None, None, None, None) => Page::Last(None, next as i64),
+            (Some(_), Some(_), _, _) => Err("Before and After can't be set at the same time.")?,
+            _ => Err("Incorrect combination or before, after, first and last")?,
+        };
+
+        Ok(page)
+    }
+
+    pub fn limit(&self) -> i64 {
+        match self {
+            Page::Last(_, limit) | Page::First(_, limit) => *limit,
+        }
+    }
+
+    /// `has_more` is whether there are results past the end of this page, `has_other` is whether
+    /// there are results on the other side of the cursor.
+    pub fn page_info(
+        &self,
+        has_more: bool,
+        has_other: bool,
+        start_cursor: Option<String>,
+        end_cursor: Option<String>,
+    ) -> PageInfo {
+        let (has_next_page, has_previous_page) = match self {
+            Page::Last(_, _) => (has_other, has_more),
+            Page::First(_, _) => (has_more, has_other),
+        };
+
+        PageInfo {
+            has_next_page,
+            has_previous_page,
+            start_cursor,
+            end_cursor,
+        }
+    }
+}
+
+/// The results in a page with their cursors, the page info and the total count across all pages.
+pub type LoadedPage<T> = (Vec<(T, String)>, PageInfo, i64);
+
+/// Loads a page of a connection. `load` gets up to the given number of results past the page's
+/// cursor, in the page's order, `has_other` is whether there are results on the other side of the
+/// cursor, and `count` counts the results across all pages.
+pub fn load_page<C, T>(
+    page: &Page<C>,
+    load: impl FnOnce(i64) -> FieldResult<Vec<T>>,
+    has_other: impl FnOnce() -> FieldResult<bool>,
+    count: impl FnOnce() -> FieldResult<i64>,
+    encode: impl Fn(&T) -> String,
+) -> FieldResult<LoadedPage<T>> {
+    // Get one more result than asked for, to tell if there are more.
+    let mut results = load(page.limit() + 1)?;
+    let has_more = results.len() as i64 > page.limit();
+    results.truncate(page.limit() as usize);
+
+    let results_and_cursor = results
+        .into_iter()
+        .map(|result| {
+            let cursor = encode(&result);
+            (result, cursor)
+        })
+        .collect::<Vec<_>>();
+
+    let start_cursor = results_and_cursor.first().map(|(_, cursor)| cursor.clone());
+    let end_cursor = results_and_cursor.last().map(|(_, cursor)| cursor.clone());
+    let page_info = page.page_info(has_more, has_other()?, start_cursor, end_cursor);
+
+    Ok((results_and_cursor, page_info, count()?))
+}
+
+/// The sql type of the `(key_id, flume_seq, value)` rows that `load_ordered_page` pages through.
+pub type OrderedRow = (Integer, Nullable<BigInt>, Nullable<BigInt>);
+
+/// Loads a page of a connection that's ordered in the db by `value`, then by `seq` to break ties.
+/// `filtered_query` selects the `(key_id, flume_seq, value)` of all the results before any
+/// pagination, and `count` counts them.
+/// The results in the page are key ids.
+pub fn load_ordered_page<'a, QS>(
+    connection: &SqliteConnection,
+    page: &Page<OrderPosition>,
+    order_by: &OrderBy,
+    filtered_query: impl Fn() -> FieldResult<BoxedSelectStatement<'a, OrderedRow, QS, Sqlite>>,
+    value: impl Fn() -> OrderExpression<'a, QS>,
+    seq: impl Fn() -> OrderExpression<'a, QS>,
+    count: impl FnOnce() -> FieldResult<i64>,
+) -> FieldResult<LoadedPage<i32>>
+where
+    QS: QuerySource + 'a,
+    QS::FromClause: QueryFragment<Sqlite>,
+{
+    type Row = (i32, Option<i64>, Option<i64>);
+
+    let load = |limit| -> FieldResult<Vec<Row>> {
+        let query = match page {
+            Page::Last(cursor, _) => {
+                let query = filtered_query()?.order(cursor_order(&value, &seq, true));
+                match cursor {
+                    Some(position) => query.filter(before_cursor(&value, &seq, *position)),
+                    None => query,
+                }
+            }
+            Page::First(cursor, _) => {
+                let query = filtered_query()?.order(cursor_order(&value, &seq, false));
+                match cursor {
+                    Some(position) => query.filter(after_cursor(&value, &seq, *position)),
+                    None => query,
+                }
+            }
+        };
+
+        // Joins can repeat a result.
+        Ok(query.distinct().limit(limit).load::<Row>(connection)?)
+    };
+
+    let has_other = || -> FieldResult<bool> {
+        let other = match page {
+            Page::Last(Some(position), _) => {
+                filtered_query()?.filter(not(before_cursor(&value, &seq, *position)))
+            }
+            Page::First(Some(position), _) => {
+                filtered_query()?.filter(not(after_cursor(&value, &seq, *position)))
+            }
+            _ => return Ok(false),
+        };
+
+        Ok(!other.limit(1).load::<Row>(connection)?.is_empty())
+    };
+
+    let (results, page_info, total_count) =
+        load_page(page, load, has_other, count, |(_, row_seq, row_value)| {
+            encode_order_cursor(row_seq.unwrap_or(0), row_value.unwrap_or(0), order_by)
+        })?;
+
+    let keys_and_cursor = results
+        .into_iter()
+        .map(|((key_id, _, _), cursor)| (key_id, cursor))
+        .collect();
+
+    Ok((keys_and_cursor, page_info, total_count))
+}
+
+/// Where a result sits when ordered by time: the value that's ordered by (the asserted timestamp,
+/// the flume seq or the last activity), then the flume seq to break ties between results with
+/// the same value.
+/// Older cursors only encoded the first value, so the tiebreak is optional.
+pub type OrderPosition = (i64, Option<i64>);
+
+pub fn encode_order_cursor(seq: i64, value: i64, order_by: &OrderBy) -> String {
+    match order_by {
+        OrderBy::Asserted | OrderBy::LastActivity => encode_compound_cursor(&[value, seq]),
+        // Flume seqs are unique, there are no ties to break.
+        _ => encode_cursor(seq),
+    }
+}
+
+pub fn decode_order_cursor(cursor: &str) -> Result<OrderPosition, String> {
+    let parts = decode_cursor_parts(cursor)?;
+    Ok((parts[0], parts.get(1).cloned()))
+}
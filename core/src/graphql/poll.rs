@@ -0,0 +1,225 @@
+use super::author::Author;
+use crate::db::models::authors::get_my_author_id;
+use crate::db::models::polls::{
+    get_positions, tally, Poll as DbPoll, PollType as DbPollType, Position as DbPosition,
+};
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(GraphQLEnum)]
+/// How voters take a position on a poll.
+pub enum PollType {
+    /// Voters spread a number of dots over the options.
+    Dot,
+    /// Voters take one stance (agree, disagree, abstain or block) on a proposal.
+    Proposal,
+    /// Voters score each option, up to a maximum score.
+    Score,
+}
+
+pub struct Poll {
+    pub poll: DbPoll,
+    pub cursor: Option<String>,
+}
+
+impl Poll {
+    fn text(&self, field: &str) -> Option<String> {
+        self.poll.content[field]
+            .as_str()
+            .map(|text| text.to_owned())
+    }
+
+    fn to_positions(&self, positions: Vec<DbPosition>) -> Vec<Position> {
+        let options = self.poll.options();
+
+        positions
+            .into_iter()
+            .map(|position| Position {
+                choices: position
+                    .choices
+                    .iter()
+                    .map(|(index, value)| PositionChoice {
+                        option: options[*index].clone(),
+                        value: *value,
+                    })
+                    .collect(),
+                position,
+            })
+            .collect()
+    }
+}
+
+graphql_object!(Poll: Context |&self| {
+    description: "A poll. Each voter's latest position counts, positions taken after the poll closes are rejected."
+
+    /// The id of the poll message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.poll.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The author of the poll.
+    field author() -> Author {
+        Author{author_id: self.poll.author_id}
+    }
+    /// The title of the poll.
+    field title() -> Option<String> {
+        self.text("title")
+    }
+    /// The body of the poll, in markdown.
+    field body() -> Option<String> {
+        self.text("body")
+    }
+    /// The type of the poll, if it's one that we know how to count.
+    field poll_type() -> Option<PollType> {
+        self.poll.poll_type().map(|poll_type| match poll_type {
+            DbPollType::Dot => PollType::Dot,
+            DbPollType::Proposal => PollType::Proposal,
+            DbPollType::Score => PollType::Score,
+        })
+    }
+    /// The asserted timestamp of the poll. Like all asserted times, it can't be totally trusted.
+    field asserted_timestamp() -> f64 {
+        self.poll.asserted_timestamp as f64
+    }
+    /// When the poll closes, in milliseconds since the unix epoch.
+    field closes_at() -> Option<f64> {
+        self.poll.closes_at.map(|time| time as f64)
+    }
+    /// Whether the poll has closed.
+    field is_closed() -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        self.poll.is_closed(now)
+    }
+    /// What voters choose between. The options of a proposal are its stances.
+    field options() -> Vec<String> {
+        self.poll.options()
+    }
+    /// The totals for each option, in the order of the options.
+    field results(&executor) -> FieldResult<Vec<PollResult>> {
+        let connection = executor.context().connection.get()?;
+        let positions = get_positions(&connection, &self.poll)?;
+
+        let results = tally(&self.poll, &positions)
+            .into_iter()
+            .map(|result| PollResult{
+                option: result.option,
+                total: result.total,
+                voter_ids: result.voter_ids,
+            })
+            .collect();
+
+        Ok(results)
+    }
+    /// Each voter's latest position, in the order they were taken.
+    field positions(&executor) -> FieldResult<Vec<Position>> {
+        let connection = executor.context().connection.get()?;
+        let positions = get_positions(&connection, &self.poll)?;
+
+        Ok(self.to_positions(positions))
+    }
+    /// The current author's latest position, if they've taken one.
+    field my_position(&executor) -> FieldResult<Option<Position>> {
+        let connection = executor.context().connection.get()?;
+        let my_author_id = get_my_author_id(&connection)?;
+
+        let positions = get_positions(&connection, &self.poll)?
+            .into_iter()
+            .filter(|position| Some(position.author_id) == my_author_id)
+            .collect();
+
+        Ok(self.to_positions(positions).pop())
+    }
+});
+
+pub struct PollResult {
+    pub option: String,
+    pub total: i32,
+    pub voter_ids: Vec<i32>,
+}
+
+graphql_object!(PollResult: Context |&self| {
+    description: "The total for one of a poll's options."
+
+    /// The option.
+    field option() -> &str {
+        &self.option
+    }
+    /// The number of dots, the sum of the scores or the number of stances for the option.
+    field total() -> i32 {
+        self.total
+    }
+    /// The authors that chose the option.
+    field voters() -> Vec<Author> {
+        self.voter_ids
+            .iter()
+            .map(|author_id| Author{author_id: *author_id})
+            .collect()
+    }
+});
+
+pub struct Position {
+    pub position: DbPosition,
+    pub choices: Vec<PositionChoice>,
+}
+
+graphql_object!(Position: Context |&self| {
+    description: "A voter's position on a poll."
+
+    /// The id of the position message.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.position.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The voter.
+    field author() -> Author {
+        Author{author_id: self.position.author_id}
+    }
+    /// The options the voter chose.
+    field choices() -> &Vec<PositionChoice> {
+        &self.choices
+    }
+    /// Why the voter took this position, if they said.
+    field reason() -> Option<&str> {
+        self.position.reason.as_deref()
+    }
+    /// The asserted timestamp of the position. Like all asserted times, it can't be totally
+    /// trusted.
+    field asserted_timestamp() -> f64 {
+        self.position.asserted_timestamp as f64
+    }
+});
+
+pub struct PositionChoice {
+    pub option: String,
+    pub value: i32,
+}
+
+graphql_object!(PositionChoice: Context |&self| {
+    description: "An option a voter chose, with how many dots or what score they gave it."
+
+    /// The option.
+    field option() -> &str {
+        &self.option
+    }
+    /// The number of dots or the score. Always 1 for the stance on a proposal.
+    field value() -> i32 {
+        self.value
+    }
+});
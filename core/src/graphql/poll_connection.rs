@@ -0,0 +1,59 @@
+use super::page_info::PageInfo;
+use super::poll::Poll;
+use crate::db::models::polls::Poll as DbPoll;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct PollConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub polls_and_cursor: Vec<(DbPoll, String)>,
+}
+
+graphql_object!(PollConnection: Context |&self| {
+    description: "Connection to collections of polls"
+
+    /// The total count of polls that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<PollEdge>{
+        self.polls_and_cursor
+            .iter()
+            .map(|(poll, cursor)|{
+                Poll{poll: poll.clone(), cursor: Some(cursor.to_owned())}
+            })
+            .map(|poll|{
+                PollEdge{
+                    node: poll
+                }
+            })
+            .collect::<Vec<PollEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct PollEdge {
+    pub node: Poll,
+}
+
+graphql_object!(PollEdge: Context |&self| {
+    description: "Edge connection to a poll"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Poll {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
@@ -1,15 +1,19 @@
 use super::author::*;
 use super::like::*;
+use super::reaction::Reaction;
+use super::revision::Revision;
+use super::tag::Tag;
 use crate::db::*;
 use diesel::prelude::*;
 use juniper::FieldResult;
 
+use crate::db::local_state::is_read;
 use crate::db::models::keys::*;
 use crate::db::models::votes::*;
 use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
 use crate::db::schema::links::dsl::{
     link_from_key_id as links_link_from_key_id, link_to_key_id as links_link_to_key_col,
-    links as links_table,
+    links as links_table, rel as links_rel,
 };
 use crate::db::schema::messages::dsl::{
     asserted_time as messages_asserted_time, author_id as messages_author_id,
@@ -18,11 +22,19 @@ use crate::db::schema::messages::dsl::{
     root_key_id, root_key_id as messages_root_key_id,
 };
 use crate::db::schema::votes::dsl::{
-    link_from_author_id as votes_link_from_author_id, link_to_key_id as votes_link_to_key_col,
-    value as votes_value, votes as votes_table,
+    id as votes_id, link_from_author_id as votes_link_from_author_id,
+    link_to_key_id as votes_link_to_key_col, value as votes_value, votes as votes_table,
 };
 
+use crate::db::models::abouts::get_key_detail;
+use crate::db::models::authors::get_my_author_id;
+use crate::db::models::branches::{get_branch_children, get_branch_parents};
+use crate::db::models::channels::get_channels;
+use crate::db::models::content_warnings::get_content_warning;
+use crate::db::models::post_edits::{get_latest_revision, get_revisions};
 use crate::db::models::posts::get_text;
+use crate::db::models::recipients::get_recipients;
+use crate::db::models::tags::get_message_tags;
 use crate::db::schema::authors::dsl::{
     authors as authors_table, id as authors_id, is_me as authors_is_me,
 };
@@ -72,7 +84,10 @@ graphql_object!(Post: Context |&self| {
             .map(|vote|{
                 Like{
                     author_id: vote.link_from_author_id,
-                    value: vote.value
+                    value: vote.value,
+                    key_id: vote.link_from_key_id,
+                    asserted_timestamp: vote.asserted_timestamp,
+                    expression: vote.expression.clone(),
                 }
             })
             .collect();
@@ -80,6 +95,43 @@ graphql_object!(Post: Context |&self| {
         Ok(result)
     }
 
+    /// The likes on this post grouped by their expression, most popular first. Likes without an
+    /// expression count as "Like".
+    field reactions(&executor) -> FieldResult<Vec<Reaction>> {
+        let connection = executor.context().connection.get()?;
+
+        let my_author_id = get_my_author_id(&connection)?;
+
+        let mut reactions: Vec<Reaction> = Vec::new();
+        votes_table
+            .filter(votes_link_to_key_col.eq(self.key_id))
+            .filter(votes_value.eq(1))
+            .order(votes_id.asc())
+            .load::<Vote>(&connection)?
+            .into_iter()
+            .for_each(|vote| {
+                let expression = vote.expression.unwrap_or_else(|| "Like".to_owned());
+                let reacted_by_me = my_author_id == Some(vote.link_from_author_id);
+
+                match reactions.iter_mut().find(|reaction| reaction.expression == expression) {
+                    Some(reaction) => {
+                        reaction.author_ids.push(vote.link_from_author_id);
+                        reaction.reacted_by_me |= reacted_by_me;
+                    }
+                    None => reactions.push(Reaction{
+                        expression,
+                        author_ids: vec![vote.link_from_author_id],
+                        reacted_by_me,
+                    }),
+                }
+            });
+
+        // Stable, so ties stay in the order they were first used.
+        reactions.sort_by_key(|reaction| -(reaction.author_ids.len() as i64));
+
+        Ok(reactions)
+    }
+
     /// Whether this post is liked by me. 
     field liked_by_me(&executor ) -> FieldResult<bool> {
         let connection = executor.context().connection.get()?;
@@ -109,6 +161,11 @@ graphql_object!(Post: Context |&self| {
 
         Ok(count as i32)
     }
+    /// Whether the post has been marked as read with the `markRead` mutation.
+    field is_read(&executor) -> FieldResult<bool> {
+        let connection = executor.context().connection.get()?;
+        Ok(is_read(&connection, self.key_id)?)
+    }
 
     /// The asserted timestamp of the post.
     /// Asserted means that it's the time the author claims that they published the message.
@@ -143,12 +200,63 @@ graphql_object!(Post: Context |&self| {
         Ok(time as f64)
     }
 
-    /// The text body of the post.
+    /// The text body of the post. If the author has edited the post, this is the text of the
+    /// latest edit.
     field text(&executor) -> FieldResult<String> {
         let connection = executor.context().connection.get()?;
-        let text = get_text(&connection, self.key_id)?;
+        let key_id = get_latest_revision(&connection, self.key_id)?.unwrap_or(self.key_id);
+        let text = get_text(&connection, key_id)?;
         Ok(text)
     }
+    /// Every version of the post's text, starting with the original post and followed by the
+    /// author's edits, oldest first. Edits by other authors are ignored.
+    field revisions(&executor) -> FieldResult<Vec<Revision>> {
+        let connection = executor.context().connection.get()?;
+
+        let original = messages_table
+            .select(messages_asserted_time)
+            .filter(messages_key_id.eq(self.key_id))
+            .first::<Option<i64>>(&connection)?;
+
+        let revisions = std::iter::once((self.key_id, original.unwrap_or(0)))
+            .chain(get_revisions(&connection, self.key_id)?)
+            .map(|(key_id, asserted_timestamp)| Revision{key_id, asserted_timestamp})
+            .collect();
+
+        Ok(revisions)
+    }
+    /// The content warning the author gave the post, if any. Clients should hide the text
+    /// behind it until the reader chooses to see it.
+    field content_warning(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let content_warning = get_content_warning(&connection, self.key_id)?;
+        Ok(content_warning)
+    }
+    /// The title the post's author gave it with an about message, if any.
+    field title(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let title = get_key_detail(&connection, self.key_id, "title")?
+            .and_then(|title| title.as_str().map(|title| title.to_owned()));
+
+        Ok(title)
+    }
+    /// The tags applied to this post by their authors and not removed, in the order they were
+    /// applied.
+    field tags(&executor) -> FieldResult<Vec<Tag>> {
+        let connection = executor.context().connection.get()?;
+        let tags = get_message_tags(&connection, self.key_id)?
+            .into_iter()
+            .map(|key_id| Tag{key_id, cursor: None})
+            .collect();
+
+        Ok(tags)
+    }
+    /// The channels this post is in, from its `channel` field and any `#hashtags` in its text.
+    field channels(&executor) -> FieldResult<Vec<String>> {
+        let connection = executor.context().connection.get()?;
+        let channels = get_channels(&connection, self.key_id)?;
+        Ok(channels)
+    }
     /// If this post forks from another discussion, the forksFromKey is the id of the message
     /// that it forks from.
     field forks_from_key(&executor) -> FieldResult<Option<String>> {
@@ -178,19 +286,54 @@ graphql_object!(Post: Context |&self| {
 
         Ok(root_key)
     }
-    /// Any other messages outside this thread that link / reference this one.
+    /// The messages this post replies to, from its `branch` field. Branches that point at messages
+    /// we don't have are skipped.
+    field reply_to(&executor) -> FieldResult<Vec<Post>> {
+        let connection = executor.context().connection.get()?;
+
+        let posts = get_branch_parents(&connection, self.key_id)?
+            .into_iter()
+            .map(|key_id| Post{key_id, cursor: None})
+            .collect();
+
+        Ok(posts)
+    }
+    /// The posts that reply directly to this one, the ones that have it in their `branch` field.
+    field direct_replies(&executor) -> FieldResult<Vec<Post>> {
+        let connection = executor.context().connection.get()?;
+
+        let posts = get_branch_children(&connection, self.key_id)?
+            .into_iter()
+            .map(|key_id| Post{key_id, cursor: None})
+            .collect();
+
+        Ok(posts)
+    }
+    /// The authors this post was sent to, if it's private. Empty for public posts.
+    field recipients(&executor) -> FieldResult<Vec<Author>> {
+        let connection = executor.context().connection.get()?;
+
+        let authors = get_recipients(&connection, self.key_id)?
+            .into_iter()
+            .map(|author_id| Author{author_id})
+            .collect();
+
+        Ok(authors)
+    }
+    /// Any other posts outside this thread that mention this one.
     field references(&executor) -> FieldResult<Vec<Post>> {
         let connection = executor.context().connection.get()?;
 
+        // Join the message the link is from, since that's the one that has to be a post outside
+        // this thread. Likes, abouts, tags and edits link here too, but with other rels.
         let posts = links_table
             .inner_join(messages_table.on(
-                    messages_key_id.eq(links_link_to_key_col)
+                    messages_key_id.eq(links_link_from_key_id)
                     ))
             .select(links_link_from_key_id)
             .filter(links_link_to_key_col.eq(self.key_id))
-            .filter(messages_content_type.ne("about"))
-            .filter(messages_content_type.ne("tag"))
-            .filter(messages_content_type.ne("vote"))
+            .filter(links_rel.eq("mentions"))
+            .filter(messages_content_type.eq("post"))
             .filter(
                 root_key_id.is_not_null().and(root_key_id.ne(self.key_id))
                     .or(root_key_id.is_null())
@@ -200,6 +343,7 @@ graphql_object!(Post: Context |&self| {
                 fork_key_id.is_not_null().and(fork_key_id.ne(self.key_id))
                     .or(fork_key_id.is_null())
                 )
+            .distinct()
             .load::<i32>(&connection)?
             .iter()
             .map(|key_id|{
@@ -1,21 +1,86 @@
-use super::page_info::PageInfo;
+use super::page_info::{OrderPosition, Page, PageInfo};
 use super::post::Post;
+use crate::cursor::encode_cursor;
 use crate::db::Context;
 use juniper::FieldResult;
 
 #[derive(Default)]
 pub struct PostConnection {
     pub next: i32,
+    pub total_count: i32,
     pub page_info: PageInfo,
     pub post_keys_and_cursor: Vec<(i32, String)>,
 }
 
+impl PostConnection {
+    /// A page of posts that are already sorted in memory, from `(key_id, flume_seq)`s. Cursors are
+    /// the flume seqs, and `not_found` is the error when a cursor isn't one of them.
+    pub fn from_sorted(
+        sorted: &[(i32, i64)],
+        page: Page<OrderPosition>,
+        next: i32,
+        not_found: &'static str,
+    ) -> FieldResult<PostConnection> {
+        let index_of = |cursor: Option<OrderPosition>| -> FieldResult<Option<usize>> {
+            match cursor {
+                Some((seq, _)) => {
+                    let index = sorted
+                        .iter()
+                        .position(|(_, post_seq)| *post_seq == seq)
+                        .ok_or(not_found)?;
+                    Ok(Some(index))
+                }
+                None => Ok(None),
+            }
+        };
+
+        let (page_posts, has_more, has_other) = match page {
+            Page::Last(cursor, limit) => {
+                let index = index_of(cursor)?;
+                let end = index.unwrap_or(sorted.len());
+                let start = end.saturating_sub(limit as usize);
+                let page_posts = sorted[start..end].iter().rev().cloned().collect::<Vec<_>>();
+                (page_posts, start > 0, index.is_some())
+            }
+            Page::First(cursor, limit) => {
+                let index = index_of(cursor)?;
+                let start = index.map_or(0, |index| index + 1);
+                let end = (start + limit as usize).min(sorted.len());
+                (
+                    sorted[start..end].to_vec(),
+                    end < sorted.len(),
+                    index.is_some(),
+                )
+            }
+        };
+
+        let post_keys_and_cursor = page_posts
+            .iter()
+            .map(|(key_id, seq)| (*key_id, encode_cursor(*seq)))
+            .collect::<Vec<(i32, String)>>();
+
+        let start_cursor = post_keys_and_cursor
+            .first()
+            .map(|(_, cursor)| cursor.clone());
+        let end_cursor = post_keys_and_cursor
+            .last()
+            .map(|(_, cursor)| cursor.clone());
+
+        Ok(PostConnection {
+            next,
+            total_count: sorted.len() as i32,
+            page_info: page.page_info(has_more, has_other, start_cursor, end_cursor),
+            post_keys_and_cursor,
+        })
+    }
+}
+
 graphql_object!(PostConnection: Context |&self| {
     description: "Connection to collections of posts"
 
-    /// The total count of posts in this connection.
+    /// The total count of posts that match the query, across all pages.
     field total_count(&executor) -> i32 {
-        self.post_keys_and_cursor.len() as i32
+        self.total_count
     }
     /// The nodes in this connection
     field edges(&executor) -> Vec<PostEdge>{
@@ -0,0 +1,44 @@
+use super::author::Author;
+use super::input_objects::OrderBy;
+use super::page_info::encode_order_cursor;
+use super::post::Post;
+use super::thread::Thread;
+use crate::db::models::recipients::PrivateConversation as DbPrivateConversation;
+use crate::db::Context;
+
+pub struct PrivateConversation {
+    pub conversation: DbPrivateConversation,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(PrivateConversation: Context |&self| {
+    description: "The private threads between one set of recipients."
+
+    /// Everyone the threads were sent to, usually including the current author.
+    field recipients(&executor) -> Vec<Author> {
+        self.conversation
+            .recipient_ids
+            .iter()
+            .map(|author_id| Author{author_id: *author_id})
+            .collect()
+    }
+    /// The threads, most recently active first. The cursors can be used with `threads` ordered by
+    /// `LAST_ACTIVITY`.
+    field threads(&executor) -> Vec<Thread> {
+        self.conversation
+            .threads
+            .iter()
+            .map(|(key_id, flume_seq, last_activity_flume_seq)| {
+                Thread{
+                    root: Post{key_id: *key_id, cursor: None},
+                    cursor: encode_order_cursor(*flume_seq, *last_activity_flume_seq, &OrderBy::LastActivity),
+                    reply_order_by: None,
+                }
+            })
+            .collect()
+    }
+    /// The number of threads.
+    field thread_count(&executor) -> i32 {
+        self.conversation.threads.len() as i32
+    }
+});
@@ -0,0 +1,59 @@
+use super::page_info::PageInfo;
+use super::private_conversation::PrivateConversation;
+use crate::db::models::recipients::PrivateConversation as DbPrivateConversation;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct PrivateConversationConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub conversations_and_cursor: Vec<(DbPrivateConversation, String)>,
+}
+
+graphql_object!(PrivateConversationConnection: Context |&self| {
+    description: "Connection to collections of private conversations"
+
+    /// The total count of private conversations, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<PrivateConversationEdge>{
+        self.conversations_and_cursor
+            .iter()
+            .map(|(conversation, cursor)|{
+                PrivateConversation{conversation: conversation.clone(), cursor: Some(cursor.to_owned())}
+            })
+            .map(|conversation|{
+                PrivateConversationEdge{
+                    node: conversation
+                }
+            })
+            .collect::<Vec<PrivateConversationEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct PrivateConversationEdge {
+    pub node: PrivateConversation,
+}
+
+graphql_object!(PrivateConversationEdge: Context |&self| {
+    description: "Edge connection to a private conversation"
+
+    /// The nodes in this connection
+    field node(&executor) -> &PrivateConversation {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
@@ -0,0 +1,33 @@
+use super::author::Author;
+use crate::db::Context;
+
+pub struct Reaction {
+    pub expression: String,
+    /// The authors that reacted this way, in the order they reacted.
+    pub author_ids: Vec<i32>,
+    pub reacted_by_me: bool,
+}
+
+graphql_object!(Reaction: Context |&self| {
+    description: "The authors that reacted to a message with the same expression."
+
+    /// The expression, eg. "Like", "Dig" or an emoji.
+    field expression() -> &str {
+        &self.expression
+    }
+    /// The number of authors that reacted this way.
+    field count() -> i32 {
+        self.author_ids.len() as i32
+    }
+    /// The authors that reacted this way, in the order they reacted.
+    field authors() -> Vec<Author> {
+        self.author_ids
+            .iter()
+            .map(|author_id| Author{author_id: *author_id})
+            .collect()
+    }
+    /// Whether the current author reacted this way.
+    field reacted_by_me() -> bool {
+        self.reacted_by_me
+    }
+});
@@ -0,0 +1,34 @@
+use super::post::Post;
+use crate::db::models::branches::ReplyTree;
+use crate::db::Context;
+
+pub struct ReplyTreeNode {
+    pub tree: ReplyTree,
+    pub depth: i32,
+}
+
+graphql_object!(ReplyTreeNode: Context |&self| {
+    description: "A reply in a thread's reply tree, with the replies that branch from it."
+
+    /// The reply post.
+    field post(&executor) -> Post {
+        Post{key_id: self.tree.key_id, cursor: None}
+    }
+    /// How deeply nested the reply is. Replies at the top of the tree have a depth of 0.
+    field depth(&executor) -> i32 {
+        self.depth
+    }
+    /// The replies to this reply, in causal order.
+    field children(&executor) -> Vec<ReplyTreeNode> {
+        self.tree
+            .children
+            .iter()
+            .map(|child| {
+                ReplyTreeNode{
+                    tree: child.clone(),
+                    depth: self.depth + 1,
+                }
+            })
+            .collect()
+    }
+});
@@ -0,0 +1,36 @@
+use crate::db::models::posts::get_text;
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+
+pub struct Revision {
+    pub key_id: i32,
+    pub asserted_timestamp: i64,
+}
+
+graphql_object!(Revision: Context |&self| {
+    description: "A version of a post's text, either the original post or one of its edits."
+
+    /// The id of the message this version came from.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The text of this version.
+    field text(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let text = get_text(&connection, self.key_id)?;
+        Ok(text)
+    }
+    /// The asserted timestamp of this version. Like all asserted times, it can't be totally
+    /// trusted.
+    field asserted_timestamp(&executor) -> f64 {
+        self.asserted_timestamp as f64
+    }
+});
@@ -1,47 +1,104 @@
-use super::page_info::PageInfo;
+use super::page_info::*;
 use crate::cursor::*;
-use diesel::dsl::max;
+use diesel::dsl::not;
 use diesel::dsl::sql;
+use diesel::expression::dsl::max;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Nullable};
+use diesel::sql_types::{BigInt, Bool, Nullable};
 use juniper::FieldResult;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::author::*;
+use super::blog_connection::*;
+use super::channel_connection::*;
+use super::gathering_connection::*;
+use super::git_repo::*;
+use super::git_repo_connection::*;
 use super::input_objects::*;
+use super::link_connection::*;
+use super::message::*;
+use super::message_connection::*;
+use super::notification::*;
+use super::poll::*;
+use super::poll_connection::*;
 use super::post::*;
 use super::post_connection::*;
+use super::private_conversation_connection::*;
+use super::tag_connection::*;
 use super::thread::*;
 use super::thread_connection::*;
+use crate::db::local_state::unread_key_ids;
+use crate::db::models::author_texts::search_author_texts;
+use crate::db::models::authors::get_my_author_id;
+use crate::db::models::blogs::get_blogs;
+use crate::db::models::channels::{channels_query, normalize_channel, SUBSCRIBER_COUNT};
+use crate::db::models::gatherings::{get_gatherings, is_attending, START_TIME};
+use crate::db::models::git_repos::is_git_repo;
+use crate::db::models::links::{
+    count_links, get_links, Link as DbLink, LinkFilter, LinkKind, LinkPage, LinkPosition,
+};
+use crate::db::models::notifications::get_notifications;
+use crate::db::models::polls::{get_poll, get_polls};
+use crate::db::models::recipients::{
+    count_private_conversations, get_private_conversations, has_private_conversations_from,
+    PrivateConversation as DbPrivateConversation,
+};
+use crate::db::models::tags::{tagged_posts_query, tags_query};
+use crate::db::schema::blobs::dsl::{blob as blobs_blob, blobs as blobs_table, id as blobs_id};
+use crate::db::schema::blogs::dsl::{
+    asserted_timestamp as blogs_asserted_timestamp, author_id as blogs_author_id,
+    blogs as blogs_table, flume_seq as blogs_flume_seq, key_id as blogs_key_id,
+};
+use crate::db::schema::channel_links::dsl::{
+    channel_links as channel_links_table, link_from_key_id as channel_links_link_from_key_id,
+    link_to_channel_id as channel_links_link_to_channel_id,
+};
+use crate::db::schema::channels::dsl::{
+    channel as channels_channel, channels as channels_table, id as channels_id,
+};
 use crate::db::schema::contacts::dsl::{
     author_id as contacts_author_id, contact_author_id as contacts_contact_author_id,
     contacts as contacts_table, state as contacts_state,
 };
 
 use crate::db::schema::authors::dsl::{
-    author as authors_author, authors as authors_table, id as authors_id, is_me as authors_is_me,
+    author as authors_author, authors as authors_table, id as authors_id,
+};
+use crate::db::schema::content_warnings::dsl::{
+    content_warnings as content_warnings_table, key_id as content_warnings_key_id,
+};
+use crate::db::schema::git_repos::dsl::{
+    flume_seq as git_repos_flume_seq, git_repos as git_repos_table, key_id as git_repos_key_id,
 };
 use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
 use crate::db::schema::mentions::dsl::{
     link_from_key_id as mentions_link_from_key_id, link_to_author_id as mentions_link_to_author_id,
-    mentions as mentions_table,
+    mentions as mentions_table, rel as mentions_rel,
 };
 use crate::db::schema::messages::dsl::{
-    author_id as messages_author_id, content as messages_content,
+    asserted_time as messages_asserted_time, author_id as messages_author_id,
     content_type as messages_content_type, flume_seq as messages_flume_seq,
     is_decrypted as messages_is_decrypted, key_id as messages_key_id, messages as messages_table,
-    root_key_id as messages_root_key_id,
-    asserted_time as messages_asserted_time,
+    received_time as messages_received_time, root_key_id as messages_root_key_id,
+};
+use crate::db::schema::polls::dsl::{
+    flume_seq as polls_flume_seq, key_id as polls_key_id, polls as polls_table,
 };
 use crate::db::schema::reply_posts::dsl::{
-    author_id as reply_posts_author_id, reply_posts as reply_posts_table,
+    asserted_timestamp as reply_posts_asserted_timestamp, author_id as reply_posts_author_id,
+    key_id as reply_posts_key_id, reply_posts as reply_posts_table,
     root_post_id as reply_posts_root_post_id,
 };
 use crate::db::schema::root_posts::dsl::{
     asserted_timestamp as root_posts_asserted_timestamp, author_id as root_posts_author_id,
-    flume_seq as root_posts_flume_seq, key_id as root_posts_key_id, root_posts as root_posts_table,
+    flume_seq as root_posts_flume_seq, key_id as root_posts_key_id,
+    last_activity_flume_seq as root_posts_last_activity_flume_seq, root_posts as root_posts_table,
 };
 use crate::db::Context;
 
+use crate::db::schema::tag_operations::dsl::flume_seq as tag_operations_flume_seq;
+use crate::db::schema::tags::dsl::flume_seq as tags_flume_seq;
 use crate::db::schema::texts::dsl::{rowid as texts_key_id, texts as texts_table};
 
 pub struct Query;
@@ -72,10 +129,7 @@ graphql_object!(Query: Context |&self| {
 
         let connection = executor.context().connection.get()?;
 
-        let author = authors_table
-            .select(authors_id)
-            .filter(authors_is_me.eq(true))
-            .first::<Option<i32>>(&connection)?
+        let author = get_my_author_id(&connection)?
             .map(|author_key_id|{
                 Author{author_id: author_key_id}
             });
@@ -83,8 +137,560 @@ graphql_object!(Query: Context |&self| {
         Ok(author)
     }
 
+    /// What happened to the current author after the cursor: mentions, replies in threads they
+    /// posted in, likes on their posts, new followers and new blocks, oldest first.
+    ///
+    /// Pass the `endCursor` of the last page you showed as `after` to get what's new since then.
+    field notifications(
+        &executor,
+        /// Use a cursor string to get notifications after the cursor.
+        after: Option<String>,
+        /// Limit the number of notifications to get, up to 100.
+        first = 10: i32,
+    ) -> FieldResult<Notification> {
+        let connection = executor.context().connection.get()?;
+
+        let author_id = get_my_author_id(&connection)?.ok_or("There is no current author")?;
+
+        let after_cursor = match after {
+            Some(cursor) => decode_cursor_parts(&cursor)?[0],
+            None => 0,
+        };
+
+        // A negative limit would get everything.
+        let limit = i64::from(first.clamp(0, 100));
+        let notifications = get_notifications(&connection, author_id, after_cursor, limit)?;
+
+        Ok(Notification{after_cursor, author_id, notifications})
+    }
+
+    /// Private threads grouped by who they were sent to, most recently active first. Only threads
+    /// this db could decrypt are included.
+    field private_conversations(
+        &executor,
+        /// Use a cursor string to get conversations after the cursor.
+        after: Option<String>,
+        /// Limit the number of conversations to get.
+        first = 10: i32,
+    ) -> FieldResult<PrivateConversationConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&None, &after, None, Some(first), next, decode_order_cursor)?;
+
+        // Conversations are most recently active first, so the ones after the cursor are older.
+        let before = match page {
+            Page::First(cursor, _) => cursor,
+            Page::Last(_, _) => None,
+        };
+
+        let (conversations_and_cursor, page_info, total_count) = load_page(
+            &page,
+            |limit| Ok(get_private_conversations(&connection, before, limit)?),
+            || match before {
+                Some(position) => Ok(has_private_conversations_from(&connection, position)?),
+                None => Ok(false),
+            },
+            || Ok(count_private_conversations(&connection)?),
+            |conversation: &DbPrivateConversation| {
+                let (last_activity_seq, seq) = conversation.position();
+                encode_order_cursor(seq, last_activity_seq, &OrderBy::LastActivity)
+            },
+        )?;
+
+        Ok(PrivateConversationConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            conversations_and_cursor
+        })
+    }
+
+    /// Blogs in the order they were published (by asserted time), newest first by default.
+    field blogs(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Find blogs that are authored by the provided authors.
+        authors: Option<Vec<String>>,
+        /// Find public, private or all blogs.
+        privacy = (Privacy::Public): Privacy,
+    ) -> FieldResult<BlogConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let author_ids = match authors {
+            Some(authors) => {
+                let author_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(authors))
+                    .load::<Option<i32>>(&connection)?
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<i32>>();
+                Some(author_ids)
+            }
+            None => None,
+        };
+
+        // All the blogs, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let mut query = blogs_table
+                .inner_join(messages_table.on(messages_key_id.eq(blogs_key_id)))
+                .select((
+                    blogs_key_id,
+                    blogs_flume_seq.nullable(),
+                    blogs_asserted_timestamp.nullable(),
+                ))
+                .into_boxed();
+
+            if let Some(author_ids) = &author_ids {
+                query = query.filter(blogs_author_id.eq_any(author_ids.clone()));
+            }
+
+            query = match privacy {
+                Privacy::Private => query.filter(messages_is_decrypted.eq(true)),
+                Privacy::Public => query.filter(messages_is_decrypted.eq(false)),
+                Privacy::All => query,
+            };
+
+            Ok(query)
+        };
+
+        let (blog_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Asserted,
+            filtered_query,
+            || Box::new(blogs_asserted_timestamp.nullable()),
+            || Box::new(blogs_flume_seq.nullable()),
+            || {
+                Ok(filtered_query()?
+                    .select(sql::<BigInt>("COUNT(DISTINCT blogs.key_id)"))
+                    .first::<i64>(&connection)?)
+            },
+        )?;
+
+        let key_ids = blog_keys_and_cursor
+            .iter()
+            .map(|(key_id, _)| *key_id)
+            .collect::<Vec<_>>();
+        let mut blogs = get_blogs(&connection, &key_ids)?
+            .into_iter()
+            .map(|blog| (blog.key_id, blog))
+            .collect::<HashMap<_, _>>();
+
+        let blogs_and_cursor = blog_keys_and_cursor
+            .into_iter()
+            .filter_map(|(key_id, cursor)| blogs.remove(&key_id).map(|blog| (blog, cursor)))
+            .collect();
+
+        Ok(BlogConnection {
+            next,
+            total_count: total_count as i32,
+            page_info,
+            blogs_and_cursor,
+        })
+    }
+    /// Gatherings (events), in the order they start. Gatherings without a start time come last.
+    field gatherings(
+        &executor,
+        /// Use a cursor string to get gatherings after the cursor.
+        after: Option<String>,
+        /// Limit the number of gatherings to get.
+        first = 10: i32,
+        /// Only include gatherings that start in the future when true, or that have already
+        /// started when false. Gatherings without a start time are only included when not set.
+        upcoming: Option<bool>,
+        /// Only include gatherings the current author is attending when true, or not attending
+        /// when false.
+        attending: Option<bool>,
+        /// Find public, private or all gatherings.
+        privacy = (Privacy::Public): Privacy,
+    ) -> FieldResult<GatheringConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&None, &after, None, Some(first), next, decode_order_cursor)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        let my_author_id = match attending {
+            Some(_) => get_my_author_id(&connection)?,
+            None => None,
+        };
+
+        // Gatherings without a start time come last.
+        let start_time = || {
+            sql::<Nullable<BigInt>>(&format!("IFNULL({}, {})", START_TIME, i64::MAX))
+        };
+
+        // All the gatherings, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let mut query = messages_table
+                .select((messages_key_id, messages_flume_seq, start_time()))
+                .filter(messages_content_type.eq("gathering"))
+                .into_boxed();
+
+            query = match privacy {
+                Privacy::Private => query.filter(messages_is_decrypted.eq(true)),
+                Privacy::Public => query.filter(messages_is_decrypted.eq(false)),
+                Privacy::All => query,
+            };
+
+            query = match upcoming {
+                Some(true) => query.filter(sql::<Nullable<BigInt>>(START_TIME).ge(now)),
+                Some(false) => query.filter(sql::<Nullable<BigInt>>(START_TIME).lt(now)),
+                None => query,
+            };
+
+            query = match (attending, my_author_id) {
+                (Some(true), Some(author_id)) => query.filter(is_attending(author_id)),
+                (Some(false), Some(author_id)) => query.filter(not(is_attending(author_id))),
+                // Without a current author no one is attending anything.
+                (Some(true), None) => query.filter(sql::<Bool>("0")),
+                _ => query,
+            };
+
+            Ok(query)
+        };
+
+        // Ordering by asserted time also encodes `[start time, flume seq]` cursors.
+        let (gathering_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Asserted,
+            filtered_query,
+            || Box::new(start_time()),
+            || Box::new(messages_flume_seq),
+            || {
+                Ok(filtered_query()?
+                    .select(sql::<BigInt>("COUNT(DISTINCT messages.key_id)"))
+                    .first::<i64>(&connection)?)
+            },
+        )?;
+
+        let key_ids = gathering_keys_and_cursor
+            .iter()
+            .map(|(key_id, _)| *key_id)
+            .collect::<Vec<_>>();
+        let mut gatherings = get_gatherings(&connection, &key_ids)?
+            .into_iter()
+            .map(|gathering| (gathering.key_id, gathering))
+            .collect::<HashMap<_, _>>();
+
+        let gatherings_and_cursor = gathering_keys_and_cursor
+            .into_iter()
+            .filter_map(|(key_id, cursor)| {
+                gatherings.remove(&key_id).map(|gathering| (gathering, cursor))
+            })
+            .collect();
+
+        Ok(GatheringConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            gatherings_and_cursor
+        })
+    }
+
+    /// git-ssb repositories in the order they were received, newest first by default.
+    field git_repos(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Find public, private or all repos. Private repos are only in the db when they could
+        /// be decrypted.
+        privacy = (Privacy::Public): Privacy,
+    ) -> FieldResult<GitRepoConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        // All the repos, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let query = git_repos_table
+                .inner_join(messages_table.on(messages_key_id.eq(git_repos_key_id)))
+                .select((
+                    git_repos_key_id,
+                    git_repos_flume_seq.nullable(),
+                    git_repos_flume_seq.nullable(),
+                ))
+                .into_boxed();
+
+            let query = match privacy {
+                Privacy::Private => query.filter(messages_is_decrypted.eq(true)),
+                Privacy::Public => query.filter(messages_is_decrypted.eq(false)),
+                Privacy::All => query,
+            };
+
+            Ok(query)
+        };
+
+        let (repo_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Received,
+            filtered_query,
+            || Box::new(git_repos_flume_seq.nullable()),
+            || Box::new(git_repos_flume_seq.nullable()),
+            || Ok(filtered_query()?.count().get_result::<i64>(&connection)?),
+        )?;
+
+        Ok(GitRepoConnection {
+            next,
+            total_count: total_count as i32,
+            page_info,
+            repo_keys_and_cursor,
+        })
+    }
+    /// Find a git-ssb repository by the key string of its git-repo message.
+    field repo(&executor, id: String) -> FieldResult<Option<GitRepo>> {
+        let connection = executor.context().connection.get()?;
+
+        let key_id = keys_table
+            .select(keys_id)
+            .filter(keys_key.eq(id))
+            .first::<Option<i32>>(&connection)
+            .optional()?
+            .and_then(|key_id| key_id);
+
+        let repo = match key_id {
+            Some(key_id) if is_git_repo(&connection, key_id)? => Some(GitRepo{key_id, cursor: None}),
+            _ => None,
+        };
+
+        Ok(repo)
+    }
+    /// Polls in the order they were received, newest first by default.
+    field polls(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Find public, private or all polls.
+        privacy = (Privacy::Public): Privacy,
+    ) -> FieldResult<PollConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        // All the polls, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let query = polls_table
+                .inner_join(messages_table.on(messages_key_id.eq(polls_key_id)))
+                .select((
+                    polls_key_id,
+                    polls_flume_seq.nullable(),
+                    polls_flume_seq.nullable(),
+                ))
+                .into_boxed();
+
+            let query = match privacy {
+                Privacy::Private => query.filter(messages_is_decrypted.eq(true)),
+                Privacy::Public => query.filter(messages_is_decrypted.eq(false)),
+                Privacy::All => query,
+            };
+
+            Ok(query)
+        };
+
+        let (poll_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Received,
+            filtered_query,
+            || Box::new(polls_flume_seq.nullable()),
+            || Box::new(polls_flume_seq.nullable()),
+            || Ok(filtered_query()?.count().get_result::<i64>(&connection)?),
+        )?;
+
+        let key_ids = poll_keys_and_cursor
+            .iter()
+            .map(|(key_id, _)| *key_id)
+            .collect::<Vec<_>>();
+        let mut polls = get_polls(&connection, &key_ids)?
+            .into_iter()
+            .map(|poll| (poll.key_id, poll))
+            .collect::<HashMap<_, _>>();
+
+        let polls_and_cursor = poll_keys_and_cursor
+            .into_iter()
+            .filter_map(|(key_id, cursor)| polls.remove(&key_id).map(|poll| (poll, cursor)))
+            .collect();
+
+        Ok(PollConnection {
+            next,
+            total_count: total_count as i32,
+            page_info,
+            polls_and_cursor,
+        })
+    }
+    /// Find a poll by the key string of its poll message.
+    field poll(&executor, id: String) -> FieldResult<Option<Poll>> {
+        let connection = executor.context().connection.get()?;
+
+        let key_id = keys_table
+            .select(keys_id)
+            .filter(keys_key.eq(id))
+            .first::<Option<i32>>(&connection)
+            .optional()?
+            .and_then(|key_id| key_id);
+
+        let poll = match key_id {
+            Some(key_id) => get_poll(&connection, key_id)?.map(|poll| Poll{poll, cursor: None}),
+            None => None,
+        };
+
+        Ok(poll)
+    }
+
+    /// Tags in the order they were created, newest first by default.
+    field tags(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Only include the tags created by this author.
+        author: Option<String>,
+    ) -> FieldResult<TagConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let author_id = match author {
+            Some(author) => {
+                let author_id = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq(author))
+                    .first::<Option<i32>>(&connection)
+                    .optional()?
+                    .and_then(|author_id| author_id);
+
+                match author_id {
+                    Some(author_id) => Some(author_id),
+                    None => return Ok(TagConnection{next, ..TagConnection::default()}),
+                }
+            }
+            None => None,
+        };
+
+        let (tag_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Received,
+            || Ok(tags_query(author_id)),
+            || Box::new(tags_flume_seq.nullable()),
+            || Box::new(tags_flume_seq.nullable()),
+            || Ok(tags_query(author_id).count().get_result::<i64>(&connection)?),
+        )?;
+
+        Ok(TagConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            tag_keys_and_cursor
+        })
+    }
+    /// The posts tagged with a tag by its author, in the order they were tagged.
+    field tagged_posts(
+        &executor,
+        /// The id of the message that created the tag.
+        tag_id: String,
+        /// Use a cursor string to get results before the cursor (backwards pagination, most
+        /// recently tagged first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, least
+        /// recently tagged first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+    ) -> FieldResult<PostConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let tag_key_id = keys_table
+            .select(keys_id)
+            .filter(keys_key.eq(tag_id))
+            .first::<Option<i32>>(&connection)
+            .optional()?
+            .and_then(|key_id| key_id);
+
+        let tag_key_id = match tag_key_id {
+            Some(tag_key_id) => tag_key_id,
+            None => return Ok(PostConnection{next, ..PostConnection::default()}),
+        };
+
+        let (post_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Received,
+            || Ok(tagged_posts_query(tag_key_id)),
+            || Box::new(tag_operations_flume_seq.nullable()),
+            || Box::new(tag_operations_flume_seq.nullable()),
+            || Ok(tagged_posts_query(tag_key_id).count().get_result::<i64>(&connection)?),
+        )?;
+
+        Ok(PostConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            post_keys_and_cursor
+        })
+    }
+
     /// Find a thread by the key string of the root message.
-    field thread(&executor, root_id: String, order_by = (OrderBy::Received): OrderBy) -> FieldResult<Option<Thread>> {
+    field thread(
+        &executor,
+        root_id: String,
+        /// How to order the replies. Causal order if not set.
+        order_by: Option<OrderBy>,
+    ) -> FieldResult<Option<Thread>> {
 
         let connection = executor.context().connection.get()?;
 
@@ -97,14 +703,19 @@ graphql_object!(Query: Context |&self| {
             .first::<i32>(&connection)
             .map(|key_id|{
                 let root = Post{key_id, cursor: None};
-                Some(Thread{root, cursor: "".to_owned()})
+                Some(Thread{root, cursor: "".to_owned(), reply_order_by: order_by.clone()})
             })
             .unwrap_or(None);
 
         Ok(thread)
     }
     /// Find the containing thread for any post message, even if it is the root message.
-    field thread_for_post(&executor, post_id: String, order_by = (OrderBy::Received): OrderBy) -> FieldResult<Option<Thread>> {
+    field thread_for_post(
+        &executor,
+        post_id: String,
+        /// How to order the replies. Causal order if not set.
+        order_by: Option<OrderBy>,
+    ) -> FieldResult<Option<Thread>> {
 
         let connection = executor.context().connection.get()?;
 
@@ -124,7 +735,7 @@ graphql_object!(Query: Context |&self| {
                 };
 
                 let root = Post{key_id: key, cursor: None};
-                Some(Thread{root, cursor: "".to_owned()})
+                Some(Thread{root, cursor: "".to_owned(), reply_order_by: order_by.clone()})
             })
             .unwrap_or(None);
 
@@ -165,186 +776,238 @@ graphql_object!(Query: Context |&self| {
         has_replies_authored_by_someone_followed_by: Option<Vec<String>>,
         /// Include threads that mention the provided authors.
         mentions_authors: Option<Vec<String>>,
+        /// Only include threads whose root post is in one of the provided channels. Unlike the
+        /// selectors above, this filter is AND'd with the rest of the query.
+        channels: Option<Vec<String>>,
+        /// Only include threads whose root was published at or after this timestamp, in
+        /// milliseconds. Uses asserted or received time, following `order_by`.
+        since: Option<f64>,
+        /// Only include threads whose root was published before this timestamp, in milliseconds.
+        /// Uses asserted or received time, following `order_by`.
+        until: Option<f64>,
+        /// Only include threads whose latest reply (or the root, if there are no replies) was
+        /// published at or after this timestamp, in milliseconds.
+        active_since: Option<f64>,
+        /// Only include threads whose latest reply (or the root, if there are no replies) was
+        /// published before this timestamp, in milliseconds.
+        active_until: Option<f64>,
+        /// Only include threads with posts that haven't been marked as read when true, or threads
+        /// that have been read completely when false.
+        unread: Option<bool>,
+        /// Include, exclude or only find threads whose root post has a content warning.
+        content_warning = (ContentWarningFilter::Include): ContentWarningFilter,
         /// Order threads by asserted time or received time.
         order_by = (OrderBy::Received): OrderBy,
         ) -> FieldResult<ThreadConnection> {
 
-        //TODO Filtering by date ranges!
-
         let next = 10;
 
         // Get the context from the executor.
         let connection = executor.context().connection.get()?;
 
-        let mut query = root_posts_table
-            .inner_join(messages_table.on(root_posts_key_id.eq(messages_key_id)))
-            .left_join(mentions_table.on(mentions_link_from_key_id.eq(messages_key_id)))
-            .select((root_posts_key_id, root_posts_flume_seq, root_posts_asserted_timestamp))
-            .into_boxed();
-
-        if let Some(mentions_authors) = mentions_authors {
-            let author_key_ids = authors_table
-                .select(authors_id)
-                .filter(authors_author.eq_any(mentions_authors))
-                .load::<Option<i32>>(&connection)?;
-
-            let sub_query = reply_posts_table
-                .select(reply_posts_root_post_id)
-                .filter(reply_posts_author_id.nullable().eq_any(author_key_ids.clone()));
-
-            query = query
-                .or_filter(mentions_link_to_author_id.nullable().eq_any(author_key_ids))
-                .or_filter(root_posts_key_id.eq_any(sub_query));
-        }
-
+        let ordering = || -> Box<dyn BoxableExpression<_, _, SqlType=Nullable<BigInt>>> {
+            match order_by {
+                OrderBy::Asserted => Box::new(root_posts_asserted_timestamp.nullable()),
+                OrderBy::LastActivity => Box::new(root_posts_last_activity_flume_seq.nullable()),
+                OrderBy::Received => Box::new(root_posts_flume_seq.nullable())
+            }
+        };
 
-        if let Some(authors) = roots_authored_by {
-            let author_key_ids = authors_table
-                .select(authors_id)
-                .filter(authors_author.eq_any(authors))
-                .load::<Option<i32>>(&(*connection))?;
+        // All the threads that match the selectors and filters, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let mut query = root_posts_table
+                .inner_join(messages_table.on(root_posts_key_id.eq(messages_key_id)))
+                .left_join(mentions_table.on(
+                        mentions_link_from_key_id.eq(messages_key_id).and(mentions_rel.eq("mentions"))
+                        ))
+                .select((root_posts_key_id, root_posts_flume_seq.nullable(), ordering()))
+                .into_boxed();
+
+            if let Some(mentions_authors) = &mentions_authors {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(mentions_authors.clone()))
+                    .load::<Option<i32>>(&connection)?;
+
+                let sub_query = reply_posts_table
+                    .select(reply_posts_root_post_id)
+                    .filter(reply_posts_author_id.nullable().eq_any(author_key_ids.clone()));
 
                 query = query
-                    .or_filter(root_posts_author_id.nullable().eq_any(author_key_ids));
-        }
+                    .or_filter(mentions_link_to_author_id.nullable().eq_any(author_key_ids))
+                    .or_filter(root_posts_key_id.eq_any(sub_query));
+            }
 
-        if let Some(authors) = roots_authored_by_someone_followed_by {
-            let author_key_ids = authors_table
-                .inner_join(
-                    contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
-                    )
-                .select(contacts_contact_author_id)
-                .filter(authors_author.eq_any(authors))
-                .filter(contacts_state.eq(1))
-                .load::<i32>(&(*connection))?;
 
-                query = query
-                    .or_filter(root_posts_author_id.nullable().eq_any(author_key_ids));
-        }
+            if let Some(authors) = &roots_authored_by {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(authors.clone()))
+                    .load::<Option<i32>>(&(*connection))?;
+
+                    query = query
+                        .or_filter(root_posts_author_id.nullable().eq_any(author_key_ids));
+            }
+
+            if let Some(authors) = &roots_authored_by_someone_followed_by {
+                let author_key_ids = authors_table
+                    .inner_join(
+                        contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
+                        )
+                    .select(contacts_contact_author_id)
+                    .filter(authors_author.eq_any(authors.clone()))
+                    .filter(contacts_state.eq(1))
+                    .load::<i32>(&(*connection))?;
+
+                    query = query
+                        .or_filter(root_posts_author_id.nullable().eq_any(author_key_ids));
+            }
+
+            if let Some(authors) = &has_replies_authored_by_someone_followed_by {
+                let author_key_ids = authors_table
+                    .inner_join(
+                        contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
+                        )
+                    .select(contacts_contact_author_id)
+                    .filter(authors_author.eq_any(authors.clone()))
+                    .filter(contacts_state.eq(1))
+                    .load::<i32>(&(*connection))?;
+
+                let sub_query = reply_posts_table
+                    .select(reply_posts_root_post_id)
+                    .filter(reply_posts_author_id.nullable().eq_any(author_key_ids));
+
+                    query = query
+                        .or_filter(root_posts_key_id.eq_any(sub_query));
+            }
 
-        if let Some(authors) = has_replies_authored_by_someone_followed_by {
-            let author_key_ids = authors_table
-                .inner_join(
-                    contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
-                    )
-                .select(contacts_contact_author_id)
-                .filter(authors_author.eq_any(authors))
-                .filter(contacts_state.eq(1))
-                .load::<i32>(&(*connection))?;
+            if let Some(authors) = &has_replies_authored_by {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(authors.clone()))
+                    .load::<Option<i32>>(&(*connection))?;
 
-            let sub_query = reply_posts_table
-                .select(reply_posts_root_post_id)
-                .filter(reply_posts_author_id.nullable().eq_any(author_key_ids));
+                let sub_query = reply_posts_table
+                    .select(reply_posts_root_post_id)
+                    .filter(reply_posts_author_id.nullable().eq_any(author_key_ids));
 
                 query = query
                     .or_filter(root_posts_key_id.eq_any(sub_query));
-        }
-
-        if let Some(authors) = has_replies_authored_by {
-            let author_key_ids = authors_table
-                .select(authors_id)
-                .filter(authors_author.eq_any(authors))
-                .load::<Option<i32>>(&(*connection))?;
+            }
 
-            let sub_query = reply_posts_table
-                .select(reply_posts_root_post_id)
-                .filter(reply_posts_author_id.nullable().eq_any(author_key_ids));
+            if let Some(channels) = &channels {
+                let channel_names = channels
+                    .iter()
+                    .filter_map(|channel| normalize_channel(channel))
+                    .collect::<Vec<_>>();
 
-            query = query
-                .or_filter(root_posts_key_id.eq_any(sub_query));
-        }
+                let sub_query = channel_links_table
+                    .inner_join(channels_table.on(channels_id.eq(channel_links_link_to_channel_id.nullable())))
+                    .select(channel_links_link_from_key_id)
+                    .filter(channels_channel.eq_any(channel_names));
 
-        query = match privacy {
-            Privacy::Private => {
-                query.filter(messages_is_decrypted.eq(true))
-            },
-            Privacy::Public => {
-                query.filter(messages_is_decrypted.eq(false))
-            },
-            Privacy::All => {
-                query
-            },
-        };
+                query = query
+                    .filter(root_posts_key_id.eq_any(sub_query));
+            }
 
-        let ordering: Box<dyn BoxableExpression<_, _, SqlType=BigInt>>  = match order_by {
-            OrderBy::Asserted => Box::new(root_posts_asserted_timestamp), 
-            _ => Box::new(root_posts_flume_seq)
-        };
+            let root_time = || -> Box<dyn BoxableExpression<_, _, SqlType=BigInt>> {
+                match order_by {
+                    OrderBy::Asserted => Box::new(root_posts_asserted_timestamp),
+                    _ => Box::new(messages_received_time)
+                }
+            };
 
-        let filtering: Box<dyn BoxableExpression<_, _, SqlType=BigInt>>  = match order_by {
-            OrderBy::Asserted => Box::new(root_posts_asserted_timestamp), 
-            _ => Box::new(root_posts_flume_seq)
-        };
+            // The roots of all the threads with a reply published at or after the time.
+            let replied_since = |time: i64| {
+                let sub_query = reply_posts_table
+                    .inner_join(messages_table.on(messages_key_id.eq(reply_posts_key_id)))
+                    .select(reply_posts_root_post_id)
+                    .into_boxed();
 
-        query = match (&before, &after, last, first) {
-            (Some(b), None, Some(l), None ) => {
-                let start_cursor = decode_cursor(&b)?;
+                match order_by {
+                    OrderBy::Asserted => sub_query.filter(reply_posts_asserted_timestamp.ge(time)),
+                    _ => sub_query.filter(messages_received_time.ge(time))
+                }
+            };
 
-                query
-                    .filter(filtering.lt(start_cursor))
-                    .order(ordering.desc())
-                    .limit(l as i64)
-            },
-            (None, Some(a), None, Some(f)) => {
-                let start_cursor = decode_cursor(&a)?;
+            if let Some(since) = since {
+                query = query.filter(root_time().ge(since as i64));
+            }
 
-                query
-                    .filter(filtering.gt(start_cursor))
-                    .order(ordering.asc())
-                    .limit(f as i64)
-            },
-            (None, None, Some(l), _) => {
-                query
-                    .order(ordering.desc())
-                    .limit(l as i64)
-            },
-            (None, None, None, Some(f)) => {
-                query
-                    .filter(filtering.gt(0))
-                    .order(ordering.asc())
-                    .limit(f as i64)
-            },
-            (None, None, None, None) => {
-                query
-                    .order(ordering.desc())
-                    .limit(next as i64)
-            },
-            (Some(_), Some(_), _, _) => {
-                Err("Before and After can't be set at the same time.")?
+            if let Some(until) = until {
+                query = query.filter(root_time().lt(until as i64));
             }
-            _ => {
-                Err("Incorrect combination or before, after, first and last")?
+
+            if let Some(since) = active_since {
+                let since = since as i64;
+                query = query.filter(
+                    root_time().ge(since).or(root_posts_key_id.eq_any(replied_since(since)))
+                    );
             }
-        };
 
-        let query = query
-            .distinct();
+            if let Some(until) = active_until {
+                let until = until as i64;
+                query = query.filter(
+                    root_time().lt(until).and(not(root_posts_key_id.eq_any(replied_since(until))))
+                    );
+            }
 
-        let results = query
-            .load::<(i32, i64, i64)>(&(*connection))?;
+            if let Some(unread) = unread {
+                let unread_replies = reply_posts_table
+                    .select(reply_posts_root_post_id)
+                    .filter(reply_posts_key_id.nullable().eq_any(unread_key_ids()));
 
-        let thread_keys_and_cursor = results
-            .iter()
-            .map(|(key_id, seq, timestamp )| {
-                match order_by {
-                    OrderBy::Asserted => (*key_id, encode_cursor(*timestamp)),
-                    _ => (*key_id, encode_cursor(*seq))
-                }
-            })
-            .collect::<Vec<(i32, String)>>();
+                let has_unread = root_posts_key_id.nullable().eq_any(unread_key_ids())
+                    .or(root_posts_key_id.eq_any(unread_replies));
 
-        let start_cursor = get_start_cursor(&results, &order_by);
-        let end_cursor = get_end_cursor(&results, &order_by);
+                query = if unread {
+                    query.filter(has_unread)
+                } else {
+                    query.filter(not(has_unread))
+                };
+            }
 
-        let page_info = PageInfo {
-            start_cursor,
-            end_cursor,
-            has_next_page: true, //TODO 
-            has_previous_page: true //TODO make this work.
+            let warned_key_ids = || content_warnings_table.select(content_warnings_key_id);
+            query = match content_warning {
+                ContentWarningFilter::Include => query,
+                ContentWarningFilter::Exclude => query.filter(not(root_posts_key_id.eq_any(warned_key_ids()))),
+                ContentWarningFilter::Only => query.filter(root_posts_key_id.eq_any(warned_key_ids())),
+            };
+
+            query = match privacy {
+                Privacy::Private => {
+                    query.filter(messages_is_decrypted.eq(true))
+                },
+                Privacy::Public => {
+                    query.filter(messages_is_decrypted.eq(false))
+                },
+                Privacy::All => {
+                    query
+                },
+            };
+
+            Ok(query)
         };
 
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let (thread_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &order_by,
+            filtered_query,
+            ordering,
+            || Box::new(root_posts_flume_seq.nullable()),
+            || {
+                Ok(filtered_query()?
+                    .select(sql::<BigInt>("COUNT(DISTINCT root_posts.key_id)"))
+                    .first::<i64>(&connection)?)
+            },
+        )?;
+
         Ok(ThreadConnection {
             next,
+            total_count: total_count as i32,
             thread_keys_and_cursor,
             page_info
         })
@@ -398,143 +1061,149 @@ graphql_object!(Query: Context |&self| {
         authors: Option<Vec<String>>,
         /// Find posts that mention the provided authors.
         mentions_authors: Option<Vec<String>>,
-        /// Find posts that mention the provided channels.
+        /// Find posts that are in any of the provided channels, either by their `channel` field or
+        /// by a `#hashtag` in their text.
+        channels: Option<Vec<String>>,
+        /// Only include posts published at or after this timestamp, in milliseconds. Uses
+        /// asserted or received time, following `order_by`.
+        since: Option<f64>,
+        /// Only include posts published before this timestamp, in milliseconds. Uses asserted or
+        /// received time, following `order_by`.
+        until: Option<f64>,
+        /// Include, exclude or only find posts with a content warning. Use `EXCLUDE` with `query`
+        /// to search without matching warned posts.
+        content_warning = (ContentWarningFilter::Include): ContentWarningFilter,
+        /// Order posts by asserted time or received time.
         order_by = (OrderBy::Received): OrderBy,
     ) -> FieldResult<PostConnection> {
 
         let next = 10;
 
-        //TODO: Date range
+        // Only threads have activity.
+        let order_by = match order_by {
+            OrderBy::LastActivity => OrderBy::Received,
+            order_by => order_by,
+        };
+
         let connection = executor.context().connection.get()?;
 
-        let mut boxed_query = messages_table
-            .left_join(mentions_table.on(mentions_link_from_key_id.eq(messages_key_id)))
-            .select((messages_key_id, messages_flume_seq, messages_asserted_time))
-            .into_boxed();
+        // All the posts that match the filters, before any pagination.
+        let filtered_query = || -> FieldResult<_> {
+            let mut boxed_query = messages_table
+                .left_join(mentions_table.on(
+                        mentions_link_from_key_id.eq(messages_key_id).and(mentions_rel.eq("mentions"))
+                        ))
+                .select((messages_key_id, messages_flume_seq, messages_asserted_time))
+                .filter(messages_content_type.eq("post"))
+                .into_boxed();
+
+            if let Some(mentions_authors) = &mentions_authors {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(mentions_authors.clone()))
+                    .load::<Option<i32>>(&connection)?;
 
-        if let Some(mentions_authors) = mentions_authors {
-            let author_key_ids = authors_table
-                .select(authors_id)
-                .filter(authors_author.eq_any(mentions_authors))
-                .load::<Option<i32>>(&connection)?;
+                boxed_query = boxed_query
+                    .filter(mentions_link_to_author_id.nullable().eq_any(author_key_ids));
+            }
 
-            boxed_query = boxed_query
-                .filter(mentions_link_to_author_id.nullable().eq_any(author_key_ids));
-        }
+            if let Some(query_string) = &query {
+                let matching_texts_keys = texts_table
+                    .select(texts_key_id)
+                    .filter(sql("text MATCH ").bind::<diesel::sql_types::Text, _>(query_string.clone()))
+                    .load::<i32>(&connection)?;
 
-        if let Some(query_string) = query {
-            let matching_texts_keys = texts_table
-                .select(texts_key_id)
-                .filter(sql("text MATCH ").bind::<diesel::sql_types::Text, _>(query_string))
-                .load::<i32>(&connection)?;
+                boxed_query = boxed_query
+                    .filter(messages_key_id.eq_any(matching_texts_keys));
+            }
 
-            boxed_query = boxed_query
-                .filter(messages_key_id.eq_any(matching_texts_keys));
-        }
+            if let Some(channels) = &channels {
+                let channel_names = channels
+                    .iter()
+                    .filter_map(|channel| normalize_channel(channel))
+                    .collect::<Vec<_>>();
 
-        boxed_query = match privacy {
-            Privacy::Private => {
-                boxed_query.filter(messages_is_decrypted.eq(true))
-            },
-            Privacy::Public => {
-                boxed_query.filter(messages_is_decrypted.eq(false))
-            },
-            Privacy::All => {
-                boxed_query
-            },
-        };
-
-        if let Some(authors) = authors {
-            let author_key_ids = authors_table
-                .select(authors_id)
-                .filter(authors_author.eq_any(authors))
-                .load::<Option<i32>>(&connection)?;
+                let sub_query = channel_links_table
+                    .inner_join(channels_table.on(channels_id.eq(channel_links_link_to_channel_id.nullable())))
+                    .select(channel_links_link_from_key_id)
+                    .filter(channels_channel.eq_any(channel_names));
 
                 boxed_query = boxed_query
-                    .filter(messages_author_id.nullable().eq_any(author_key_ids));
-        }
+                    .filter(messages_key_id.eq_any(sub_query));
+            }
 
-        let ordering: Box<dyn BoxableExpression<_, _, SqlType=Nullable<BigInt>>>  = match order_by {
-            OrderBy::Asserted => Box::new(messages_asserted_time), 
-            _ => Box::new(messages_flume_seq)
-        };
+            let warned_key_ids = || content_warnings_table.select(content_warnings_key_id);
+            boxed_query = match content_warning {
+                ContentWarningFilter::Include => boxed_query,
+                ContentWarningFilter::Exclude => boxed_query.filter(not(messages_key_id.eq_any(warned_key_ids()))),
+                ContentWarningFilter::Only => boxed_query.filter(messages_key_id.eq_any(warned_key_ids())),
+            };
+
+            boxed_query = match privacy {
+                Privacy::Private => {
+                    boxed_query.filter(messages_is_decrypted.eq(true))
+                },
+                Privacy::Public => {
+                    boxed_query.filter(messages_is_decrypted.eq(false))
+                },
+                Privacy::All => {
+                    boxed_query
+                },
+            };
+
+            if let Some(authors) = &authors {
+                let author_key_ids = authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq_any(authors.clone()))
+                    .load::<Option<i32>>(&connection)?;
+
+                    boxed_query = boxed_query
+                        .filter(messages_author_id.nullable().eq_any(author_key_ids));
+            }
 
-        let filtering: Box<dyn BoxableExpression<_, _, SqlType=Nullable<BigInt>>>  = match order_by {
-            OrderBy::Asserted => Box::new(messages_asserted_time), 
-            _ => Box::new(messages_flume_seq)
-        };
+            if let Some(since) = since {
+                boxed_query = match order_by {
+                    OrderBy::Asserted => boxed_query.filter(messages_asserted_time.ge(since as i64)),
+                    _ => boxed_query.filter(messages_received_time.ge(since as i64))
+                };
+            }
 
-        boxed_query = match (&before, &after, last, first) {
-            (Some(b), None, Some(l), None ) => {
-                let start_cursor = decode_cursor(&b)?;
+            if let Some(until) = until {
+                boxed_query = match order_by {
+                    OrderBy::Asserted => boxed_query.filter(messages_asserted_time.lt(until as i64)),
+                    _ => boxed_query.filter(messages_received_time.lt(until as i64))
+                };
+            }
 
-                boxed_query
-                    .filter(filtering.lt(start_cursor))
-                    .order(ordering.desc())
-                    .limit(l as i64)
-            },
-            (None, Some(a), None, Some(f)) => {
-                let start_cursor = decode_cursor(&a)?;
+            Ok(boxed_query)
+        };
 
-                boxed_query
-                    .filter(filtering.gt(start_cursor))
-                    .order(ordering.asc())
-                    .limit(f as i64)
-            },
-            (None, None, Some(l), _) => {
-                boxed_query
-                    .order(ordering.desc())
-                    .limit(l as i64)
-            },
-            (None, None, None, Some(f)) => {
-                boxed_query
-                    .filter(filtering.gt(0))
-                    .order(ordering.asc())
-                    .limit(f as i64)
-            },
-            (None, None, None, None) => {
-                boxed_query
-                    .order(ordering.desc())
-                    .limit(next as i64)
-            },
-            (Some(_), Some(_), _, _) => {
-                Err("Before and After can't be set at the same time.")?
-            }
-            _ => {
-                Err("Incorrect combination or before, after, first and last")?
+        let ordering = || -> Box<dyn BoxableExpression<_, _, SqlType=Nullable<BigInt>>> {
+            match order_by {
+                OrderBy::Asserted => Box::new(messages_asserted_time),
+                _ => Box::new(messages_flume_seq)
             }
         };
 
-        let results = boxed_query
-            .filter(messages_content_type.eq("post"))
-            .distinct()
-            .load::<(i32, Option<i64>, Option<i64>)>(&connection)?
-            .into_iter()
-            .map(|(key_id, seq, time)| (key_id, seq.unwrap_or(0), time.unwrap_or(0)))
-            .collect::<Vec<_>>();
-
-        let start_cursor = get_start_cursor(&results[..], &order_by);
-        let end_cursor = get_end_cursor(&results[..], &order_by);
-
-
-        let page_info = PageInfo {
-            start_cursor,
-            end_cursor,
-            has_next_page: true, //TODO 
-            has_previous_page: true //TODO make this work.
-        };
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let (post_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &order_by,
+            filtered_query,
+            ordering,
+            || Box::new(messages_flume_seq),
+            || {
+                Ok(filtered_query()?
+                    .select(sql::<BigInt>("COUNT(DISTINCT messages.key_id)"))
+                    .first::<i64>(&connection)?)
+            },
+        )?;
 
-        let post_keys_and_cursor = results
-            .iter()
-            .map(|(key_id, seq, timestamp )| {
-                match order_by {
-                    OrderBy::Asserted => (*key_id, encode_cursor(*timestamp)),
-                    _ => (*key_id, encode_cursor(*seq))
-                }
-            })
-            .collect::<Vec<(i32, String)>>();
         Ok(PostConnection{
             next,
+            total_count: total_count as i32,
             page_info,
             post_keys_and_cursor
         })
@@ -556,8 +1225,78 @@ graphql_object!(Query: Context |&self| {
     }
 
     /// Search for an author by a query string. Will search names and optionally descriptions too.
+    /// The best matches come first.
     field authors(&executor, query: String, exclude_if_blocked_by: Option<Vec<String>>, include_descriptions = false: bool) -> FieldResult<Vec<Author>>{
-        Err("Not implemented")?
+        let connection = executor.context().connection.get()?;
+
+        let matching_author_ids = search_author_texts(&connection, &query, include_descriptions)?;
+
+        let blocked_author_ids = match exclude_if_blocked_by {
+            Some(authors) => {
+                authors_table
+                    .inner_join(
+                        contacts_table.on(authors_id.eq(contacts_author_id.nullable()))
+                        )
+                    .select(contacts_contact_author_id)
+                    .filter(authors_author.eq_any(authors))
+                    .filter(contacts_state.eq(-1))
+                    .load::<i32>(&connection)?
+            },
+            None => Vec::new()
+        };
+
+        let authors = matching_author_ids
+            .into_iter()
+            .filter(|author_id| !blocked_author_ids.contains(author_id))
+            .map(|author_id| Author{author_id})
+            .collect();
+
+        Ok(authors)
+    }
+
+    /// Find all the channels we know about, from posts and from channel subscriptions. The most
+    /// popular channels (by number of subscribers) come first, then the most recent ones.
+    ///
+    /// `last` pages from the most popular channels down, `first` from the least popular up. When
+    /// not passing any options for `before`, `after`, `first` and `last`, the default is the 10
+    /// most popular channels.
+    field channels(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, most
+        /// popular first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, least
+        /// popular first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+    ) -> FieldResult<ChannelConnection>{
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        // Ordering by a value other than the flume seq encodes `[subscriber count, channel id]`
+        // cursors.
+        let (channel_ids_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &OrderBy::Asserted,
+            || Ok(channels_query()),
+            || Box::new(sql::<Nullable<BigInt>>(SUBSCRIBER_COUNT)),
+            || Box::new(sql::<Nullable<BigInt>>("channels.id")),
+            || Ok(channels_table.count().get_result::<i64>(&connection)?),
+        )?;
+
+        Ok(ChannelConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            channel_ids_and_cursor
+        })
     }
 
     /// Find all the message types we know about
@@ -569,53 +1308,240 @@ graphql_object!(Query: Context |&self| {
             .distinct()
             .load::<Option<String>>(&connection)?
             .into_iter()
-            .filter_map(|message_type|{message_type})
+            .flatten()
             .collect();
 
         Ok(results)
     }
 
-    /// Find all messages by type
-    field messagesByType(&executor, message_type: String) -> FieldResult<String> { //TODO define a Message type that would be compatible with existing js.
-        Err("Not implemented")?
+    /// Find messages by the type of their content, eg. `git-repo` or `gathering`.
+    ///
+    /// Messages are paginated the same way as `posts`: `first` will give you the oldest messages
+    /// first, `last` will give you the newest ones. When not passing any options for `before`,
+    /// `after`, `first` and `last`, the default is the 10 most recent messages.
+    field messages_by_type(
+        &executor,
+        /// The type of message to find.
+        message_type: String,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Find public, private or all messages.
+        privacy = (Privacy::Public): Privacy,
+        /// Order messages by asserted time or received time.
+        order_by = (OrderBy::Received): OrderBy,
+    ) -> FieldResult<MessageConnection> {
+
+        let next = 10;
+
+        // Only threads have activity.
+        let order_by = match order_by {
+            OrderBy::LastActivity => OrderBy::Received,
+            order_by => order_by,
+        };
+
+        let connection = executor.context().connection.get()?;
+
+        // All the messages of this type, before any pagination.
+        let filtered_query = || {
+            let boxed_query = messages_table
+                .select((messages_key_id, messages_flume_seq, messages_asserted_time))
+                .filter(messages_content_type.eq(message_type.clone()))
+                .into_boxed();
+
+            match privacy {
+                Privacy::Private => {
+                    boxed_query.filter(messages_is_decrypted.eq(true))
+                },
+                Privacy::Public => {
+                    boxed_query.filter(messages_is_decrypted.eq(false))
+                },
+                Privacy::All => {
+                    boxed_query
+                },
+            }
+        };
+
+        let ordering = || -> Box<dyn BoxableExpression<_, _, SqlType=Nullable<BigInt>>> {
+            match order_by {
+                OrderBy::Asserted => Box::new(messages_asserted_time),
+                _ => Box::new(messages_flume_seq)
+            }
+        };
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        let (message_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            &connection,
+            &page,
+            &order_by,
+            || Ok(filtered_query()),
+            ordering,
+            || Box::new(messages_flume_seq),
+            || Ok(filtered_query().count().get_result::<i64>(&connection)?),
+        )?;
+
+        Ok(MessageConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            message_keys_and_cursor
+        })
     }
 
     /// Find a message by key string
-    field message(&executor, id: String) -> FieldResult<Option<String>> { // TODO: use message type.
+    field message(&executor, id: String) -> FieldResult<Option<Message>> {
         let connection = executor.context().connection.get()?;
-        let results = messages_table
+        let message = messages_table
             .inner_join(keys_table.on(
                     messages_key_id.nullable().eq(keys_id)
                     ))
-            .select(messages_content)
+            .select(messages_key_id)
             .filter(keys_key.eq(id))
-            .first::<Option<String>>(&connection)?;
+            .first::<i32>(&connection)
+            .optional()?;
 
-        Ok(results)
+        let message = match message {
+            Some(key_id) => Some(Message::load(&connection, key_id, None)?),
+            None => None,
+        };
+
+        Ok(message)
     }
 
-    /// Search for links from or to a message id
-    field links(&executor, from: Option<String>, to: Option<String>) -> FieldResult<Vec<Author>>{
-        Err("Not implemented")?
+    /// Search for links from a message, or to a message, author or blob. This is a generic
+    /// backlink api: eg. use `to` and `rel: "vote.link"` to find the votes on a message.
+    ///
+    /// Links are ordered by the message they are from. `first` will give you the oldest links
+    /// first, `last` will give you the newest ones. When not passing any options for `before`,
+    /// `after`, `first` and `last`, the default is the 10 most recent links.
+    field links(
+        &executor,
+        /// Find links in the message with this key.
+        from: Option<String>,
+        /// Find links to the message, author or blob with this id.
+        to: Option<String>,
+        /// Find links with this rel, eg. `root`, `branch`, `mentions`, `vote.link` or `contact`.
+        rel: Option<String>,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+    ) -> FieldResult<LinkConnection>{
+
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        let mut filter = LinkFilter::default();
+
+        if let Some(from) = from {
+            let from_key_id = keys_table
+                .select(keys_id)
+                .filter(keys_key.eq(from))
+                .first::<Option<i32>>(&connection)
+                .optional()?
+                .and_then(|key_id| key_id);
+
+            match from_key_id {
+                Some(key_id) => filter.from_key_id = Some(key_id),
+                None => return Ok(LinkConnection::default()),
+            }
+        }
+
+        if let Some(to) = to {
+            let to_id = match to.chars().next() {
+                Some('%') => keys_table
+                    .select(keys_id)
+                    .filter(keys_key.eq(&to))
+                    .first::<Option<i32>>(&connection)
+                    .optional()?
+                    .and_then(|key_id| key_id)
+                    .map(|key_id| (LinkKind::Message, key_id)),
+                Some('@') => authors_table
+                    .select(authors_id)
+                    .filter(authors_author.eq(&to))
+                    .first::<Option<i32>>(&connection)
+                    .optional()?
+                    .and_then(|author_id| author_id)
+                    .map(|author_id| (LinkKind::Author, author_id)),
+                Some('&') => blobs_table
+                    .select(blobs_id)
+                    .filter(blobs_blob.eq(&to))
+                    .first::<Option<i32>>(&connection)
+                    .optional()?
+                    .and_then(|blob_id| blob_id)
+                    .map(|blob_id| (LinkKind::Blob, blob_id)),
+                _ => Err("`to` must be a message, author or blob id")?
+            };
+
+            match to_id {
+                Some(to_id) => filter.to = Some(to_id),
+                None => return Ok(LinkConnection::default()),
+            }
+        }
+
+        filter.rel = rel;
+
+        let page = Page::new(&before, &after, last, first, next, decode_link_cursor)?;
+
+        let load = |limit| -> FieldResult<_> {
+            let links = match page {
+                Page::Last(position, _) => get_links(&connection, &filter, &LinkPage::Before(position, limit), false)?,
+                Page::First(position, _) => get_links(&connection, &filter, &LinkPage::After(position, limit), false)?,
+            };
+            Ok(links)
+        };
+
+        let has_other = || -> FieldResult<_> {
+            let has_other = match page {
+                Page::Last(Some(position), _) => {
+                    !get_links(&connection, &filter, &LinkPage::After(Some(position), 1), true)?.is_empty()
+                },
+                Page::First(Some(position), _) => {
+                    !get_links(&connection, &filter, &LinkPage::Before(Some(position), 1), true)?.is_empty()
+                },
+                _ => false,
+            };
+            Ok(has_other)
+        };
+
+        let (links_and_cursor, page_info, total_count) = load_page(
+            &page,
+            load,
+            has_other,
+            || Ok(count_links(&connection, &filter)?),
+            encode_link_cursor,
+        )?;
+
+        Ok(LinkConnection{
+            next,
+            total_count: total_count as i32,
+            page_info,
+            links_and_cursor
+        })
     }
 });
 
-fn get_start_cursor(results: &[(i32, i64, i64)], order_by: &OrderBy) -> Option<String> {
-    return match order_by {
-        OrderBy::Asserted => results
-            .first()
-            .map(|(_, _, timestamp)| *timestamp)
-            .map(encode_cursor),
-        _ => results.first().map(|(_, seq, _)| *seq).map(encode_cursor),
-    };
+fn encode_link_cursor(link: &DbLink) -> String {
+    let (seq, kind, id) = link.position();
+    encode_compound_cursor(&[seq, kind as i64, i64::from(id)])
 }
 
-fn get_end_cursor(results: &[(i32, i64, i64)], order_by: &OrderBy) -> Option<String> {
-    return match order_by {
-        OrderBy::Asserted => results
-            .last()
-            .map(|(_, _, timestamp)| *timestamp)
-            .map(encode_cursor),
-        _ => results.last().map(|(_, seq, _)| *seq).map(encode_cursor),
-    };
+fn decode_link_cursor(cursor: &str) -> Result<LinkPosition, String> {
+    match decode_cursor_parts(cursor)?.as_slice() {
+        [seq, kind, id] => Ok((*seq, LinkKind::from(*kind), *id as i32)),
+        _ => Err("Error decoding cursor. Is it a link cursor?".to_string()),
+    }
 }
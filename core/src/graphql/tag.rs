@@ -0,0 +1,46 @@
+use super::author::Author;
+use crate::db::models::abouts::get_key_detail;
+use crate::db::models::tags::get_tag_author;
+use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
+use crate::db::Context;
+use diesel::prelude::*;
+use juniper::FieldResult;
+use serde_json::Value;
+
+pub struct Tag {
+    pub key_id: i32,
+    pub cursor: Option<String>,
+}
+
+graphql_object!(Tag: Context |&self| {
+    description: "A tag that authors can apply to posts, eg. to bookmark them."
+
+    /// The id of the message that created the tag.
+    field id(&executor) -> FieldResult<String> {
+        let connection = executor.context().connection.get()?;
+        let key = keys_table
+            .select(keys_key)
+            .filter(keys_id.eq(self.key_id))
+            .first::<String>(&connection)?;
+
+        Ok(key)
+    }
+    /// The author that created the tag. Only they can name it.
+    field author(&executor) -> FieldResult<Option<Author>> {
+        let connection = executor.context().connection.get()?;
+        let author = get_tag_author(&connection, self.key_id)?
+            .map(|author_id| Author{author_id});
+
+        Ok(author)
+    }
+    /// The name of the tag, from the latest about its author posted.
+    field name(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let name = match get_key_detail(&connection, self.key_id, "name")? {
+            Some(Value::String(name)) => Some(name),
+            _ => None,
+        };
+
+        Ok(name)
+    }
+});
@@ -0,0 +1,58 @@
+use super::page_info::PageInfo;
+use super::tag::Tag;
+use crate::db::Context;
+use juniper::FieldResult;
+
+#[derive(Default)]
+pub struct TagConnection {
+    pub next: i32,
+    pub total_count: i32,
+    pub page_info: PageInfo,
+    pub tag_keys_and_cursor: Vec<(i32, String)>,
+}
+
+graphql_object!(TagConnection: Context |&self| {
+    description: "Connection to collections of tags"
+
+    /// The total count of tags that match the query, across all pages.
+    field total_count(&executor) -> i32 {
+        self.total_count
+    }
+    /// The nodes in this connection
+    field edges(&executor) -> Vec<TagEdge>{
+        self.tag_keys_and_cursor
+            .iter()
+            .map(|(key_id, cursor)|{
+                Tag{key_id: *key_id, cursor: Some(cursor.to_owned())}
+            })
+            .map(|tag|{
+                TagEdge{
+                    node: tag
+                }
+            })
+            .collect::<Vec<TagEdge>>()
+    }
+
+    /// The relay-spec pageInfo for this connection
+    field page_info(&executor) -> &PageInfo{
+        &self.page_info
+    }
+});
+
+pub struct TagEdge {
+    pub node: Tag,
+}
+
+graphql_object!(TagEdge: Context |&self| {
+    description: "Edge connection to a tag"
+
+    /// The nodes in this connection
+    field node(&executor) -> &Tag {
+        &self.node
+    }
+
+    /// The cursor for this node
+    field cursor(&executor) -> FieldResult<Option<String>> {
+        Ok(self.node.cursor.clone())
+    }
+});
@@ -1,18 +1,34 @@
+use super::author::Author;
+use super::input_objects::OrderBy;
+use super::page_info::*;
 use super::post::*;
+use super::post_connection::PostConnection;
+use super::reply_tree::ReplyTreeNode;
+use crate::db::local_state::unread_key_ids;
+use crate::db::models::abouts::get_key_detail;
+use crate::db::models::branches::{build_reply_tree, get_thread_branches};
+use crate::db::models::recipients::get_recipients;
 use crate::db::schema::keys::dsl::{id as keys_id, key as keys_key, keys as keys_table};
 use crate::db::schema::messages::dsl::{
-    content as messages_content, content_type as messages_content_type, key_id as messages_key_id,
-    messages as messages_table, root_key_id as messages_root_key_id,
+    asserted_time as messages_asserted_time, author_id as messages_author_id,
+    content as messages_content, content_type as messages_content_type,
+    flume_seq as messages_flume_seq, is_decrypted as messages_is_decrypted,
+    key_id as messages_key_id, messages as messages_table, root_key_id as messages_root_key_id,
 };
 use crate::db::Context;
+use diesel::expression::dsl::max;
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable};
 use juniper::FieldResult;
 use ssb_causal_sort::causal_sort;
 use ssb_multiformats::multihash::Multihash;
+use std::collections::HashMap;
 
 pub struct Thread {
     pub root: Post,
     pub cursor: String,
+    /// How to order the replies when `replies` isn't given an `order_by`. `None` is causal order.
+    pub reply_order_by: Option<OrderBy>,
 }
 
 graphql_object!(Thread: Context |&self| {
@@ -22,39 +38,279 @@ graphql_object!(Thread: Context |&self| {
     field root(&executor) -> &Post {
         &self.root
     }
+    /// The title the root's author gave the thread with an about message, if any.
+    field title(&executor) -> FieldResult<Option<String>> {
+        let connection = executor.context().connection.get()?;
+        let title = get_key_detail(&connection, self.root.key_id, "title")?
+            .and_then(|title| title.as_str().map(|title| title.to_owned()));
+
+        Ok(title)
+    }
     /// The reply posts.
-    /// The replies are sorted by causal ordering based on which messages reference other messages.
-    field replies(&executor) -> FieldResult<Vec<Post>>{
+    ///
+    /// By default the replies are sorted by causal ordering based on which messages reference
+    /// other messages, oldest first. Pass `order_by` to order them by time instead.
+    ///
+    /// Note that when not passing any options for `before`, `after`, `first` and `last`, the
+    /// default is to give you the oldest replies with a default `first` value of 10 replies.
+    field replies(
+        &executor,
+        /// Use a cursor string to get results before the cursor (backwards pagination, newest
+        /// first)
+        before: Option<String>,
+        /// Use a cursor string to get results after the cursor (forwards pagination, oldest first)
+        after: Option<String>,
+        /// Limit the number or results to get when using `before`.
+        last = (None): Option<i32>,
+        /// Limit the number or results to get when using `after`.
+        first = (None): Option<i32>,
+        /// Order replies by asserted time or received time instead of causal order.
+        order_by: Option<OrderBy>,
+    ) -> FieldResult<PostConnection> {
+        let next = 10;
+
+        let connection = executor.context().connection.get()?;
+
+        // Threads are read from the start, unlike most connections.
+        let first = match (&before, &after, last, first) {
+            (None, None, None, None) => Some(next),
+            _ => first,
+        };
+
+        let page = Page::new(&before, &after, last, first, next, decode_order_cursor)?;
+
+        match order_by.or_else(|| self.reply_order_by.clone()) {
+            None => self.causal_replies(&connection, page, next),
+            Some(order_by) => self.ordered_replies(&connection, page, next, order_by),
+        }
+    }
+    /// The number of replies in the thread.
+    field reply_count(&executor) -> FieldResult<i32> {
+        let connection = executor.context().connection.get()?;
+
+        let count = messages_table
+            .filter(messages_root_key_id.eq(self.root.key_id))
+            .filter(messages_content_type.eq("post"))
+            .count()
+            .get_result::<i64>(&connection)?;
+
+        Ok(count as i32)
+    }
+    /// The number of posts in the thread, including the root, that haven't been marked as read.
+    field unread_count(&executor) -> FieldResult<i32> {
+        let connection = executor.context().connection.get()?;
+
+        let count = messages_table
+            .filter(
+                messages_key_id.eq(self.root.key_id)
+                    .or(messages_root_key_id.eq(self.root.key_id).and(messages_content_type.eq("post")))
+            )
+            .filter(messages_key_id.nullable().eq_any(unread_key_ids()))
+            .count()
+            .get_result::<i64>(&connection)?;
+
+        Ok(count as i32)
+    }
+    /// Everyone who has posted in the thread, starting with the author of the root, then in the
+    /// order they first replied.
+    field participants(&executor) -> FieldResult<Vec<Author>> {
         let connection = executor.context().connection.get()?;
 
+        let root_author_id = messages_table
+            .select(messages_author_id)
+            .filter(messages_key_id.eq(self.root.key_id))
+            .first::<i32>(&connection)
+            .optional()?;
+
+        let reply_author_ids = messages_table
+            .select(messages_author_id)
+            .filter(messages_root_key_id.eq(self.root.key_id))
+            .filter(messages_content_type.eq("post"))
+            .order(messages_flume_seq.asc())
+            .load::<i32>(&connection)?;
+
+        let mut author_ids: Vec<i32> = Vec::new();
+        root_author_id
+            .into_iter()
+            .chain(reply_author_ids)
+            .for_each(|author_id| {
+                if !author_ids.contains(&author_id) {
+                    author_ids.push(author_id);
+                }
+            });
+
+        let participants = author_ids
+            .into_iter()
+            .map(|author_id| Author{author_id})
+            .collect();
+
+        Ok(participants)
+    }
+    /// The asserted timestamp of the newest reply, or null if there are no replies yet.
+    field last_reply_at(&executor) -> FieldResult<Option<f64>> {
+        let connection = executor.context().connection.get()?;
+
+        let time = messages_table
+            .select(max(messages_asserted_time))
+            .filter(messages_root_key_id.eq(self.root.key_id))
+            .filter(messages_content_type.eq("post"))
+            .first::<Option<i64>>(&connection)?
+            .map(|time| time as f64);
+
+        Ok(time)
+    }
+    /// The replies nested by their branches, so each reply is a child of the message it replies
+    /// to. Replies that branch from the root, or only from messages we don't have, are at the
+    /// top. Siblings are in causal order.
+    field reply_tree(&executor) -> FieldResult<Vec<ReplyTreeNode>> {
+        let connection = executor.context().connection.get()?;
+
+        let replies = self
+            .causally_sorted_replies(&connection)?
+            .into_iter()
+            .map(|(key_id, _)| key_id)
+            .collect::<Vec<i32>>();
+        let branches = get_thread_branches(&connection, self.root.key_id)?;
+
+        let nodes = build_reply_tree(&replies, &branches)
+            .into_iter()
+            .map(|tree| ReplyTreeNode{tree, depth: 0})
+            .collect();
+
+        Ok(nodes)
+    }
+    /// Whether or not the messages are encrypted.
+    field is_private(&executor) -> FieldResult<bool> {
+        let connection = executor.context().connection.get()?;
+
+        let is_decrypted = messages_table
+            .select(messages_is_decrypted)
+            .filter(messages_key_id.eq(self.root.key_id))
+            .first::<bool>(&connection)
+            .optional()?;
+
+        Ok(is_decrypted.unwrap_or(false))
+    }
+    /// The authors the root was sent to, if the thread is private. Empty for public threads.
+    field recipients(&executor) -> FieldResult<Vec<Author>> {
+        let connection = executor.context().connection.get()?;
+
+        let authors = get_recipients(&connection, self.root.key_id)?
+            .into_iter()
+            .map(|author_id| Author{author_id})
+            .collect();
+
+        Ok(authors)
+    }
+});
+
+impl Thread {
+    /// The key ids and flume seqs of all the replies, in causal order, oldest first.
+    fn causally_sorted_replies(
+        &self,
+        connection: &SqliteConnection,
+    ) -> Result<Vec<(i32, i64)>, diesel::result::Error> {
         // causal sort wants a collection of (multihash, key_id, bytes)
         let replies = messages_table
             .inner_join(keys_table.on(messages_key_id.nullable().eq(keys_id)))
-            .select((messages_content, messages_key_id,  keys_key))
+            .select((
+                messages_content,
+                messages_key_id,
+                keys_key,
+                messages_flume_seq,
+            ))
             .filter(messages_root_key_id.eq(self.root.key_id))
             .filter(messages_content_type.eq("post"))
-            .load::<(Option<String>, i32, String)>(&connection)
-            .into_iter()
-            .flatten()
-            .filter_map(|(content_string, message_id, key_string)|{
-                match Multihash::from_legacy(key_string.as_bytes()){
-                    Ok((key, _)) => Some((key, message_id, content_string.unwrap_or("{}".into()))),
-                    _ => None
+            .load::<(Option<String>, i32, String, Option<i64>)>(connection)?;
 
+        let seqs = replies
+            .iter()
+            .map(|(_, key_id, _, seq)| (*key_id, seq.unwrap_or(0)))
+            .collect::<HashMap<i32, i64>>();
+
+        let sortable = replies
+            .into_iter()
+            .filter_map(|(content_string, key_id, key_string, _)| {
+                match Multihash::from_legacy(key_string.as_bytes()) {
+                    Ok((key, _)) => {
+                        Some((key, key_id, content_string.unwrap_or_else(|| "{}".into())))
+                    }
+                    _ => None,
                 }
             })
             .collect::<Vec<_>>();
 
-        let sorted_reply_keys = causal_sort(replies.as_slice());
-
-        let sorted_replies = sorted_reply_keys
+        // Oldest first.
+        let sorted = causal_sort(sortable.as_slice())
             .into_iter()
-            .map(|key_id| Post{key_id, cursor: None})
             .rev()
-            .collect();
+            .filter_map(|key_id| seqs.get(&key_id).map(|seq| (key_id, *seq)))
+            .collect::<Vec<(i32, i64)>>();
 
-        Ok(sorted_replies)
+        Ok(sorted)
     }
-    /// Whether or not the messages are encrypted.
-    field is_private() -> bool {false}
-});
+
+    /// A page of the replies in causal order. The whole thread has to be sorted to find the page,
+    /// but only the page is sent. The cursor is the flume seq of a reply.
+    fn causal_replies(
+        &self,
+        connection: &SqliteConnection,
+        page: Page<OrderPosition>,
+        next: i32,
+    ) -> FieldResult<PostConnection> {
+        let sorted = self.causally_sorted_replies(connection)?;
+        PostConnection::from_sorted(
+            &sorted,
+            page,
+            next,
+            "Cursor doesn't match a reply in this thread",
+        )
+    }
+
+    /// A page of the replies ordered by time, paginated in the db like `posts`.
+    fn ordered_replies(
+        &self,
+        connection: &SqliteConnection,
+        page: Page<OrderPosition>,
+        next: i32,
+        order_by: OrderBy,
+    ) -> FieldResult<PostConnection> {
+        // Only threads have activity.
+        let order_by = match order_by {
+            OrderBy::LastActivity => OrderBy::Received,
+            order_by => order_by,
+        };
+
+        let filtered_query = || {
+            messages_table
+                .select((messages_key_id, messages_flume_seq, messages_asserted_time))
+                .filter(messages_root_key_id.eq(self.root.key_id))
+                .filter(messages_content_type.eq("post"))
+                .into_boxed()
+        };
+
+        let ordering = || -> Box<dyn BoxableExpression<_, _, SqlType = Nullable<BigInt>>> {
+            match order_by {
+                OrderBy::Asserted => Box::new(messages_asserted_time),
+                _ => Box::new(messages_flume_seq),
+            }
+        };
+
+        let (post_keys_and_cursor, page_info, total_count) = load_ordered_page(
+            connection,
+            &page,
+            &order_by,
+            || Ok(filtered_query()),
+            ordering,
+            || Box::new(messages_flume_seq),
+            || Ok(filtered_query().count().get_result::<i64>(connection)?),
+        )?;
+
+        Ok(PostConnection {
+            next,
+            total_count: total_count as i32,
+            page_info,
+            post_keys_and_cursor,
+        })
+    }
+}
@@ -6,6 +6,7 @@ use juniper::FieldResult;
 
 pub struct ThreadConnection {
     pub next: i32,
+    pub total_count: i32,
     pub page_info: PageInfo,
     pub thread_keys_and_cursor: Vec<(i32, String)>,
 }
@@ -18,7 +19,7 @@ graphql_object!(ThreadConnection: Context |&self| {
         self.thread_keys_and_cursor
             .iter()
             .map(|(key_id, cursor)|{
-                Thread{root: Post{key_id: *key_id, cursor: None}, cursor: cursor.to_owned()}
+                Thread{root: Post{key_id: *key_id, cursor: None}, cursor: cursor.to_owned(), reply_order_by: None}
             })
             .map(|thread|{
                 ThreadEdge{
@@ -33,9 +34,9 @@ graphql_object!(ThreadConnection: Context |&self| {
         &self.page_info
     }
 
-    /// The total count of posts in this connection.
+    /// The total count of threads that match the query, across all pages.
     field total_count(&executor) -> i32 {
-        self.thread_keys_and_cursor.len() as i32
+        self.total_count
     }
 });
 
@@ -1,3 +1,6 @@
+// The diesel and juniper derives and macros expand to impls inside functions.
+#![allow(non_local_definitions)]
+
 #[macro_use]
 extern crate diesel;
 extern crate dotenv;
@@ -17,14 +20,16 @@ extern crate serde_json;
 mod cursor;
 pub mod db;
 pub mod graphql;
-mod ssb_message;
+pub mod ssb_message;
 pub mod utils;
 
+pub use db::indexer::MessageIndexer;
+
 use db::Context;
 use graphql::db::DbMutation;
 use graphql::root::*;
 use juniper::http::GraphQLRequest;
-use juniper::RootNode;
+use juniper::{GraphQLType, RootNode};
 use serde_json::Error;
 
 #[derive(Clone)]
@@ -39,15 +44,50 @@ impl Patchql {
         pub_key: String,
         secret_key: String,
     ) -> Patchql {
-        let context = Context::new(offset_log_path, database_path, pub_key, secret_key);
+        Patchql::with_indexers(
+            offset_log_path,
+            database_path,
+            pub_key,
+            secret_key,
+            Vec::new(),
+        )
+    }
+    /// Like `new`, but also runs the indexers on every message that's processed.
+    pub fn with_indexers(
+        offset_log_path: String,
+        database_path: String,
+        pub_key: String,
+        secret_key: String,
+        indexers: Vec<Box<dyn MessageIndexer>>,
+    ) -> Patchql {
+        let context = Context::with_indexers(
+            offset_log_path,
+            database_path,
+            pub_key,
+            secret_key,
+            indexers,
+        );
 
         Patchql { context }
     }
     pub fn query(&self, query_string: &str) -> Result<String, Error> {
+        let root_node = RootNode::new(Query, DbMutation::default());
+        self.execute(query_string, &root_node)
+    }
+    /// Runs a query against a custom schema, eg. one with extra fields that read the tables of
+    /// your own indexers.
+    pub fn execute<Q, M>(
+        &self,
+        query_string: &str,
+        root_node: &RootNode<Q, M>,
+    ) -> Result<String, Error>
+    where
+        Q: GraphQLType<Context = Context>,
+        M: GraphQLType<Context = Context>,
+    {
         let request: GraphQLRequest = serde_json::from_str(query_string)?;
 
-        let root_node = RootNode::new(Query, DbMutation::default());
-        let response = request.execute(&root_node, &self.context);
+        let response = request.execute(root_node, &self.context);
         serde_json::to_string(&response)
     }
 }
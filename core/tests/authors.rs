@@ -0,0 +1,52 @@
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+
+#[test]
+fn search_authors_by_name() {
+    let offset_log_path = "./misc/out.offset".to_owned();
+    let db_path = "/tmp/search_authors_by_name.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+    let response = patchql.query(AUTHORS_MATCHING_PI).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    assert_eq!(
+        jsn["data"]["authors"],
+        serde_json::json!([{
+            "id": "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519",
+            "name": "Piet"
+        }])
+    );
+}
+
+#[test]
+fn contact_status_with_unknown_author() {
+    let offset_log_path = "./misc/out.offset".to_owned();
+    let db_path = "/tmp/contact_status_with_unknown_author.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+    let response = patchql.query(CONTACT_STATUS_WITH_UNKNOWN).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let neutral = serde_json::json!({"public": "NEUTRAL", "private": null});
+    assert_eq!(jsn["data"]["author"]["contactStatusTo"], neutral);
+    assert_eq!(jsn["data"]["author"]["contactStatusFrom"], neutral);
+}
+
+const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
+
+const AUTHORS_MATCHING_PI: &str = r##"
+{"query":"{\n  authors(query: \"pi\"){\n    id\n    name\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const CONTACT_STATUS_WITH_UNKNOWN: &str = r##"
+{"query":"{\n  author(id: \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\"){\n    contactStatusTo(otherAuthor: \"@nope.ed25519\"){\n      public\n      private\n    }\n    contactStatusFrom(otherAuthor: \"@nope.ed25519\"){\n      public\n      private\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
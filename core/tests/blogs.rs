@@ -0,0 +1,67 @@
+mod common;
+
+use serde_json::Value;
+
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+
+fn query(query: &str) -> Value {
+    common::query("blogs", "", query)
+}
+
+fn titles(blogs: &Value) -> Vec<&str> {
+    blogs["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["title"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn blogs() {
+    let jsn = query(&BLOGS.replace("ARGS", ""));
+    let blogs = &jsn["data"]["blogs"];
+    assert_eq!(blogs["totalCount"], 2);
+    // Only the author can retitle their blog.
+    assert_eq!(titles(blogs), vec!["Soup season", "Why sqlite"]);
+
+    let soup = &blogs["edges"][0]["node"];
+    assert_eq!(soup["author"]["id"], KATIE);
+    assert_eq!(soup["summary"], "Recipes for winter");
+    assert_eq!(soup["thumbnailLink"], "&soupthumb.sha256");
+    assert_eq!(soup["blogLink"], "&soupbody.sha256");
+    assert_eq!(soup["comments"]["totalCount"], 0);
+
+    let sql = &blogs["edges"][1]["node"];
+    assert_eq!(sql["thumbnailLink"], "&sqlthumb.sha256");
+    assert_eq!(sql["comments"]["totalCount"], 2);
+    assert_eq!(sql["comments"]["edges"][0]["node"]["text"], "Agreed");
+}
+
+#[test]
+fn blogs_by_author() {
+    let jsn = query(&BLOGS.replace("ARGS", &format!("(authors: [\\\"{}\\\"])", PIET)));
+    assert_eq!(titles(&jsn["data"]["blogs"]), vec!["Why sqlite"]);
+}
+
+#[test]
+fn paginate_blogs() {
+    let jsn = query(&BLOGS.replace("ARGS", "(first: 1)"));
+    let blogs = &jsn["data"]["blogs"];
+    assert_eq!(titles(blogs), vec!["Why sqlite"]);
+    assert_eq!(blogs["pageInfo"]["hasNextPage"], true);
+
+    let end_cursor = blogs["pageInfo"]["endCursor"].as_str().unwrap();
+    let jsn = query(&BLOGS.replace(
+        "ARGS",
+        &format!("(first: 1, after: \\\"{}\\\")", end_cursor),
+    ));
+    let blogs = &jsn["data"]["blogs"];
+    assert_eq!(titles(blogs), vec!["Soup season"]);
+    assert_eq!(blogs["pageInfo"]["hasNextPage"], false);
+}
+
+const BLOGS: &str = r##"
+{"query":"{\n  blogs ARGS {\n    totalCount\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    edges {\n      node {\n        id\n        author {\n          id\n        }\n        title\n        summary\n        thumbnailLink\n        blogLink\n        comments {\n          totalCount\n          edges {\n            node {\n              text\n            }\n          }\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,159 @@
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+
+fn patchql(db_path: &str) -> Patchql {
+    let offset_log_path = "./misc/out.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        db_path.to_owned(),
+        "".to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+    patchql
+}
+
+#[test]
+fn posts_by_channel() {
+    let patchql = patchql("/tmp/posts_by_channel.sqlite");
+
+    let response = patchql.query(POSTS_IN_PROTOZOA).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let edges = jsn["data"]["posts"]["edges"].as_array().unwrap();
+
+    assert_eq!(edges.len(), 7);
+    assert!(edges.iter().all(|edge| edge["node"]["channels"]
+        .as_array()
+        .unwrap()
+        .contains(&Value::from("protozoa"))));
+}
+
+#[test]
+fn posts_by_hashtag() {
+    let patchql = patchql("/tmp/posts_by_hashtag.sqlite");
+
+    let response = patchql.query(POSTS_IN_TABLEFLIP).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let channels = &jsn["data"]["posts"]["edges"][0]["node"]["channels"];
+
+    assert_eq!(channels, &serde_json::json!(["new-people", "tableflip"]));
+}
+
+#[test]
+fn threads_by_channel() {
+    let patchql = patchql("/tmp/threads_by_channel.sqlite");
+
+    let response = patchql.query(THREADS_IN_PROTOZOA).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let edges = jsn["data"]["threads"]["edges"].as_array().unwrap();
+
+    assert_eq!(edges.len(), 2);
+}
+
+#[test]
+fn author_subscribed_channels() {
+    let patchql = patchql("/tmp/author_subscribed_channels.sqlite");
+
+    let response = patchql.query(SUBSCRIBED_CHANNELS).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let channels = jsn["data"]["author"]["subscribedChannels"]
+        .as_array()
+        .unwrap();
+
+    assert_eq!(channels.len(), 23);
+    assert_eq!(channels[0]["name"], "android");
+    assert!(channels.contains(&serde_json::json!({"name": "lgbtq"})));
+}
+
+fn channels(connection: &Value) -> Vec<Value> {
+    connection["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"].clone())
+        .collect()
+}
+
+#[test]
+fn channels_by_popularity() {
+    let patchql = patchql("/tmp/channels_by_popularity.sqlite");
+
+    let response = patchql
+        .query(&CHANNELS.replace("ARGS", "(last: 1000)"))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let channels = channels(&jsn["data"]["channels"]);
+
+    assert_eq!(jsn["data"]["channels"]["totalCount"], channels.len());
+    let counts = channels
+        .iter()
+        .map(|channel| channel["subscriberCount"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert!(counts.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert_eq!(counts[0], 1);
+    assert_eq!(counts.last(), Some(&0));
+
+    let popular = channels
+        .iter()
+        .take_while(|channel| channel["subscriberCount"] == 1)
+        .collect::<Vec<_>>();
+    assert_eq!(popular.len(), 23);
+    assert!(popular.contains(&&serde_json::json!({"name": "android", "subscriberCount": 1})));
+}
+
+#[test]
+fn channels_before_cursor() {
+    let patchql = patchql("/tmp/channels_before_cursor.sqlite");
+
+    let response = patchql
+        .query(&CHANNELS.replace("ARGS", "(last: 4)"))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let all = channels(&jsn["data"]["channels"]);
+
+    let response = patchql
+        .query(&CHANNELS.replace("ARGS", "(last: 2)"))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let mut paged = channels(&jsn["data"]["channels"]);
+    let page_info = &jsn["data"]["channels"]["pageInfo"];
+    assert_eq!(page_info["hasPreviousPage"], true);
+
+    let args = format!(
+        "(last: 2, before: \\\"{}\\\")",
+        page_info["endCursor"].as_str().unwrap()
+    );
+    let response = patchql.query(&CHANNELS.replace("ARGS", &args)).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    paged.extend(channels(&jsn["data"]["channels"]));
+
+    assert_eq!(paged, all);
+}
+
+const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
+
+const POSTS_IN_PROTOZOA: &str = r##"
+{"query":"{\n  posts(last: 20, channels: [\"#Protozoa\"]){\n    edges{\n      node{\n        id\n        channels\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const POSTS_IN_TABLEFLIP: &str = r##"
+{"query":"{\n  posts(channels: [\"tableflip\"]){\n    edges{\n      node{\n        id\n        channels\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_IN_PROTOZOA: &str = r##"
+{"query":"{\n  threads(last: 20, channels: [\"protozoa\"]){\n    edges{\n      node{\n        root{\n          id\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const SUBSCRIBED_CHANNELS: &str = r##"
+{"query":"{\n  author(id: \"@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519\"){\n    subscribedChannels{\n      name\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const CHANNELS: &str = r##"
+{"query":"{\n  channels ARGS {\n    totalCount\n    pageInfo {\n      hasPreviousPage\n      endCursor\n    }\n    edges {\n      node {\n        name\n        subscriberCount\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+use std::thread;
+
+/// A db path for processing an offset log from `misc` into.
+///
+/// Each test gets its own db, named after the test, so tests running in parallel don't share
+/// one. Whatever an earlier run left there, including the local db, is deleted first.
+pub fn db_path(offset_log: &str) -> String {
+    let test_name = thread::current()
+        .name()
+        .unwrap_or("test")
+        .replace("::", "_");
+    let db_path = format!("/tmp/{}_{}.sqlite", offset_log, test_name);
+    remove_db(&db_path);
+    remove_db(&format!("{}.local", db_path));
+    db_path
+}
+
+/// Deletes a db along with its wal files.
+pub fn remove_db(db_path: &str) {
+    for suffix in &["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", db_path, suffix));
+    }
+}
+
+/// Opens the db at `db_path` and processes an offset log from `misc` into it.
+pub fn open(offset_log: &str, db_path: &str, pub_key: &str) -> Patchql {
+    let offset_log_path = format!("./misc/{}.offset", offset_log);
+    let patchql = Patchql::new(
+        offset_log_path,
+        db_path.to_owned(),
+        pub_key.to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+    patchql
+}
+
+/// Runs a query and parses the response.
+pub fn run(patchql: &Patchql, query: &str) -> Value {
+    let response = patchql.query(query).unwrap();
+    from_str(&response).unwrap()
+}
+
+/// Processes an offset log from `misc` into a new db and runs a query against it.
+pub fn query(offset_log: &str, pub_key: &str, query: &str) -> Value {
+    let patchql = open(offset_log, &db_path(offset_log), pub_key);
+    run(&patchql, query)
+}
+
+pub const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
@@ -0,0 +1,89 @@
+mod common;
+
+use serde_json::Value;
+
+fn query(query: &str) -> Value {
+    common::query("content_warnings", "", query)
+}
+
+fn texts(connection: &Value, path: &[&str]) -> Vec<String> {
+    connection["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| {
+            path.iter()
+                .fold(&edge["node"], |node, key| &node[key])
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn posts_with_content_warnings() {
+    let jsn = query(&POSTS.replace("FILTER", "INCLUDE"));
+    let posts = &jsn["data"]["posts"];
+    assert_eq!(posts["edges"].as_array().unwrap().len(), 4);
+
+    let jsn = query(&POSTS.replace("FILTER", "ONLY"));
+    let posts = &jsn["data"]["posts"];
+    assert_eq!(
+        texts(posts, &["text"]),
+        vec!["The finale twist was that it was all a dream"]
+    );
+    assert_eq!(
+        posts["edges"][0]["node"]["contentWarning"],
+        "spoilers for the finale"
+    );
+
+    // An empty content warning isn't a warning.
+    let jsn = query(&POSTS.replace("FILTER", "EXCLUDE"));
+    let posts = &jsn["data"]["posts"];
+    assert_eq!(posts["edges"].as_array().unwrap().len(), 3);
+    assert!(posts["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|edge| edge["node"]["contentWarning"].is_null()));
+}
+
+#[test]
+fn search_without_content_warnings() {
+    let jsn = query(SEARCH);
+    assert_eq!(
+        texts(&jsn["data"]["posts"], &["text"]),
+        vec!["What did everyone think of the finale?"]
+    );
+}
+
+#[test]
+fn threads_with_content_warnings() {
+    let jsn = query(&THREADS.replace("FILTER", "EXCLUDE"));
+    assert_eq!(
+        texts(&jsn["data"]["threads"], &["root", "text"]),
+        vec![
+            "Nothing to warn about",
+            "What did everyone think of the finale?"
+        ]
+    );
+
+    let jsn = query(&THREADS.replace("FILTER", "ONLY"));
+    assert_eq!(
+        texts(&jsn["data"]["threads"], &["root", "text"]),
+        vec!["The finale twist was that it was all a dream"]
+    );
+}
+
+const POSTS: &str = r##"
+{"query":"{\n  posts(contentWarning: FILTER){\n    edges{\n      node{\n        text\n        contentWarning\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const SEARCH: &str = r##"
+{"query":"{\n  posts(query: \"finale\", contentWarning: EXCLUDE){\n    edges{\n      node{\n        text\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS: &str = r##"
+{"query":"{\n  threads(contentWarning: FILTER){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
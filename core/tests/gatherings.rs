@@ -0,0 +1,89 @@
+mod common;
+
+use serde_json::Value;
+
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+
+fn query(query: &str) -> Value {
+    common::query("gatherings", PIET, query)
+}
+
+fn titles(jsn: &Value) -> Vec<&str> {
+    jsn["data"]["gatherings"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["title"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn gatherings_in_start_order() {
+    let jsn = query(&GATHERINGS.replace("ARGS", ""));
+    assert_eq!(
+        titles(&jsn),
+        vec!["Scuttlebutt workshop", "Picnic", "Someday"]
+    );
+
+    let picnic = &jsn["data"]["gatherings"]["edges"][1]["node"];
+    assert_eq!(picnic["author"]["id"], PIET);
+    assert_eq!(picnic["description"], "Bring snacks");
+    assert_eq!(picnic["location"], "The beach");
+    assert_eq!(picnic["startTime"], 4102444800000.0);
+    assert_eq!(picnic["imageLink"], "&picnic.sha256");
+    assert_eq!(
+        picnic["attendees"],
+        serde_json::json!([{"id": PIET}, {"id": KATIE}])
+    );
+    assert_eq!(picnic["isAttending"], true);
+
+    let workshop = &jsn["data"]["gatherings"]["edges"][0]["node"];
+    assert_eq!(workshop["attendees"], serde_json::json!([{"id": KATIE}]));
+    assert_eq!(workshop["isAttending"], false);
+    assert_eq!(
+        jsn["data"]["gatherings"]["edges"][2]["node"]["startTime"],
+        Value::Null
+    );
+    assert_eq!(jsn["data"]["gatherings"]["totalCount"], 3);
+}
+
+#[test]
+fn upcoming_gatherings() {
+    let jsn = query(&GATHERINGS.replace("ARGS", "(upcoming: true)"));
+    assert_eq!(titles(&jsn), vec!["Picnic"]);
+
+    let jsn = query(&GATHERINGS.replace("ARGS", "(upcoming: false)"));
+    assert_eq!(titles(&jsn), vec!["Scuttlebutt workshop"]);
+}
+
+#[test]
+fn gatherings_im_attending() {
+    let jsn = query(&GATHERINGS.replace("ARGS", "(attending: true)"));
+    assert_eq!(titles(&jsn), vec!["Picnic"]);
+
+    let jsn = query(&GATHERINGS.replace("ARGS", "(attending: false)"));
+    assert_eq!(titles(&jsn), vec!["Scuttlebutt workshop", "Someday"]);
+}
+
+#[test]
+fn gatherings_after_cursor() {
+    let jsn = query(&GATHERINGS.replace("ARGS", "(first: 2)"));
+    assert_eq!(titles(&jsn), vec!["Scuttlebutt workshop", "Picnic"]);
+    let page_info = &jsn["data"]["gatherings"]["pageInfo"];
+    assert_eq!(page_info["hasNextPage"], true);
+
+    let args = format!(
+        "(first: 2, after: \\\"{}\\\")",
+        page_info["endCursor"].as_str().unwrap()
+    );
+    let jsn = query(&GATHERINGS.replace("ARGS", &args));
+    assert_eq!(titles(&jsn), vec!["Someday"]);
+    let page_info = &jsn["data"]["gatherings"]["pageInfo"];
+    assert_eq!(page_info["hasNextPage"], false);
+    assert_eq!(page_info["hasPreviousPage"], true);
+}
+
+const GATHERINGS: &str = r##"
+{"query":"{\n  gatherings ARGS {\n    totalCount\n    pageInfo {\n      hasNextPage\n      hasPreviousPage\n      endCursor\n    }\n    edges {\n      node {\n        id\n        author {\n          id\n        }\n        title\n        description\n        location\n        startTime\n        imageLink\n        attendees {\n          id\n        }\n        isAttending\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
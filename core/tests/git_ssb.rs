@@ -0,0 +1,147 @@
+mod common;
+
+use serde_json::{json, Value};
+
+const REPO: &str = "%eQU1p7VnTh0pRRTpPj1Qf2peKu0Rr46GRtxITKvO+rc=.sha256";
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+
+fn query(query: &str) -> Value {
+    common::query("git_ssb", "", query)
+}
+
+fn nodes(connection: &Value) -> Vec<Value> {
+    connection["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"].clone())
+        .collect()
+}
+
+#[test]
+fn git_repos() {
+    let jsn = query(&GIT_REPOS.replace("ARGS", ""));
+    assert_eq!(jsn["data"]["gitRepos"]["totalCount"], 2);
+    let repos = nodes(&jsn["data"]["gitRepos"]);
+    assert_eq!(repos[0]["name"], "patchql-fork");
+    assert_eq!(repos[0]["author"]["id"], KATIE);
+    assert_eq!(repos[1]["id"], REPO);
+    assert_eq!(repos[1]["name"], "ssb-patchql");
+    assert_eq!(repos[1]["author"]["id"], PIET);
+}
+
+#[test]
+fn git_repos_after_cursor() {
+    let jsn = query(&GIT_REPOS.replace("ARGS", "(first: 1)"));
+    let repos = nodes(&jsn["data"]["gitRepos"]);
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0]["name"], "ssb-patchql");
+    let page_info = &jsn["data"]["gitRepos"]["pageInfo"];
+    assert_eq!(page_info["hasNextPage"], true);
+
+    let args = format!(
+        "(first: 1, after: \\\"{}\\\")",
+        page_info["endCursor"].as_str().unwrap()
+    );
+    let jsn = query(&GIT_REPOS.replace("ARGS", &args));
+    let repos = nodes(&jsn["data"]["gitRepos"]);
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0]["name"], "patchql-fork");
+    assert_eq!(jsn["data"]["gitRepos"]["pageInfo"]["hasNextPage"], false);
+}
+
+#[test]
+fn repo_updates_and_refs() {
+    let jsn = query(&REPO_UPDATES.replace("REPO_ID", REPO));
+    let repo = &jsn["data"]["repo"];
+
+    // Only the repo's author can push to it.
+    assert_eq!(repo["branches"], json!([{"name": "master", "sha1": "ccc"}]));
+    assert_eq!(
+        repo["refs"],
+        json!([
+            {"name": "refs/heads/master", "sha1": "ccc"},
+            {"name": "refs/tags/v1", "sha1": "ccc"}
+        ])
+    );
+
+    let updates = repo["updates"].as_array().unwrap();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(
+        updates[0]["refs"],
+        json!([
+            {"name": "refs/heads/master", "sha1": "ccc"},
+            {"name": "refs/heads/wip", "sha1": null},
+            {"name": "refs/tags/v1", "sha1": "ccc"}
+        ])
+    );
+    assert_eq!(
+        updates[1]["commits"],
+        json!([{"sha1": "aaa", "title": "Initial commit"}])
+    );
+}
+
+#[test]
+fn repo_issues() {
+    let jsn = query(&REPO_ISSUES.replace("REPO_ID", REPO).replace("OPEN", ""));
+    let issues = &jsn["data"]["repo"]["issues"];
+    assert_eq!(issues[0]["title"], "Add docs");
+    assert_eq!(issues[0]["isOpen"], true);
+
+    // Closed by a comment from its author. Reopening by anyone else is ignored.
+    assert_eq!(issues[1]["title"], "Crashes on startup");
+    assert_eq!(issues[1]["isOpen"], false);
+    assert_eq!(issues[1]["comments"], json!([{"text": "Fixed by ccc"}]));
+
+    let jsn = query(
+        &REPO_ISSUES
+            .replace("REPO_ID", REPO)
+            .replace("OPEN", "(open: false)"),
+    );
+    let issues = &jsn["data"]["repo"]["issues"];
+    assert_eq!(issues.as_array().unwrap().len(), 1);
+    assert_eq!(issues[0]["title"], "Crashes on startup");
+}
+
+#[test]
+fn repo_pull_requests() {
+    let jsn = query(&REPO_PULL_REQUESTS.replace("REPO_ID", REPO));
+    let pull_requests = &jsn["data"]["repo"]["pullRequests"];
+    assert_eq!(pull_requests.as_array().unwrap().len(), 1);
+
+    let pull_request = &pull_requests[0];
+    assert_eq!(pull_request["title"], "Faster queries");
+    assert_eq!(pull_request["author"]["id"], KATIE);
+    assert_eq!(pull_request["branch"], "master");
+    assert_eq!(pull_request["headRepo"]["name"], "patchql-fork");
+    assert_eq!(pull_request["headBranch"], "faster");
+    // Closed by the repo's author.
+    assert_eq!(pull_request["isOpen"], false);
+}
+
+#[test]
+fn repo_not_found() {
+    let jsn = query(
+        &REPO_ISSUES
+            .replace("REPO_ID", "%nope.sha256")
+            .replace("OPEN", ""),
+    );
+    assert_eq!(jsn["data"]["repo"], Value::Null);
+}
+
+const GIT_REPOS: &str = r##"
+{"query":"{\n  gitRepos ARGS {\n    totalCount\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    edges {\n      node {\n        id\n        name\n        author {\n          id\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const REPO_UPDATES: &str = r##"
+{"query":"{\n  repo(id: \"REPO_ID\") {\n    branches {\n      name\n      sha1\n    }\n    refs {\n      name\n      sha1\n    }\n    updates {\n      id\n      refs {\n        name\n        sha1\n      }\n      commits {\n        sha1\n        title\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const REPO_ISSUES: &str = r##"
+{"query":"{\n  repo(id: \"REPO_ID\") {\n    issues OPEN {\n      id\n      title\n      isOpen\n      comments {\n        text\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const REPO_PULL_REQUESTS: &str = r##"
+{"query":"{\n  repo(id: \"REPO_ID\") {\n    pullRequests {\n      title\n      author {\n        id\n      }\n      branch\n      headRepo {\n        name\n      }\n      headBranch\n      isOpen\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,129 @@
+// The diesel `table!` macro expands to impls inside functions.
+#![allow(non_local_definitions)]
+
+#[macro_use]
+extern crate diesel;
+#[macro_use]
+extern crate juniper;
+
+mod common;
+
+use common::{db_path, open, PROCESS};
+use diesel::prelude::*;
+use diesel::result::Error;
+use juniper::{FieldResult, RootNode};
+use serde_json::{from_str, Value};
+use ssb_patchql_core::db::Context;
+use ssb_patchql_core::graphql::db::DbMutation;
+use ssb_patchql_core::graphql::post::Post;
+use ssb_patchql_core::graphql::root::Query;
+use ssb_patchql_core::ssb_message::SsbMessage;
+use ssb_patchql_core::{MessageIndexer, Patchql};
+
+table! {
+    post_lengths (key_id) {
+        key_id -> Integer,
+        length -> Integer,
+    }
+}
+
+/// Indexes the length of every post's text.
+struct PostLengths;
+
+impl MessageIndexer for PostLengths {
+    fn name(&self) -> &str {
+        "post_lengths"
+    }
+    fn migrations(&self) -> &[&str] {
+        &["CREATE TABLE post_lengths (key_id INTEGER PRIMARY KEY, length INTEGER NOT NULL)"]
+    }
+    fn index(
+        &self,
+        connection: &SqliteConnection,
+        message: &SsbMessage,
+        key_id: i32,
+    ) -> Result<(), Error> {
+        if message.value.content["type"] != "post" {
+            return Ok(());
+        }
+        if let Some(text) = message.value.content["text"].as_str() {
+            diesel::insert_into(post_lengths::table)
+                .values((
+                    post_lengths::key_id.eq(key_id),
+                    post_lengths::length.eq(text.len() as i32),
+                ))
+                .execute(connection)?;
+        }
+        Ok(())
+    }
+}
+
+struct CustomQuery;
+
+graphql_object!(CustomQuery: Context |&self| {
+    /// The post with the longest text.
+    field longest_post(&executor) -> FieldResult<Option<Post>> {
+        let connection = executor.context().connection.get()?;
+        let key_id = post_lengths::table
+            .select(post_lengths::key_id)
+            .order(post_lengths::length.desc())
+            .first::<i32>(&connection)
+            .optional()?;
+
+        Ok(key_id.map(|key_id| Post{key_id, cursor: None}))
+    }
+    /// The core queries.
+    field patchql() -> Query {
+        Query
+    }
+});
+
+fn longest_post(patchql: &Patchql) -> Value {
+    let root_node = RootNode::new(CustomQuery, DbMutation::default());
+    let response = patchql.execute(LONGEST_POST, &root_node).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    jsn["data"].clone()
+}
+
+#[test]
+fn index_processed_messages() {
+    let patchql = Patchql::with_indexers(
+        "./misc/content_warnings.offset".to_owned(),
+        db_path("content_warnings"),
+        "".to_owned(),
+        "".to_owned(),
+        vec![Box::new(PostLengths)],
+    );
+    patchql.query(PROCESS).unwrap();
+
+    let data = longest_post(&patchql);
+    assert_eq!(
+        data["longestPost"]["text"],
+        "The finale twist was that it was all a dream"
+    );
+    assert!(data["patchql"]["dbCursor"].is_string());
+}
+
+#[test]
+fn new_indexers_catch_up() {
+    let db_path = db_path("content_warnings");
+    drop(open("content_warnings", &db_path, ""));
+
+    let patchql = Patchql::with_indexers(
+        "./misc/content_warnings.offset".to_owned(),
+        db_path,
+        "".to_owned(),
+        "".to_owned(),
+        vec![Box::new(PostLengths)],
+    );
+
+    let data = longest_post(&patchql);
+    assert_eq!(
+        data["longestPost"]["text"],
+        "The finale twist was that it was all a dream"
+    );
+}
+
+const LONGEST_POST: &str = r##"
+{"query":"{\n  longestPost{\n    text\n  }\n  patchql{\n    dbCursor\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,136 @@
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+
+fn patchql(db_path: &str) -> Patchql {
+    let offset_log_path = "./misc/out.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        db_path.to_owned(),
+        "".to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+    patchql
+}
+
+fn rels(jsn: &Value) -> Vec<&str> {
+    jsn["data"]["links"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["rel"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn links_to_message_by_rel() {
+    let patchql = patchql("/tmp/links_to_message_by_rel.sqlite");
+
+    let response = patchql.query(VOTES_ON_POST).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let edges = jsn["data"]["links"]["edges"].as_array().unwrap();
+
+    assert_eq!(edges.len(), 18);
+    assert!(edges
+        .iter()
+        .all(|edge| edge["node"]["from"]["type"] == "vote"));
+}
+
+#[test]
+fn links_from_message() {
+    let patchql = patchql("/tmp/links_from_message.sqlite");
+
+    let response = patchql.query(LINKS_FROM_GIT_UPDATE).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let targets = jsn["data"]["links"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["to"]["__typename"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    // Only the `link`s of the blobs are indexed, not the `repo` and `repoBranch` fields.
+    assert_eq!(rels(&jsn), vec!["indexes", "packs"]);
+    assert_eq!(targets, vec!["Blob", "Blob"]);
+}
+
+#[test]
+fn links_paginate_across_kinds() {
+    let patchql = patchql("/tmp/links_paginate_across_kinds.sqlite");
+
+    let response = patchql.query(FIRST_LINKS_FROM_REPLY).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    assert_eq!(rels(&jsn), vec!["branch", "root", "mentions"]);
+
+    let end_cursor = jsn["data"]["links"]["pageInfo"]["endCursor"]
+        .as_str()
+        .unwrap();
+    let response = patchql
+        .query(&NEXT_LINKS_FROM_REPLY.replace("END_CURSOR", end_cursor))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+
+    assert_eq!(rels(&jsn), vec!["mentions"; 4]);
+}
+
+#[test]
+fn references_are_only_mentions_from_other_threads() {
+    let offset_log_path = "./misc/references.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        "/tmp/references_are_only_mentions_from_other_threads.sqlite".to_owned(),
+        "".to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+
+    // Liked, tagged, given a title, edited and mentioned in a reply in its own thread.
+    let response = patchql
+        .query(&REFERENCES.replace(
+            "POST_ID",
+            "%BIZbgsIKKFT0hfUD+/WZB76CCKhQdOXz8pj3V8i81ok=.sha256",
+        ))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    assert_eq!(jsn["data"]["post"]["references"], serde_json::json!([]));
+
+    let response = patchql
+        .query(&REFERENCES.replace(
+            "POST_ID",
+            "%WlgFqjYKoecJPGj8wnHMw96iwQmB7pnUdzRqnw8vgWc=.sha256",
+        ))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    assert_eq!(
+        jsn["data"]["post"]["references"],
+        serde_json::json!([{"text": "Have you all seen how Alice planted her garden?"}])
+    );
+}
+
+const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
+
+const VOTES_ON_POST: &str = r##"
+{"query":"{\n  links(to: \"%c2qA4o+aiMzkx0QzV48WZRxv/VaiXXURbHSwFnL9rYo=.sha256\", rel: \"vote.link\", last: 50){\n    edges{\n      node{\n        rel\n        from{\n          type\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const LINKS_FROM_GIT_UPDATE: &str = r##"
+{"query":"{\n  links(from: \"%au94CCd+NbqFWt7WWlyxAbW9auNX9hgxSEmhJX0HIAc=.sha256\", first: 10){\n    edges{\n      node{\n        rel\n        to{\n          __typename\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const FIRST_LINKS_FROM_REPLY: &str = r##"
+{"query":"{\n  links(from: \"%WCQziqKuknTZvaPgl0JR0hMmR5GQ+fhJyu9BZpW95wI=.sha256\", first: 3){\n    edges{\n      node{\n        rel\n      }\n    }\n    pageInfo{\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const NEXT_LINKS_FROM_REPLY: &str = r##"
+{"query":"{\n  links(from: \"%WCQziqKuknTZvaPgl0JR0hMmR5GQ+fhJyu9BZpW95wI=.sha256\", after: \"END_CURSOR\", first: 10){\n    edges{\n      node{\n        rel\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const REFERENCES: &str = r##"
+{"query":"{\n  post(id: \"POST_ID\"){\n    references{\n      text\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
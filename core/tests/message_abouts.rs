@@ -0,0 +1,53 @@
+mod common;
+
+use serde_json::{json, Value};
+
+const POST: &str = "%p1ZBd868bL1ag8qYhDVv87djmG7hbPUKebZZ8RuLUPo=.sha256";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+const MIX: &str = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+
+fn query(query: &str) -> Value {
+    common::query("message_abouts", "", query)
+}
+
+#[test]
+fn post_and_thread_titles() {
+    // The author's latest title wins.
+    let jsn = query(&THREAD.replace("POST_ID", POST));
+    assert_eq!(jsn["data"]["thread"]["title"], "Offset logs");
+    assert_eq!(jsn["data"]["thread"]["root"]["title"], "Offset logs");
+}
+
+#[test]
+fn message_abouts() {
+    let jsn = query(&MESSAGE_ABOUTS.replace("MESSAGE_ID", POST));
+    let abouts = &jsn["data"]["message"]["abouts"];
+    assert_eq!(abouts["title"], "Offset logs");
+    assert_eq!(abouts["description"], Value::Null);
+    assert_eq!(abouts["imageLink"], "&offsetlog.sha256");
+    assert_eq!(
+        abouts["byOthers"],
+        json!([
+            {
+                "author": {"id": KATIE},
+                "title": "Katie's title",
+                "description": "A summary",
+                "imageLink": "&katie.sha256"
+            },
+            {
+                "author": {"id": MIX},
+                "title": null,
+                "description": "Mix's summary",
+                "imageLink": null
+            }
+        ])
+    );
+}
+
+const THREAD: &str = r##"
+{"query":"{\n  thread(rootId: \"POST_ID\") {\n    title\n    root {\n      title\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const MESSAGE_ABOUTS: &str = r##"
+{"query":"{\n  message(id: \"MESSAGE_ID\") {\n    abouts {\n      title\n      description\n      imageLink\n      byOthers {\n        author {\n          id\n        }\n        title\n        description\n        imageLink\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
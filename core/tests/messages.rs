@@ -0,0 +1,72 @@
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+
+fn patchql(db_path: &str) -> Patchql {
+    let offset_log_path = "./misc/out.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        db_path.to_owned(),
+        "".to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+    patchql
+}
+
+#[test]
+fn messages_by_type() {
+    let patchql = patchql("/tmp/messages_by_type.sqlite");
+
+    let response = patchql.query(GIT_REPOS).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let edges = jsn["data"]["messagesByType"]["edges"].as_array().unwrap();
+
+    assert_eq!(edges.len(), 8);
+    assert!(edges
+        .iter()
+        .all(|edge| edge["node"]["type"] == "git-repo"
+            && edge["node"]["content"]["type"] == "git-repo"));
+}
+
+#[test]
+fn messages_by_type_paginates() {
+    let patchql = patchql("/tmp/messages_by_type_paginates.sqlite");
+
+    let response = patchql.query(FIRST_GIT_REPOS).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let first_page = &jsn["data"]["messagesByType"];
+    assert_eq!(first_page["edges"].as_array().unwrap().len(), 5);
+    assert_eq!(first_page["totalCount"], 8);
+    assert_eq!(first_page["pageInfo"]["hasNextPage"], true);
+
+    let end_cursor = first_page["pageInfo"]["endCursor"].as_str().unwrap();
+    let response = patchql
+        .query(&NEXT_GIT_REPOS.replace("END_CURSOR", end_cursor))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let next_page = &jsn["data"]["messagesByType"];
+
+    assert_eq!(next_page["edges"].as_array().unwrap().len(), 3);
+    assert_eq!(next_page["pageInfo"]["hasNextPage"], false);
+    assert_eq!(next_page["pageInfo"]["hasPreviousPage"], true);
+}
+
+const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
+
+const GIT_REPOS: &str = r##"
+{"query":"{\n  messagesByType(messageType: \"git-repo\", last: 20){\n    edges{\n      node{\n        id\n        type\n        content\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const FIRST_GIT_REPOS: &str = r##"
+{"query":"{\n  messagesByType(messageType: \"git-repo\", first: 5){\n    totalCount\n    edges{\n      node{\n        id\n      }\n    }\n    pageInfo{\n      endCursor\n      hasNextPage\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const NEXT_GIT_REPOS: &str = r##"
+{"query":"{\n  messagesByType(messageType: \"git-repo\", after: \"END_CURSOR\", first: 5){\n    edges{\n      node{\n        id\n      }\n    }\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,133 @@
+use serde_json::{from_str, Value};
+use ssb_patchql_core::Patchql;
+
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+
+fn patchql(db_path: &str) -> Patchql {
+    let offset_log_path = "./misc/notifications.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        db_path.to_owned(),
+        PIET.to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+    patchql
+}
+
+#[test]
+fn notifications_for_current_author() {
+    let patchql = patchql("/tmp/notifications_for_current_author.sqlite");
+
+    let response = patchql.query(NOTIFICATIONS).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let notifications = &jsn["data"]["notifications"];
+
+    assert_eq!(notifications["totalCount"], 7);
+    assert_eq!(notifications["mentionCount"], 1);
+    assert_eq!(notifications["replyCount"], 3);
+    assert_eq!(notifications["likeCount"], 1);
+    assert_eq!(notifications["followerCount"], 1);
+    assert_eq!(notifications["blockCount"], 1);
+
+    let kinds = notifications["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["__typename"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec!["Mention", "Reply", "PostLike", "NewFollower", "NewBlock"]
+    );
+    assert_eq!(
+        notifications["edges"][0]["node"]["post"]["text"],
+        "Hi [@Piet]"
+    );
+    assert_eq!(notifications["edges"][1]["node"]["root"]["text"], "Hello");
+    assert_eq!(notifications["edges"][2]["node"]["author"]["name"], "Katie");
+    assert_eq!(notifications["edges"][4]["node"]["author"]["name"], "Mix");
+    assert_eq!(notifications["pageInfo"]["hasNextPage"], true);
+}
+
+#[test]
+fn notifications_after_cursor() {
+    let patchql = patchql("/tmp/notifications_after_cursor.sqlite");
+
+    let response = patchql.query(NOTIFICATIONS).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let end_cursor = jsn["data"]["notifications"]["pageInfo"]["endCursor"]
+        .as_str()
+        .unwrap();
+
+    let response = patchql
+        .query(&NOTIFICATIONS_AFTER.replace("END_CURSOR", end_cursor))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let notifications = &jsn["data"]["notifications"];
+
+    assert_eq!(notifications["totalCount"], 2);
+    assert_eq!(notifications["replyCount"], 2);
+    assert_eq!(notifications["mentionCount"], 0);
+
+    let texts = notifications["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["post"]["text"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, vec!["Before Piet", "After Piet"]);
+    assert_eq!(notifications["pageInfo"]["hasNextPage"], false);
+}
+
+#[test]
+fn mentions_notify_once_per_post() {
+    let offset_log_path = "./misc/double_mentions.offset".to_owned();
+    let patchql = Patchql::new(
+        offset_log_path,
+        "/tmp/mentions_notify_once_per_post.sqlite".to_owned(),
+        PIET.to_owned(),
+        "".to_owned(),
+    );
+    patchql.query(PROCESS).unwrap();
+
+    let response = patchql.query(&MENTIONS.replace("FIRST", "10")).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let notifications = &jsn["data"]["notifications"];
+
+    // The first post mentions Piet twice.
+    assert_eq!(notifications["mentionCount"], 2);
+    let texts = notifications["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["post"]["text"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, vec!["[@Piet] and [@piet] again", "Hi [@Piet]"]);
+
+    // A negative `first` doesn't lift the limit.
+    let response = patchql.query(&MENTIONS.replace("FIRST", "-1")).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    assert_eq!(jsn["data"]["notifications"]["edges"], serde_json::json!([]));
+    assert_eq!(jsn["data"]["notifications"]["mentionCount"], 2);
+}
+
+const PROCESS: &str = r##"
+{
+    "operationName":"process",
+    "variables":{"chunkSize":10000},
+    "query":
+        "mutation process($chunkSize: Int) {\n  process(chunkSize: $chunkSize) {\n    __typename\n    chunkSize\n    latestSequence\n  }\n}"}
+"##;
+
+const NOTIFICATIONS: &str = r##"
+{"query":"{\n  notifications(first: 5){\n    totalCount\n    mentionCount\n    replyCount\n    likeCount\n    followerCount\n    blockCount\n    edges{\n      cursor\n      node{\n        __typename\n        ... on Mention{\n          post{\n            text\n          }\n        }\n        ... on Reply{\n          root{\n            text\n          }\n        }\n        ... on PostLike{\n          author{\n            name\n          }\n        }\n        ... on NewBlock{\n          author{\n            name\n          }\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const NOTIFICATIONS_AFTER: &str = r##"
+{"query":"{\n  notifications(after: \"END_CURSOR\"){\n    totalCount\n    mentionCount\n    replyCount\n    edges{\n      node{\n        ... on Reply{\n          post{\n            text\n          }\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const MENTIONS: &str = r##"
+{"query":"{\n  notifications(first: FIRST){\n    mentionCount\n    edges{\n      node{\n        ... on Mention{\n          post{\n            text\n          }\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,147 @@
+mod common;
+
+use serde_json::{json, Value};
+
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+const MIX: &str = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+
+const LUNCH: &str = "%TavjWEVWj8tddtKheVoWUDVqU+VNyHDSr6y983ZZoLk=.sha256";
+
+fn query(query: &str) -> Value {
+    common::query("polls", PIET, query)
+}
+
+fn polls(jsn: &Value) -> Vec<&Value> {
+    jsn["data"]["polls"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| &edge["node"])
+        .collect()
+}
+
+fn results(poll: &Value) -> Vec<(&str, i64, Vec<&str>)> {
+    poll["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            let voters = result["voters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|voter| voter["id"].as_str().unwrap())
+                .collect();
+            (
+                result["option"].as_str().unwrap(),
+                result["total"].as_i64().unwrap(),
+                voters,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn newest_polls_first() {
+    let jsn = query(&POLLS.replace("ARGS", ""));
+    let polls = polls(&jsn);
+    assert_eq!(polls.len(), 2);
+    assert_eq!(jsn["data"]["polls"]["totalCount"], 2);
+
+    let printer = polls[0];
+    assert_eq!(printer["title"], "Buy a printer");
+    assert_eq!(printer["pollType"], "PROPOSAL");
+    assert_eq!(printer["isClosed"], false);
+    assert_eq!(
+        printer["options"],
+        json!(["Agree", "Disagree", "Abstain", "Block"])
+    );
+    assert_eq!(printer["myPosition"], Value::Null);
+
+    let lunch = polls[1];
+    assert_eq!(lunch["id"], LUNCH);
+    assert_eq!(lunch["author"]["id"], PIET);
+    assert_eq!(lunch["pollType"], "DOT");
+    assert_eq!(lunch["closesAt"], 1583841600000.0);
+    assert_eq!(lunch["isClosed"], true);
+    assert_eq!(lunch["options"], json!(["Pizza", "Soup", "Salad"]));
+}
+
+#[test]
+fn polls_before_cursor() {
+    let jsn = query(&POLLS.replace("ARGS", "(last: 1)"));
+    assert_eq!(polls(&jsn)[0]["title"], "Buy a printer");
+    let page_info = &jsn["data"]["polls"]["pageInfo"];
+    assert_eq!(page_info["hasPreviousPage"], true);
+
+    let args = format!(
+        "(last: 1, before: \\\"{}\\\")",
+        page_info["endCursor"].as_str().unwrap()
+    );
+    let jsn = query(&POLLS.replace("ARGS", &args));
+    assert_eq!(polls(&jsn)[0]["id"], LUNCH);
+    assert_eq!(jsn["data"]["polls"]["pageInfo"]["hasPreviousPage"], false);
+}
+
+#[test]
+fn latest_valid_positions_win() {
+    let jsn = query(&POLL.replace("ID", LUNCH));
+    let lunch = &jsn["data"]["poll"];
+
+    // Katie changed her mind. Mix's position after the poll closed, Dan's that was backdated to
+    // before it closed but arrived after, and Piet's with too many dots are rejected.
+    assert_eq!(
+        results(lunch),
+        vec![
+            ("Pizza", 1, vec![PIET]),
+            ("Soup", 2, vec![MIX, KATIE]),
+            ("Salad", 1, vec![KATIE]),
+        ]
+    );
+
+    let authors = lunch["positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|position| position["author"]["id"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(authors, vec![MIX, KATIE, PIET]);
+
+    assert_eq!(lunch["myPosition"]["reason"], "Pizza Friday");
+    assert_eq!(
+        lunch["myPosition"]["choices"],
+        json!([{"option": "Pizza", "value": 1}])
+    );
+}
+
+#[test]
+fn proposal_results() {
+    let jsn = query(&POLLS.replace("ARGS", ""));
+    let printer = polls(&jsn)[0];
+
+    assert_eq!(
+        results(printer),
+        vec![
+            ("Agree", 1, vec![MIX]),
+            ("Disagree", 0, vec![]),
+            ("Abstain", 0, vec![]),
+            ("Block", 1, vec![KATIE]),
+        ]
+    );
+    assert_eq!(printer["positions"][0]["reason"], "We print nothing");
+}
+
+#[test]
+fn missing_poll() {
+    let jsn = query(&POLL.replace("ID", "%nope.sha256"));
+    assert_eq!(jsn["data"]["poll"], Value::Null);
+}
+
+const POLLS: &str = r##"
+{"query":"{\n  polls ARGS {\n    totalCount\n    pageInfo {\n      hasPreviousPage\n      endCursor\n    }\n    edges {\n      node {\n        id\n        author {\n          id\n        }\n        title\n        pollType\n        closesAt\n        isClosed\n        options\n        results {\n          option\n          total\n          voters {\n            id\n          }\n        }\n        positions {\n          author {\n            id\n          }\n          reason\n        }\n        myPosition {\n          reason\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const POLL: &str = r##"
+{"query":"{\n  poll(id: \"ID\") {\n    results {\n      option\n      total\n      voters {\n        id\n      }\n    }\n    positions {\n      author {\n        id\n      }\n    }\n    myPosition {\n      reason\n      choices {\n        option\n        value\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
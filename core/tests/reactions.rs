@@ -0,0 +1,52 @@
+mod common;
+
+use serde_json::Value;
+
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+const KATIE: &str = "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519";
+const MIX: &str = "@U5GvOKP/YUza9k53DSXxT0mk3PIrnyAmessvNfZl5E0=.ed25519";
+
+fn post() -> Value {
+    let jsn = common::query("reactions", PIET, POST);
+    jsn["data"]["posts"]["edges"][0]["node"].clone()
+}
+
+#[test]
+fn reactions_grouped_by_expression() {
+    let post = post();
+    let reactions = post["reactions"].as_array().unwrap();
+
+    // Katie changed her mind from "Like" to "Dig".
+    assert_eq!(reactions.len(), 2);
+    assert_eq!(reactions[0]["expression"], "🌻");
+    assert_eq!(reactions[0]["count"], 2);
+    assert_eq!(reactions[0]["authors"][0]["id"], MIX);
+    assert_eq!(reactions[0]["authors"][1]["id"], PIET);
+    assert_eq!(reactions[0]["reactedByMe"], true);
+    assert_eq!(reactions[1]["expression"], "Dig");
+    assert_eq!(reactions[1]["count"], 1);
+    assert_eq!(reactions[1]["authors"][0]["id"], KATIE);
+    assert_eq!(reactions[1]["reactedByMe"], false);
+}
+
+#[test]
+fn likes_have_expressions() {
+    let post = post();
+    let katies_like = post["likes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|like| like["author"]["id"] == KATIE)
+        .unwrap();
+
+    assert_eq!(katies_like["expression"], "Dig");
+    assert_eq!(katies_like["timestamp"], 1582500004000.0);
+    assert_eq!(
+        katies_like["messageId"],
+        "%y/weZqZAh9D0PouLCZ+CLkkE3jIxjiv2gk8Y6CG5f1w=.sha256"
+    );
+}
+
+const POST: &str = r##"
+{"query":"{\n  posts{\n    edges{\n      node{\n        likes{\n          author{\n            id\n          }\n          expression\n          timestamp\n          messageId\n        }\n        reactions{\n          expression\n          count\n          authors{\n            id\n          }\n          reactedByMe\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{db_path, open, run};
+use diesel::prelude::*;
+use serde_json::Value;
+
+fn root_texts(jsn: &Value) -> Vec<String> {
+    jsn["data"]["threads"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["root"]["text"].as_str().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn mark_threads_read_and_unread() {
+    let patchql = open("bumped_thread", &db_path("bumped_thread"), "");
+
+    let jsn = run(&patchql, THREADS);
+    assert_eq!(root_texts(&jsn), vec!["Thread A", "Thread C", "Thread B"]);
+    let thread_a = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread_a["unreadCount"], 2);
+    assert_eq!(thread_a["root"]["isRead"], false);
+
+    let root_id = thread_a["root"]["id"].as_str().unwrap().to_owned();
+    let reply_id = thread_a["replies"]["edges"][0]["node"]["id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+    // Marking the root marks the whole thread.
+    run(&patchql, &MARK_READ.replace("ID", &root_id));
+
+    let jsn = run(&patchql, THREADS);
+    let thread_a = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread_a["unreadCount"], 0);
+    assert_eq!(thread_a["root"]["isRead"], true);
+    assert_eq!(thread_a["replies"]["edges"][0]["node"]["isRead"], true);
+
+    let unread = run(&patchql, &THREADS_UNREAD.replace("UNREAD", "true"));
+    assert_eq!(root_texts(&unread), vec!["Thread C", "Thread B"]);
+    let read = run(&patchql, &THREADS_UNREAD.replace("UNREAD", "false"));
+    assert_eq!(root_texts(&read), vec!["Thread A"]);
+
+    run(&patchql, &MARK_UNREAD.replace("ID", &reply_id));
+
+    let jsn = run(&patchql, THREADS);
+    let thread_a = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread_a["unreadCount"], 1);
+    assert_eq!(thread_a["root"]["isRead"], true);
+    assert_eq!(thread_a["replies"]["edges"][0]["node"]["isRead"], false);
+
+    let unread = run(&patchql, &THREADS_UNREAD.replace("UNREAD", "true"));
+    assert_eq!(
+        root_texts(&unread),
+        vec!["Thread A", "Thread C", "Thread B"]
+    );
+}
+
+#[test]
+fn read_state_survives_rebuilding_the_db() {
+    let db_path = db_path("bumped_thread");
+    let patchql = open("bumped_thread", &db_path, "");
+    let jsn = run(&patchql, THREADS);
+    let root_id = jsn["data"]["threads"]["edges"][1]["node"]["root"]["id"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    run(&patchql, &MARK_READ.replace("ID", &root_id));
+    drop(patchql);
+
+    // Forget the latest migration, like an older version of the db, so it has to be rebuilt.
+    let connection = SqliteConnection::establish(&db_path).unwrap();
+    connection
+        .execute(
+            "DELETE FROM __diesel_schema_migrations
+             WHERE version = (SELECT MAX(version) FROM __diesel_schema_migrations)",
+        )
+        .unwrap();
+    drop(connection);
+
+    let patchql = open("bumped_thread", &db_path, "");
+    let read = run(&patchql, &THREADS_UNREAD.replace("UNREAD", "false"));
+    assert_eq!(root_texts(&read), vec!["Thread C"]);
+}
+
+#[test]
+fn mark_a_long_thread_read_and_unread() {
+    // More replies than sqlite can bind variables in one statement.
+    let patchql = open("long_thread", &db_path("long_thread"), "");
+
+    let jsn = run(&patchql, THREADS);
+    let thread = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread["unreadCount"], 1101);
+    let root_id = thread["root"]["id"].as_str().unwrap().to_owned();
+
+    run(&patchql, &MARK_READ.replace("ID", &root_id));
+    let jsn = run(&patchql, THREADS);
+    let thread = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread["unreadCount"], 0);
+
+    run(&patchql, &MARK_UNREAD.replace("ID", &root_id));
+    let jsn = run(&patchql, THREADS);
+    let thread = &jsn["data"]["threads"]["edges"][0]["node"];
+    assert_eq!(thread["unreadCount"], 1101);
+}
+
+const MARK_READ: &str = r##"
+{
+    "query":
+        "mutation {\n  markRead(ids: [\"ID\"])\n}"}
+"##;
+
+const MARK_UNREAD: &str = r##"
+{
+    "query":
+        "mutation {\n  markUnread(ids: [\"ID\"])\n}"}
+"##;
+
+const THREADS: &str = r##"
+{
+    "query":
+        "{\n  threads(orderBy: LAST_ACTIVITY) {\n    edges {\n      node {\n        unreadCount\n        root {\n          id\n          text\n          isRead\n        }\n        replies {\n          edges {\n            node {\n              id\n              isRead\n            }\n          }\n        }\n      }\n    }\n  }\n}"}
+"##;
+
+const THREADS_UNREAD: &str = r##"
+{
+    "query":
+        "{\n  threads(orderBy: LAST_ACTIVITY, unread: UNREAD) {\n    edges {\n      node {\n        root {\n          text\n        }\n      }\n    }\n  }\n}"}
+"##;
@@ -0,0 +1,124 @@
+mod common;
+
+use serde_json::Value;
+
+const RECIPE: &str = "%gvuGr4z8FLd+YmYtKtI3Q2JXqqRalgq4BpEwifSQghg=.sha256";
+const BOOKMARKS: &str = "%D30AmVor2KnK2wJyCaIIrvczg6i2FuQT+GZ4E7cM5BE=.sha256";
+const PIET: &str = "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519";
+
+fn query(query: &str) -> Value {
+    common::query("tags", "", query)
+}
+
+fn names(tags: &Value) -> Vec<&str> {
+    tags.as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| tag["name"].as_str().unwrap())
+        .collect()
+}
+
+fn nodes(connection: &Value) -> Value {
+    connection["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"].clone())
+        .collect()
+}
+
+fn texts(posts: &Value) -> Vec<&str> {
+    posts["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["text"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn tags_by_author() {
+    let jsn = query(&TAGS.replace("ARGS", ""));
+    assert_eq!(
+        names(&nodes(&jsn["data"]["tags"])),
+        vec!["recipes", "bookmarks"]
+    );
+    assert_eq!(jsn["data"]["tags"]["totalCount"], 2);
+
+    let jsn = query(&TAGS.replace("ARGS", &format!("(author: \\\"{}\\\")", PIET)));
+    let tags = &nodes(&jsn["data"]["tags"]);
+    assert_eq!(names(tags), vec!["bookmarks"]);
+    assert_eq!(tags[0]["id"], BOOKMARKS);
+    assert_eq!(tags[0]["author"]["id"], PIET);
+}
+
+#[test]
+fn tags_before_cursor() {
+    let jsn = query(&TAGS.replace("ARGS", "(last: 1)"));
+    assert_eq!(names(&nodes(&jsn["data"]["tags"])), vec!["recipes"]);
+    let page_info = &jsn["data"]["tags"]["pageInfo"];
+    assert_eq!(page_info["hasPreviousPage"], true);
+
+    let args = format!(
+        "(last: 1, before: \\\"{}\\\")",
+        page_info["endCursor"].as_str().unwrap()
+    );
+    let jsn = query(&TAGS.replace("ARGS", &args));
+    assert_eq!(names(&nodes(&jsn["data"]["tags"])), vec!["bookmarks"]);
+    assert_eq!(jsn["data"]["tags"]["pageInfo"]["hasPreviousPage"], false);
+}
+
+#[test]
+fn post_tags() {
+    let jsn = query(&POST_TAGS.replace("POST_ID", RECIPE));
+    assert_eq!(
+        names(&jsn["data"]["thread"]["root"]["tags"]),
+        vec!["bookmarks", "recipes"]
+    );
+}
+
+#[test]
+fn tagged_posts() {
+    // Untagged posts are left out.
+    let jsn = query(
+        &TAGGED_POSTS
+            .replace("TAG_ID", BOOKMARKS)
+            .replace("PAGE", ""),
+    );
+    let posts = &jsn["data"]["taggedPosts"];
+    assert_eq!(posts["totalCount"], 2);
+    assert_eq!(
+        texts(posts),
+        vec!["A great soup recipe", "The patchql docs"]
+    );
+
+    let jsn = query(
+        &TAGGED_POSTS
+            .replace("TAG_ID", BOOKMARKS)
+            .replace("PAGE", ", first: 1"),
+    );
+    let posts = &jsn["data"]["taggedPosts"];
+    assert_eq!(texts(posts), vec!["The patchql docs"]);
+    assert_eq!(posts["pageInfo"]["hasNextPage"], true);
+
+    let end_cursor = posts["pageInfo"]["endCursor"].as_str().unwrap();
+    let jsn = query(&TAGGED_POSTS.replace("TAG_ID", BOOKMARKS).replace(
+        "PAGE",
+        &format!(", first: 1, after: \\\"{}\\\"", end_cursor),
+    ));
+    let posts = &jsn["data"]["taggedPosts"];
+    assert_eq!(texts(posts), vec!["A great soup recipe"]);
+    assert_eq!(posts["pageInfo"]["hasNextPage"], false);
+}
+
+const TAGS: &str = r##"
+{"query":"{\n  tags ARGS {\n    totalCount\n    pageInfo {\n      hasPreviousPage\n      endCursor\n    }\n    edges {\n      node {\n        id\n        name\n        author {\n          id\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const POST_TAGS: &str = r##"
+{"query":"{\n  thread(rootId: \"POST_ID\") {\n    root {\n      tags {\n        name\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const TAGGED_POSTS: &str = r##"
+{"query":"{\n  taggedPosts(tagId: \"TAG_ID\"PAGE) {\n    totalCount\n    pageInfo {\n      hasNextPage\n      endCursor\n    }\n    edges {\n      node {\n        text\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
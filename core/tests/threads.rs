@@ -1,5 +1,4 @@
 use serde_json::{from_str, Value};
-use ssb_patchql_core;
 use ssb_patchql_core::Patchql;
 
 #[test]
@@ -9,7 +8,7 @@ fn first() {
 
     let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
 
-    let process_response = patchql.query(PROCESS).unwrap();
+    patchql.query(PROCESS).unwrap();
     let response = patchql.query(THREADS_FIRST).unwrap();
     let jsn: Value = from_str(&response).unwrap();
     assert_eq!(
@@ -20,6 +19,301 @@ fn first() {
     );
 }
 
+#[test]
+fn page_info() {
+    let offset_log_path = "./misc/fifty_replies.offset".to_owned();
+    let db_path = "/tmp/page_info.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+    let response = patchql.query(THREADS_FIRST_PAGE_INFO).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let threads = &jsn["data"]["threads"];
+
+    assert_eq!(threads["totalCount"], 50);
+    assert_eq!(threads["pageInfo"]["hasNextPage"], true);
+    assert_eq!(threads["pageInfo"]["hasPreviousPage"], false);
+
+    let end_cursor = threads["pageInfo"]["endCursor"].as_str().unwrap();
+    let response = patchql
+        .query(&THREADS_REST_PAGE_INFO.replace("END_CURSOR", end_cursor))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let threads = &jsn["data"]["threads"];
+
+    assert_eq!(threads["edges"].as_array().unwrap().len(), 49);
+    assert_eq!(threads["totalCount"], 50);
+    assert_eq!(threads["pageInfo"]["hasNextPage"], false);
+    assert_eq!(threads["pageInfo"]["hasPreviousPage"], true);
+}
+
+#[test]
+fn same_asserted_timestamps_paginate() {
+    let offset_log_path = "./misc/same_timestamps.offset".to_owned();
+    let db_path = "/tmp/same_asserted_timestamps_paginate.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let mut texts = Vec::new();
+    let mut after: Option<String> = None;
+
+    loop {
+        let query = match &after {
+            Some(cursor) => THREADS_ASSERTED_AFTER.replace("END_CURSOR", cursor),
+            None => THREADS_ASSERTED_FIRST.to_owned(),
+        };
+        let response = patchql.query(&query).unwrap();
+        let jsn: Value = from_str(&response).unwrap();
+        let threads = &jsn["data"]["threads"];
+
+        threads["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .for_each(|edge| texts.push(edge["node"]["root"]["text"].as_str().unwrap().to_owned()));
+
+        if threads["pageInfo"]["hasNextPage"] == false {
+            break;
+        }
+        after = threads["pageInfo"]["endCursor"]
+            .as_str()
+            .map(|cursor| cursor.to_owned());
+    }
+
+    assert_eq!(
+        texts,
+        (1..=6)
+            .map(|i| format!("Same time {}", i))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn old_cursors_still_decode() {
+    let offset_log_path = "./misc/same_timestamps.offset".to_owned();
+    let db_path = "/tmp/old_cursors_still_decode.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    // A single i64 cursor, just before the asserted timestamp of all the posts.
+    let response = patchql
+        .query(&THREADS_ASSERTED_AFTER.replace("END_CURSOR", "3yi2A24BAAA="))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+
+    assert_eq!(jsn["data"]["threads"]["edges"].as_array().unwrap().len(), 2);
+    assert_eq!(jsn["data"]["threads"]["totalCount"], 6);
+}
+
+#[test]
+fn threads_in_date_range() {
+    let offset_log_path = "./misc/fifty_replies.offset".to_owned();
+    let db_path = "/tmp/threads_in_date_range.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let root_texts = |query: &str| -> Vec<String> {
+        let response = patchql.query(query).unwrap();
+        let jsn: Value = from_str(&response).unwrap();
+        jsn["data"]["threads"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["root"]["text"].as_str().unwrap().to_owned())
+            .collect()
+    };
+
+    assert_eq!(root_texts(THREADS_SINCE), vec!["Root 50", "Root 49"]);
+    assert_eq!(
+        root_texts(THREADS_ACTIVE_SINCE),
+        vec!["Root 50", "Root 49", "Root 48"]
+    );
+    assert_eq!(root_texts(THREADS_ACTIVE_BETWEEN), vec!["Root 48"]);
+}
+
+#[test]
+fn posts_in_date_range() {
+    let offset_log_path = "./misc/fifty_replies.offset".to_owned();
+    let db_path = "/tmp/posts_in_date_range.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let response = patchql.query(POSTS_BETWEEN).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let texts = jsn["data"]["posts"]["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["node"]["text"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(texts, vec!["Root 49", "Root 48, Reply 3"]);
+}
+
+#[test]
+fn threads_by_last_activity() {
+    let offset_log_path = "./misc/bumped_thread.offset".to_owned();
+    let db_path = "/tmp/threads_by_last_activity.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let threads = |query: &str| -> Value {
+        let response = patchql.query(query).unwrap();
+        let jsn: Value = from_str(&response).unwrap();
+        jsn["data"]["threads"].clone()
+    };
+    let root_texts = |threads: &Value| -> Vec<String> {
+        threads["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["root"]["text"].as_str().unwrap().to_owned())
+            .collect()
+    };
+
+    // Thread A was bumped by its reply.
+    let newest = threads(THREADS_BY_ACTIVITY_LAST);
+    assert_eq!(
+        root_texts(&newest),
+        vec!["Thread A", "Thread C", "Thread B"]
+    );
+
+    let oldest = threads(THREADS_BY_ACTIVITY_FIRST);
+    assert_eq!(root_texts(&oldest), vec!["Thread B", "Thread C"]);
+    assert_eq!(oldest["pageInfo"]["hasNextPage"], true);
+
+    let end_cursor = oldest["pageInfo"]["endCursor"].as_str().unwrap();
+    let rest = threads(&THREADS_BY_ACTIVITY_AFTER.replace("END_CURSOR", end_cursor));
+    assert_eq!(root_texts(&rest), vec!["Thread A"]);
+    assert_eq!(rest["pageInfo"]["hasNextPage"], false);
+    assert_eq!(rest["pageInfo"]["hasPreviousPage"], true);
+}
+
+#[test]
+fn thread_replies() {
+    let offset_log_path = "./misc/fifty_replies.offset".to_owned();
+    let db_path = "/tmp/thread_replies.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let response = patchql.query(THREAD_REPLIES_FIRST).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let thread = &jsn["data"]["thread"];
+
+    assert_eq!(thread["replyCount"], 3);
+    assert_eq!(thread["lastReplyAt"], 1572020259275.0);
+    assert_eq!(
+        thread["participants"],
+        serde_json::json!([
+            {"id": "@x8KXiOdQyWeJncR0Q65Y9IEyKF0Nmq67/5WGcONf3CM=.ed25519"},
+            {"id": "@F/s626zts3RI1HsR8Hd1XlvDYQK2sOIFrwc13fOA8iE=.ed25519"}
+        ])
+    );
+
+    let replies = &thread["replies"];
+    assert_eq!(replies["totalCount"], 3);
+    assert_eq!(replies["edges"][0]["node"]["text"], "Root 1, Reply 1");
+    assert_eq!(replies["edges"][1]["node"]["text"], "Root 1, Reply 2");
+    assert_eq!(replies["pageInfo"]["hasNextPage"], true);
+
+    let end_cursor = replies["pageInfo"]["endCursor"].as_str().unwrap();
+    let response = patchql
+        .query(&THREAD_REPLIES_AFTER.replace("END_CURSOR", end_cursor))
+        .unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let replies = &jsn["data"]["thread"]["replies"];
+
+    assert_eq!(replies["edges"].as_array().unwrap().len(), 1);
+    assert_eq!(replies["edges"][0]["node"]["text"], "Root 1, Reply 3");
+    assert_eq!(replies["pageInfo"]["hasNextPage"], false);
+    assert_eq!(replies["pageInfo"]["hasPreviousPage"], true);
+
+    let response = patchql.query(THREAD_REPLIES_NEWEST).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let replies = &jsn["data"]["thread"]["replies"];
+
+    assert_eq!(replies["edges"][0]["node"]["text"], "Root 1, Reply 3");
+    assert_eq!(replies["pageInfo"]["hasPreviousPage"], true);
+
+    // Without any arguments the thread reads from the start.
+    let response = patchql.query(THREAD_REPLIES_DEFAULT).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let replies = &jsn["data"]["thread"]["replies"];
+
+    assert_eq!(
+        replies["edges"],
+        serde_json::json!([
+            {"node": {"text": "Root 1, Reply 1"}},
+            {"node": {"text": "Root 1, Reply 2"}},
+            {"node": {"text": "Root 1, Reply 3"}}
+        ])
+    );
+    assert_eq!(replies["pageInfo"]["hasNextPage"], false);
+    assert_eq!(replies["pageInfo"]["hasPreviousPage"], false);
+}
+
+#[test]
+fn thread_reply_tree() {
+    let offset_log_path = "./misc/fifty_replies.offset".to_owned();
+    let db_path = "/tmp/thread_reply_tree.sqlite".to_owned();
+
+    let patchql = Patchql::new(offset_log_path, db_path, "".to_owned(), "".to_owned());
+
+    patchql.query(PROCESS).unwrap();
+
+    let response = patchql.query(THREAD_REPLY_TREE).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let thread = &jsn["data"]["thread"];
+
+    // Each reply branches from the one before it.
+    assert_eq!(
+        thread["replyTree"],
+        serde_json::json!([{
+            "post": {"text": "Root 1, Reply 1"},
+            "depth": 0,
+            "children": [{
+                "post": {"text": "Root 1, Reply 2"},
+                "depth": 1,
+                "children": [{
+                    "post": {"text": "Root 1, Reply 3"},
+                    "depth": 2,
+                    "children": []
+                }]
+            }]
+        }])
+    );
+    assert_eq!(
+        thread["root"]["directReplies"],
+        serde_json::json!([{"text": "Root 1, Reply 1"}])
+    );
+
+    let response = patchql.query(POST_BRANCHES).unwrap();
+    let jsn: Value = from_str(&response).unwrap();
+    let post = &jsn["data"]["post"];
+
+    assert_eq!(
+        post["replyTo"],
+        serde_json::json!([{"text": "Root 1, Reply 1"}])
+    );
+    assert_eq!(
+        post["directReplies"],
+        serde_json::json!([{"text": "Root 1, Reply 3"}])
+    );
+}
+
 const PROCESS: &str = r##"
 {
     "operationName":"process",
@@ -29,5 +323,73 @@ const PROCESS: &str = r##"
 "##;
 
 const THREADS_FIRST: &str = r##"
-{"query":"{\n\tthreads(first: 1 ){\n    totalCount\n    edges{\n      cursor\n      node{        \n        root{\n          id\n          text\n          author{\n            name\n          }\n        }\n        replies{\n          id\n          text\n          author{\n            name\n          }\n        \n      \t}\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+{"query":"{\n\tthreads(first: 1 ){\n    totalCount\n    edges{\n      cursor\n      node{        \n        root{\n          id\n          text\n          author{\n            name\n          }\n        }\n        replies{\n          edges{\n            node{\n              id\n              text\n              author{\n                name\n              }\n            }\n          }\n      \t}\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_FIRST_PAGE_INFO: &str = r##"
+{"query":"{\n  threads(first: 1){\n    totalCount\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_REST_PAGE_INFO: &str = r##"
+{"query":"{\n  threads(after: \"END_CURSOR\", first: 100){\n    totalCount\n    edges{\n      cursor\n    }\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_ASSERTED_FIRST: &str = r##"
+{"query":"{\n  threads(first: 2, orderBy: ASSERTED){\n    totalCount\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_ASSERTED_AFTER: &str = r##"
+{"query":"{\n  threads(after: \"END_CURSOR\", first: 2, orderBy: ASSERTED){\n    totalCount\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_SINCE: &str = r##"
+{"query":"{\n  threads(since: 1572020279000, orderBy: ASSERTED){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_ACTIVE_SINCE: &str = r##"
+{"query":"{\n  threads(activeSince: 1572020279000, orderBy: ASSERTED){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_ACTIVE_BETWEEN: &str = r##"
+{"query":"{\n  threads(activeSince: 1572020279000, activeUntil: 1572020279200, orderBy: ASSERTED){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const POSTS_BETWEEN: &str = r##"
+{"query":"{\n  posts(since: 1572020279000, until: 1572020279200){\n    edges{\n      node{\n        text\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_BY_ACTIVITY_LAST: &str = r##"
+{"query":"{\n  threads(last: 10, orderBy: LAST_ACTIVITY){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_BY_ACTIVITY_FIRST: &str = r##"
+{"query":"{\n  threads(first: 2, orderBy: LAST_ACTIVITY){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREADS_BY_ACTIVITY_AFTER: &str = r##"
+{"query":"{\n  threads(after: \"END_CURSOR\", first: 2, orderBy: LAST_ACTIVITY){\n    edges{\n      node{\n        root{\n          text\n        }\n      }\n    }\n    pageInfo{\n      hasNextPage\n      hasPreviousPage\n      endCursor\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREAD_REPLIES_FIRST: &str = r##"
+{"query":"{\n  thread(rootId: \"%622gQex5o2tvXbFvlruY5kT6/uz0dWu9onn4dpgN2iw=.sha256\"){\n    replyCount\n    lastReplyAt\n    participants{\n      id\n    }\n    replies(first: 2){\n      totalCount\n      edges{\n        node{\n          text\n        }\n      }\n      pageInfo{\n        hasNextPage\n        hasPreviousPage\n        endCursor\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREAD_REPLIES_AFTER: &str = r##"
+{"query":"{\n  thread(rootId: \"%622gQex5o2tvXbFvlruY5kT6/uz0dWu9onn4dpgN2iw=.sha256\"){\n    replyCount\n    replies(after: \"END_CURSOR\", first: 2){\n      totalCount\n      edges{\n        node{\n          text\n        }\n      }\n      pageInfo{\n        hasNextPage\n        hasPreviousPage\n        endCursor\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREAD_REPLIES_NEWEST: &str = r##"
+{"query":"{\n  thread(rootId: \"%622gQex5o2tvXbFvlruY5kT6/uz0dWu9onn4dpgN2iw=.sha256\"){\n    replyCount\n    replies(last: 1, orderBy: ASSERTED){\n      totalCount\n      edges{\n        node{\n          text\n        }\n      }\n      pageInfo{\n        hasNextPage\n        hasPreviousPage\n        endCursor\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREAD_REPLIES_DEFAULT: &str = r##"
+{"query":"{\n  thread(rootId: \"%622gQex5o2tvXbFvlruY5kT6/uz0dWu9onn4dpgN2iw=.sha256\"){\n    replies{\n      edges{\n        node{\n          text\n        }\n      }\n      pageInfo{\n        hasNextPage\n        hasPreviousPage\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const THREAD_REPLY_TREE: &str = r##"
+{"query":"{\n  thread(rootId: \"%622gQex5o2tvXbFvlruY5kT6/uz0dWu9onn4dpgN2iw=.sha256\"){\n    root{\n      directReplies{\n        text\n      }\n    }\n    replyTree{\n      post{\n        text\n      }\n      depth\n      children{\n        post{\n          text\n        }\n        depth\n        children{\n          post{\n            text\n          }\n          depth\n          children{\n            depth\n          }\n        }\n      }\n    }\n  }\n}\n","variables":null,"operationName":null}
+"##;
+
+const POST_BRANCHES: &str = r##"
+{"query":"{\n  post(id: \"%fqL+0Oci6zY+HZSHHoq0OatUZdJDntgBO5Pt17Yp1wU=.sha256\"){\n    replyTo{\n      text\n    }\n    directReplies{\n      text\n    }\n  }\n}\n","variables":null,"operationName":null}
 "##;